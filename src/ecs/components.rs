@@ -0,0 +1,89 @@
+use crate::framework::Pixels;
+use crate::math;
+use glam::{Vec2, Vec3};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+/// where an entity is, which way it faces and how big it is.
+#[derive(Clone)]
+pub struct Transform(pub math::Transform);
+
+impl Transform {
+    pub fn new(position: Vec3) -> Self {
+        Self(math::Transform::with_position(position))
+    }
+}
+
+impl Deref for Transform {
+    type Target = math::Transform;
+
+    fn deref(&self) -> &math::Transform {
+        &self.0
+    }
+}
+
+impl DerefMut for Transform {
+    fn deref_mut(&mut self) -> &mut math::Transform {
+        &mut self.0
+    }
+}
+
+/// how fast an entity moves, in tiles per second, for things that fly straight through the world
+/// rather than having a `Body`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Velocity(pub Vec3);
+
+/// how many seconds an entity has left before it's despawned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lifetime(pub f32);
+
+/// how many frames a directional sprite sheet has, one for every 45 degrees around the entity.
+const DIRECTIONS: usize = 8;
+
+/// draws an entity as a billboard standing where its transform is.
+#[derive(Clone)]
+pub struct Sprite {
+    /// shared between every entity that looks the same.
+    pub image: Rc<Pixels>,
+    /// how wide and tall the billboard is, in tiles.
+    pub size: Vec2,
+    /// whether the image is a sheet of 8 frames side by side, rather than one picture.
+    ///
+    /// the first frame shows the entity from the front, and each after it from 45 degrees further
+    /// around in yaw, so the entity seems to turn as the camera walks around it.
+    pub directional: bool,
+}
+
+impl Sprite {
+    pub fn new(image: Rc<Pixels>, size: Vec2) -> Self {
+        Self {
+            image,
+            size,
+            directional: false,
+        }
+    }
+
+    pub fn directional(image: Rc<Pixels>, size: Vec2) -> Self {
+        Self {
+            directional: true,
+            ..Self::new(image, size)
+        }
+    }
+
+    /// returns the part of the image to draw as (x, y, width, height) in pixels, for an entity
+    /// facing `yaw` degrees seen from `to_camera`, the direction from the entity to the camera.
+    pub fn frame(&self, yaw: f32, to_camera: Vec3) -> (usize, usize, usize, usize) {
+        let (width, height) = (self.image.width(), self.image.height());
+        if !self.directional {
+            return (0, 0, width, height);
+        }
+
+        // measured the same way as `math::Transform`'s yaw, where 0 faces along +z.
+        let camera_yaw = to_camera.x.atan2(to_camera.z).to_degrees();
+        let turns = ((camera_yaw - yaw) / (360.0 / DIRECTIONS as f32)).round() as i32;
+        let frame = turns.rem_euclid(DIRECTIONS as i32) as usize;
+
+        let frame_width = width / DIRECTIONS;
+        (frame * frame_width, 0, frame_width, height)
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+
+/// an id for something in the world which isn't a tile, like an item or a projectile.
+///
+/// ids are an index plus a generation: when an entity is despawned its index is reused, but with
+/// the next generation, so old ids to it stop working rather than referring to whatever replaced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+/// hands out entity ids, reusing the indices of despawned entities.
+pub(super) struct EntityAllocator {
+    /// the current generation of each index.
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    /// despawned indices waiting to be reused.
+    free: Vec<u32>,
+}
+
+impl EntityAllocator {
+    pub fn new() -> Self {
+        Self {
+            generations: Vec::new(),
+            alive: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn allocate(&mut self) -> Entity {
+        match self.free.pop() {
+            Some(index) => {
+                self.alive[index as usize] = true;
+                Entity {
+                    index,
+                    generation: self.generations[index as usize],
+                }
+            }
+            None => {
+                let index = self.generations.len() as u32;
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// frees an entity's index, returning false if it was already dead.
+    pub fn free(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        let index = entity.index();
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.push(entity.index);
+        true
+    }
+
+    /// frees every living entity, keeping their generations so none of their ids work again.
+    pub fn clear(&mut self) {
+        let living: Vec<_> = self.iter().collect();
+        for entity in living {
+            self.free(entity);
+        }
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index();
+        self.alive.get(index).copied().unwrap_or(false)
+            && self.generations[index] == entity.generation
+    }
+
+    /// returns every living entity, in index order.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive
+            .iter()
+            .enumerate()
+            .filter(|(_, alive)| **alive)
+            .map(|(index, _)| Entity {
+                index: index as u32,
+                generation: self.generations[index],
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.alive.len() - self.free.len()
+    }
+}
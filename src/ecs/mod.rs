@@ -0,0 +1,12 @@
+mod components;
+mod entity;
+mod query;
+mod registry;
+mod storage;
+pub mod systems;
+
+pub use components::{Lifetime, Sprite, Transform, Velocity};
+pub use entity::Entity;
+pub use query::Query;
+pub use registry::Registry;
+pub use systems::System;
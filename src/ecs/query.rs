@@ -0,0 +1,32 @@
+use super::{Entity, Registry};
+
+/// a combination of component types to look for, written as a tuple like `(Transform, Velocity)`,
+/// or `(Transform,)` for just one.
+pub trait Query {
+    /// returns the entities that might match: those with whichever of the components is rarest.
+    fn candidates(registry: &Registry) -> &[Entity];
+    /// returns true if an entity has every component.
+    fn matches(registry: &Registry, entity: Entity) -> bool;
+}
+
+macro_rules! impl_query {
+    ($($component:ident),+) => {
+        impl<$($component: 'static),+> Query for ($($component,)+) {
+            fn candidates(registry: &Registry) -> &[Entity] {
+                [$(registry.entities_with::<$component>()),+]
+                    .into_iter()
+                    .min_by_key(|entities| entities.len())
+                    .unwrap_or(&[])
+            }
+
+            fn matches(registry: &Registry, entity: Entity) -> bool {
+                $(registry.has::<$component>(entity))&&+
+            }
+        }
+    };
+}
+
+impl_query!(A);
+impl_query!(A, B);
+impl_query!(A, B, C);
+impl_query!(A, B, C, D);
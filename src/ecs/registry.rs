@@ -0,0 +1,205 @@
+use super::entity::EntityAllocator;
+use super::storage::{AnyStorage, Storage};
+use super::{Entity, Query};
+use std::any::TypeId;
+use std::collections::HashMap;
+
+/// every entity and their components.
+///
+/// any `'static` type can be a component, and an entity has at most one component of each type.
+/// systems find the entities they work on with `query`, then read and write their components with
+/// `get` and `get_mut`.
+pub struct Registry {
+    allocator: EntityAllocator,
+    storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self {
+            allocator: EntityAllocator::new(),
+            storages: HashMap::new(),
+        }
+    }
+
+    /// creates an entity with no components.
+    pub fn spawn(&mut self) -> Entity {
+        self.allocator.allocate()
+    }
+
+    /// destroys an entity and all of its components, returning false if it was already gone.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.allocator.free(entity) {
+            return false;
+        }
+
+        for storage in self.storages.values_mut() {
+            storage.remove_entity(entity);
+        }
+        true
+    }
+
+    /// destroys every entity.
+    pub fn clear(&mut self) {
+        self.allocator.clear();
+        for storage in self.storages.values_mut() {
+            storage.clear();
+        }
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.allocator.is_alive(entity)
+    }
+
+    /// returns how many entities are alive.
+    pub fn len(&self) -> usize {
+        self.allocator.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// returns every living entity.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.allocator.iter()
+    }
+
+    /// gives an entity a component, returning the component of the same type it replaced.
+    ///
+    /// does nothing if the entity has been despawned.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.storage_mut::<T>().insert(entity, component)
+    }
+
+    /// takes a component away from an entity, returning it.
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        self.storages
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()?
+            .remove(entity)
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        self.storage::<T>()?.get(entity)
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.storages
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()?
+            .get_mut(entity)
+    }
+
+    pub fn has<T: 'static>(&self, entity: Entity) -> bool {
+        self.get::<T>(entity).is_some()
+    }
+
+    /// returns every entity with a component of type `T`, alongside the component.
+    pub fn iter<T: 'static>(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.storage::<T>().into_iter().flat_map(Storage::iter)
+    }
+
+    pub fn iter_mut<T: 'static>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.storages
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<Storage<T>>())
+            .into_iter()
+            .flat_map(Storage::iter_mut)
+    }
+
+    /// returns every entity with all of the components in a tuple of types, e.g.
+    /// `registry.query::<(Transform, Velocity)>()`.
+    ///
+    /// the entities are collected up front, so the registry can be changed while going through them.
+    pub fn query<Q: Query>(&self) -> Vec<Entity> {
+        Q::candidates(self)
+            .iter()
+            .copied()
+            .filter(|entity| Q::matches(self, *entity))
+            .collect()
+    }
+
+    /// returns every entity with a component of type `T`, or none if there's no storage for it yet.
+    pub(super) fn entities_with<T: 'static>(&self) -> &[Entity] {
+        self.storage::<T>().map_or(&[], Storage::entities)
+    }
+
+    fn storage<T: 'static>(&self) -> Option<&Storage<T>> {
+        self.storages
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref::<Storage<T>>()
+    }
+
+    fn storage_mut<T: 'static>(&mut self) -> &mut Storage<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Storage::<T>::new()))
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()
+            .expect("storages are keyed by the type they store")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reused_indices_get_a_new_generation() {
+        let mut registry = Registry::new();
+        let old = registry.spawn();
+        registry.insert(old, 1u32);
+        assert!(registry.despawn(old));
+        assert!(!registry.despawn(old));
+
+        let new = registry.spawn();
+        assert_eq!(new.index(), old.index());
+        assert_ne!(new.generation(), old.generation());
+        assert!(!registry.is_alive(old));
+        assert_eq!(registry.get::<u32>(old), None);
+        assert_eq!(registry.get::<u32>(new), None);
+    }
+
+    #[test]
+    fn ids_from_before_a_clear_stay_dead() {
+        let mut registry = Registry::new();
+        let old = registry.spawn();
+        registry.insert(old, 1u32);
+        registry.clear();
+        assert!(registry.is_empty());
+
+        let new = registry.spawn();
+        registry.insert(new, 2u32);
+        assert_eq!(new.index(), old.index());
+        assert!(!registry.is_alive(old));
+        assert_eq!(registry.get::<u32>(old), None);
+        assert!(!registry.despawn(old));
+        assert_eq!(registry.get::<u32>(new), Some(&2));
+    }
+
+    #[test]
+    fn queries_find_entities_with_every_component() {
+        let mut registry = Registry::new();
+        let both = registry.spawn();
+        registry.insert(both, 1u32);
+        registry.insert(both, 1.0f32);
+        let one = registry.spawn();
+        registry.insert(one, 2u32);
+
+        assert_eq!(registry.query::<(u32, f32)>(), vec![both]);
+        assert_eq!(registry.query::<(u32,)>().len(), 2);
+    }
+}
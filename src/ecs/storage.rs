@@ -0,0 +1,154 @@
+use super::Entity;
+use std::any::Any;
+
+/// the components of one type, packed together for quick iteration.
+///
+/// a sparse set: components sit in a dense array alongside the entity each belongs to, and a
+/// sparse array indexed by entity index says where in the dense array each entity's component is.
+pub(super) struct Storage<T> {
+    components: Vec<T>,
+    entities: Vec<Entity>,
+    /// where each entity index's component is in `components`, if it has one.
+    indices: Vec<Option<usize>>,
+}
+
+impl<T> Storage<T> {
+    pub fn new() -> Self {
+        Self {
+            components: Vec::new(),
+            entities: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    /// adds or replaces an entity's component, returning the one it replaced.
+    pub fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        if let Some(dense) = self.dense_index(entity.index()) {
+            return Some(std::mem::replace(&mut self.components[dense], component));
+        }
+
+        if self.indices.len() <= entity.index() {
+            self.indices.resize(entity.index() + 1, None);
+        }
+        self.indices[entity.index()] = Some(self.components.len());
+        self.components.push(component);
+        self.entities.push(entity);
+        None
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let dense = self.dense_index(entity.index())?;
+        if self.entities[dense] != entity {
+            return None;
+        }
+
+        // moves the last component into the gap.
+        self.indices[entity.index()] = None;
+        let component = self.components.swap_remove(dense);
+        self.entities.swap_remove(dense);
+        if let Some(moved) = self.entities.get(dense) {
+            self.indices[moved.index()] = Some(dense);
+        }
+        Some(component)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        let dense = self.dense_index(entity.index())?;
+        (self.entities[dense] == entity).then(|| &self.components[dense])
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        let dense = self.dense_index(entity.index())?;
+        (self.entities[dense] == entity).then(|| &mut self.components[dense])
+    }
+
+    /// returns every entity with this component, in no particular order.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.entities.iter().copied().zip(self.components.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.entities
+            .iter()
+            .copied()
+            .zip(self.components.iter_mut())
+    }
+
+    fn dense_index(&self, index: usize) -> Option<usize> {
+        self.indices.get(index).copied().flatten()
+    }
+}
+
+/// a `Storage` of any type, so the registry can keep storages of every type together.
+pub(super) trait AnyStorage {
+    /// removes an entity's component, if it has one, e.g. when it's despawned.
+    fn remove_entity(&mut self, entity: Entity);
+    fn clear(&mut self);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> AnyStorage for Storage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn clear(&mut self) {
+        self.components.clear();
+        self.entities.clear();
+        self.indices.clear();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Registry;
+
+    #[test]
+    fn removing_keeps_the_moved_component_findable() {
+        let mut registry = Registry::new();
+        let entities: Vec<_> = (0..3).map(|_| registry.spawn()).collect();
+        let mut storage = Storage::new();
+        for (number, entity) in entities.iter().enumerate() {
+            storage.insert(*entity, number);
+        }
+
+        // the last component is swapped into the first one's place.
+        assert_eq!(storage.remove(entities[0]), Some(0));
+        assert_eq!(storage.get(entities[0]), None);
+        assert_eq!(storage.get(entities[1]), Some(&1));
+        assert_eq!(storage.get(entities[2]), Some(&2));
+        assert_eq!(storage.entities().len(), 2);
+
+        assert_eq!(storage.remove(entities[2]), Some(2));
+        assert_eq!(storage.get(entities[1]), Some(&1));
+    }
+
+    #[test]
+    fn old_generations_are_ignored() {
+        let mut registry = Registry::new();
+        let old = registry.spawn();
+        registry.despawn(old);
+        let new = registry.spawn();
+        assert_eq!(old.index(), new.index());
+
+        let mut storage = Storage::new();
+        storage.insert(new, 1);
+        assert_eq!(storage.get(old), None);
+        assert_eq!(storage.remove(old), None);
+        assert_eq!(storage.get(new), Some(&1));
+    }
+}
@@ -0,0 +1,49 @@
+use super::{Lifetime, Registry, Transform, Velocity};
+use crate::physics::Body;
+use crate::world::World;
+use glam::Vec2;
+
+/// updates some of the entities in a registry, given the world and how much time has passed.
+pub type System = fn(&mut Registry, &World, f32);
+
+/// moves entities along their velocity.
+pub fn movement(registry: &mut Registry, _world: &World, delta_time: f32) {
+    for entity in registry.query::<(Transform, Velocity)>() {
+        let Some(Velocity(velocity)) = registry.get::<Velocity>(entity).copied() else {
+            continue;
+        };
+        if let Some(transform) = registry.get_mut::<Transform>(entity) {
+            let position = transform.position() + velocity * delta_time;
+            transform.set_position(position);
+        }
+    }
+}
+
+/// steps entities' bodies against the world, and moves their transforms to match; should be run
+/// at a fixed rate, like the player's body.
+pub fn bodies(registry: &mut Registry, world: &World, delta_time: f32) {
+    for entity in registry.query::<(Body,)>() {
+        let Some(body) = registry.get_mut::<Body>(entity) else {
+            continue;
+        };
+        body.step(world, Vec2::ZERO, false, delta_time);
+
+        let position = body.position;
+        if let Some(transform) = registry.get_mut::<Transform>(entity) {
+            transform.set_position(position);
+        }
+    }
+}
+
+/// counts down lifetimes, despawning entities whose time is up.
+pub fn lifetimes(registry: &mut Registry, _world: &World, delta_time: f32) {
+    for entity in registry.query::<(Lifetime,)>() {
+        let Some(Lifetime(remaining)) = registry.get_mut::<Lifetime>(entity) else {
+            continue;
+        };
+        *remaining -= delta_time;
+        if *remaining <= 0.0 {
+            registry.despawn(entity);
+        }
+    }
+}
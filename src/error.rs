@@ -5,17 +5,33 @@ use std::{error::Error, fmt};
 pub enum ErrorType {
     WrongBufferSize(usize, usize),
     String(String),
-    RustNulError(std::ffi::NulError)
+    RustNulError(std::ffi::NulError),
+    Io(String),
+    CorruptFile(String),
+    UnsupportedVersion(u16, u16),
 }
 
 impl fmt::Display for ErrorType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             ErrorType::WrongBufferSize(expected, actual) => {
-                write!(f, "buffer was wrong size- expected size of {} but received {}", expected, actual)
-            },
+                write!(
+                    f,
+                    "buffer was wrong size- expected size of {} but received {}",
+                    expected, actual
+                )
+            }
             ErrorType::String(str) => write!(f, "{}", str),
-            ErrorType::RustNulError(..) => write!(f, "{}", self.source().unwrap()) 
+            ErrorType::RustNulError(..) => write!(f, "{}", self.source().unwrap()),
+            ErrorType::Io(message) => write!(f, "io error: {}", message),
+            ErrorType::CorruptFile(reason) => write!(f, "file is corrupt- {}", reason),
+            ErrorType::UnsupportedVersion(major, minor) => {
+                write!(
+                    f,
+                    "file format version {}.{} is newer than this build supports",
+                    major, minor
+                )
+            }
         }
     }
 }
@@ -25,7 +41,10 @@ impl Error for ErrorType {
         match *self {
             ErrorType::WrongBufferSize(..) => None,
             ErrorType::String(..) => None,
-            ErrorType::RustNulError(ref e) => Some(e)
+            ErrorType::RustNulError(ref e) => Some(e),
+            ErrorType::Io(..) => None,
+            ErrorType::CorruptFile(..) => None,
+            ErrorType::UnsupportedVersion(..) => None,
         }
     }
 }
@@ -41,4 +60,10 @@ macro_rules! impl_from_for_errortype {
 }
 
 impl_from_for_errortype!(String, String);
-impl_from_for_errortype!(std::ffi::NulError, RustNulError);
\ No newline at end of file
+impl_from_for_errortype!(std::ffi::NulError, RustNulError);
+
+impl From<std::io::Error> for ErrorType {
+    fn from(err: std::io::Error) -> ErrorType {
+        ErrorType::Io(err.to_string())
+    }
+}
@@ -1,4 +1,167 @@
+/// a platform-independent keyboard key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Num0,
+    Num1,
+    Num2,
+    Num3,
+    Num4,
+    Num5,
+    Num6,
+    Num7,
+    Num8,
+    Num9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Up,
+    Down,
+    Left,
+    Right,
+    Space,
+    Enter,
+    Escape,
+    Backspace,
+    Delete,
+    Tab,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Backquote,
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    LeftAlt,
+    RightAlt,
+}
+
+impl Key {
+    /// every key, in declaration order.
+    pub const ALL: [Key; 69] = [
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+        Key::Up,
+        Key::Down,
+        Key::Left,
+        Key::Right,
+        Key::Space,
+        Key::Enter,
+        Key::Escape,
+        Key::Backspace,
+        Key::Delete,
+        Key::Tab,
+        Key::Home,
+        Key::End,
+        Key::PageUp,
+        Key::PageDown,
+        Key::Backquote,
+        Key::LeftShift,
+        Key::RightShift,
+        Key::LeftCtrl,
+        Key::RightCtrl,
+        Key::LeftAlt,
+        Key::RightAlt,
+    ];
+
+    /// returns the key's name, as used in config files.
+    pub fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    /// looks up a key by the name returned from `name`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Key> {
+        Key::ALL
+            .iter()
+            .find(|key| key.name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+}
+
 /// represents the player's inputs into the game- e.g. movement and pressed actions like jump / interact.
+#[derive(Clone, Default)]
 pub struct Input {
     /// a value between -1.0 and 1.0 representing the player's forwards and backwards movement.
     ///
@@ -14,6 +177,45 @@ pub struct Input {
     /// * `-1.0` : wants to move to the left at full speed6
     pub sideway_move: f32,
 
+    /// the mouse position, in `Pixels` coordinates rather than window coordinates.
     pub mouse_x: u32,
     pub mouse_y: u32,
+
+    /// whether the left mouse button is currently held.
+    pub mouse_left: bool,
+    /// whether the right mouse button is currently held.
+    pub mouse_right: bool,
+    /// how far the scroll wheel moved this frame; positive is away from the player.
+    pub mouse_wheel: f32,
+
+    /// every key currently held down.
+    pub keys_down: Vec<Key>,
+    /// keys that went down this frame, including key repeats.
+    pub keys_pressed: Vec<Key>,
+    /// text typed this frame, already translated by the keyboard layout.
+    pub text: String,
+}
+
+impl Input {
+    /// returns true if the key is currently held down.
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    /// returns true if the key went down this frame.
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// returns a value between -1.0 and 1.0 from a pair of held keys, in the same style as `forward_move`.
+    pub fn axis(&self, positive: Key, negative: Key) -> f32 {
+        let mut value = 0.0;
+        if self.is_key_down(positive) {
+            value += 1.0;
+        }
+        if self.is_key_down(negative) {
+            value -= 1.0;
+        }
+        value
+    }
 }
@@ -1,11 +1,29 @@
 mod input;
 mod pixels;
 mod platform;
+mod platformcapture;
+mod platformheadless;
 mod platformminifb;
+mod platformrecorder;
+mod platformreplay;
 mod platformsdl2;
+#[cfg(unix)]
+mod platformterminal;
+pub mod profiler;
+mod replay;
+mod upscale;
 
-pub use input::Input;
+pub use input::{Input, Key};
 pub use pixels::Pixels;
-pub use platform::Platform;
+pub use platform::{Platform, WindowMode};
+pub use platformcapture::{PlatformCapture, CAPTURE_FRAME_RATE};
+pub use platformheadless::PlatformHeadless;
 pub use platformminifb::PlatformMinifb;
+pub use platformrecorder::PlatformRecorder;
+pub use platformreplay::PlatformReplay;
 pub use platformsdl2::PlatformSDL2;
+#[cfg(unix)]
+pub use platformterminal::PlatformTerminal;
+pub use profiler::Profiler;
+pub use replay::Replay;
+pub use upscale::UpscaleMode;
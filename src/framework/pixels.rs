@@ -1,3 +1,5 @@
+use std::{fs, io, path::Path};
+
 fn pack_rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
     return (a as u32) << 24 | (r as u32) << 16 | (g as u32) << 8 | b as u32;
 }
@@ -31,6 +33,11 @@ impl Pixels {
         self.buffer[offset] = pack_rgba(colour.0, colour.1, colour.2, 255);
     }
 
+    pub fn get_pixel(&self, position: (usize, usize)) -> (u8, u8, u8) {
+        let pixel = self.buffer[self.offset_of(position.0, position.1)];
+        ((pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8)
+    }
+
     pub fn draw_text(&mut self, position: (usize, usize), text: &str) {
         let mut position = position;
         for c in text.chars() {
@@ -51,6 +58,111 @@ impl Pixels {
         }
     }
 
+    /// draws text with the given foreground colour, leaving the background untouched if `background` is none.
+    ///
+    /// characters falling outside of the buffer are clipped.
+    pub fn draw_text_coloured(
+        &mut self,
+        position: (i32, i32),
+        text: &str,
+        foreground: (u8, u8, u8),
+        background: Option<(u8, u8, u8)>,
+    ) {
+        let mut x = position.0;
+        for c in text.chars() {
+            let font_char = font8x8::BASIC_UNICODE
+                .get(c as usize)
+                .unwrap_or(&font8x8::BASIC_UNICODE['?' as usize]);
+
+            for (row, y) in font_char.byte_array().iter().enumerate() {
+                for bit in 0..8 {
+                    let point = (x + bit, position.1 + row as i32);
+                    match y & 1 << bit {
+                        0 => {
+                            if let Some(background) = background {
+                                self.draw_pixel_clipped(point, background);
+                            }
+                        }
+                        _ => self.draw_pixel_clipped(point, foreground),
+                    }
+                }
+            }
+
+            x += 8;
+        }
+    }
+
+    /// returns how many pixels wide the given text is when drawn with `draw_text`.
+    pub fn text_width(text: &str) -> usize {
+        text.chars().count() * 8
+    }
+
+    /// draws a pixel, silently ignoring positions outside of the buffer.
+    pub fn draw_pixel_clipped(&mut self, position: (i32, i32), colour: (u8, u8, u8)) {
+        if position.0 < 0 || position.1 < 0 {
+            return;
+        }
+
+        let (x, y) = (position.0 as usize, position.1 as usize);
+        if x < self.width && y < self.height {
+            self.draw_pixel((x, y), colour);
+        }
+    }
+
+    /// fills a rectangle, clipped to the buffer.
+    pub fn fill_rect(&mut self, position: (i32, i32), size: (usize, usize), colour: (u8, u8, u8)) {
+        let x0 = position.0.clamp(0, self.width as i32) as usize;
+        let y0 = position.1.clamp(0, self.height as i32) as usize;
+        let x1 = (position.0 + size.0 as i32).clamp(0, self.width as i32) as usize;
+        let y1 = (position.1 + size.1 as i32).clamp(0, self.height as i32) as usize;
+
+        let packed = pack_rgba(colour.0, colour.1, colour.2, 255);
+        for y in y0..y1 {
+            let offset = self.offset_of(0, y);
+            self.buffer[offset + x0..offset + x1].fill(packed);
+        }
+    }
+
+    /// draws the one pixel wide outline of a rectangle, clipped to the buffer.
+    pub fn draw_rect(&mut self, position: (i32, i32), size: (usize, usize), colour: (u8, u8, u8)) {
+        if size.0 == 0 || size.1 == 0 {
+            return;
+        }
+
+        let (w, h) = (size.0 as i32, size.1 as i32);
+        self.fill_rect(position, (size.0, 1), colour);
+        self.fill_rect((position.0, position.1 + h - 1), (size.0, 1), colour);
+        self.fill_rect(position, (1, size.1), colour);
+        self.fill_rect((position.0 + w - 1, position.1), (1, size.1), colour);
+    }
+
+    /// draws a line between two points using bresenham's algorithm, clipped to the buffer.
+    pub fn draw_line(&mut self, from: (i32, i32), to: (i32, i32), colour: (u8, u8, u8)) {
+        let (mut x, mut y) = from;
+        let dx = (to.0 - from.0).abs();
+        let dy = -(to.1 - from.1).abs();
+        let sx = if from.0 < to.0 { 1 } else { -1 };
+        let sy = if from.1 < to.1 { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            self.draw_pixel_clipped((x, y), colour);
+            if x == to.0 && y == to.1 {
+                break;
+            }
+
+            let error2 = error * 2;
+            if error2 >= dy {
+                error += dy;
+                x += sx;
+            }
+            if error2 <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
     pub fn resize(&mut self, new_width: usize, new_height: usize) {
         self.buffer.resize(new_width * new_height, 0);
         self.buffer.fill(0);
@@ -79,6 +191,75 @@ impl Pixels {
         &mut self.buffer
     }
 
+    /// writes the pixels to a binary ppm image.
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut data = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.reserve(self.buffer.len() * 3);
+        for pixel in &self.buffer {
+            data.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8]);
+        }
+
+        fs::write(path, data)
+    }
+
+    /// reads a binary ppm image, as written by `save_ppm`.
+    pub fn load_ppm<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        // the header is four whitespace separated fields, which may be broken up by comments.
+        let mut fields = Vec::new();
+        let mut position = 0;
+        while fields.len() < 4 {
+            while position < data.len() && data[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            if position < data.len() && data[position] == b'#' {
+                while position < data.len() && data[position] != b'\n' {
+                    position += 1;
+                }
+                continue;
+            }
+            let start = position;
+            while position < data.len() && !data[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            if start == position {
+                return Err(invalid("truncated ppm header"));
+            }
+            fields.push(String::from_utf8_lossy(&data[start..position]).into_owned());
+        }
+        // exactly one whitespace byte separates the header from the pixel data.
+        position += 1;
+
+        let number = |field: &str| {
+            field
+                .parse::<usize>()
+                .map_err(|_| invalid("bad ppm header"))
+        };
+        if fields[0] != "P6" || number(&fields[3])? != 255 {
+            return Err(invalid("only 8-bit binary ppm images are supported"));
+        }
+        let (width, height) = (number(&fields[1])?, number(&fields[2])?);
+
+        let length = width
+            .checked_mul(height)
+            .and_then(|area| area.checked_mul(3))
+            .ok_or_else(|| invalid("ppm image is too big"))?;
+        let pixels_data = data
+            .get(position..)
+            .filter(|rest| rest.len() >= length)
+            .ok_or_else(|| invalid("truncated ppm pixel data"))?;
+
+        let mut pixels = Pixels::new(width, height);
+        for (pixel, rgb) in pixels.buffer.iter_mut().zip(pixels_data.chunks_exact(3)) {
+            *pixel = pack_rgba(rgb[0], rgb[1], rgb[2], 255);
+        }
+
+        Ok(pixels)
+    }
+
     pub fn offset_of(&self, x: usize, y: usize) -> usize {
         y * self.pitch() + x
     }
@@ -1,5 +1,36 @@
 use super::{Input, Pixels};
 
+/// how the window is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    /// takes over the display.
+    Fullscreen,
+    /// a window without a title bar or border.
+    Borderless,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [
+        WindowMode::Windowed,
+        WindowMode::Fullscreen,
+        WindowMode::Borderless,
+    ];
+
+    /// returns the mode's name, as used in settings files and the console.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "windowed",
+            WindowMode::Fullscreen => "fullscreen",
+            WindowMode::Borderless => "borderless",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<WindowMode> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
+
 pub trait Platform {
     /// signals the platform to poll events- window changes, inputs, etc.
     ///
@@ -14,4 +45,29 @@ pub trait Platform {
 
     /// returns how long the platform has been running for.
     fn runtime(&self) -> f32;
+
+    fn set_title(&mut self, title: &str);
+
+    /// returns the size of the window's drawable area.
+    fn window_size(&self) -> (usize, usize);
+
+    fn set_window_size(&mut self, width: usize, height: usize);
+
+    fn window_mode(&self) -> WindowMode;
+
+    fn set_window_mode(&mut self, mode: WindowMode);
+
+    fn set_cursor_visible(&mut self, visible: bool);
+
+    /// keeps the cursor inside the window, for mouse look.
+    fn set_cursor_grabbed(&mut self, grabbed: bool);
+
+    /// returns the new window size if the window was resized during the last `poll_events`.
+    fn resized(&self) -> Option<(usize, usize)>;
+
+    /// returns something the platform needs to tell the player, like a recording that had to stop,
+    /// which the game shows in the console. each message is only returned once.
+    fn take_message(&mut self) -> Option<String> {
+        None
+    }
 }
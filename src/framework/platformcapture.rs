@@ -0,0 +1,242 @@
+use super::{Input, Pixels, Platform, WindowMode};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// the frame rate recordings are written at.
+pub const CAPTURE_FRAME_RATE: u32 = 30;
+
+/// a video being written by `PlatformCapture`.
+struct Recording {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// true for `.y4m`, otherwise raw rgb frames with a `.txt` description beside them.
+    y4m: bool,
+    /// every frame is written at the size of the first, whatever size later frames are.
+    size: (usize, usize),
+    /// the platform's runtime when recording started.
+    started_at: f32,
+    frames_written: u64,
+    /// the last frame, converted to the recording's format and size.
+    frame: Vec<u8>,
+}
+
+/// wraps another platform, recording every frame it's given to a video file.
+///
+/// frames are written at a fixed `CAPTURE_FRAME_RATE`, repeating or skipping frames to keep up
+/// with however fast the game is actually running, so recordings play back at the right speed.
+/// `.y4m` files can be played or encoded directly, e.g. `ffmpeg -i capture.y4m capture.mp4`.
+pub struct PlatformCapture {
+    inner: Box<dyn Platform>,
+    recording: Option<Recording>,
+    /// why the last recording stopped on its own, until the game asks.
+    error: Option<String>,
+}
+
+impl PlatformCapture {
+    pub fn new(inner: Box<dyn Platform>) -> Self {
+        Self {
+            inner,
+            recording: None,
+            error: None,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// starts recording to a file, stopping any recording already running.
+    ///
+    /// paths ending in `.y4m` are written as y4m video; anything else is written as raw rgb24
+    /// frames, with the size and frame rate written to the same path with `.txt` appended.
+    pub fn start_recording<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        self.stop_recording()?;
+
+        let path = path.as_ref().to_path_buf();
+        let y4m = path.extension().is_some_and(|extension| extension == "y4m");
+        self.recording = Some(Recording {
+            writer: BufWriter::new(File::create(&path)?),
+            path,
+            y4m,
+            size: (0, 0),
+            started_at: self.inner.runtime(),
+            frames_written: 0,
+            frame: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// finishes the current recording, returning where it was written.
+    pub fn stop_recording(&mut self) -> io::Result<Option<PathBuf>> {
+        let Some(mut recording) = self.recording.take() else {
+            return Ok(None);
+        };
+
+        recording.writer.flush()?;
+        if !recording.y4m {
+            let mut sidecar = recording.path.clone().into_os_string();
+            sidecar.push(".txt");
+            fs::write(
+                sidecar,
+                format!(
+                    "format = rgb24\nwidth = {}\nheight = {}\nframe_rate = {}\nframes = {}\n",
+                    recording.size.0,
+                    recording.size.1,
+                    CAPTURE_FRAME_RATE,
+                    recording.frames_written
+                ),
+            )?;
+        }
+
+        Ok(Some(recording.path))
+    }
+
+    /// converts and writes a frame, as many times as needed to catch the recording up to now.
+    fn record_frame(&mut self, pixels: &Pixels) -> io::Result<()> {
+        let runtime = self.inner.runtime();
+        let Some(recording) = &mut self.recording else {
+            return Ok(());
+        };
+
+        if recording.frames_written == 0 {
+            recording.size = (pixels.width(), pixels.height());
+            if recording.y4m {
+                writeln!(
+                    recording.writer,
+                    "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+                    recording.size.0, recording.size.1, CAPTURE_FRAME_RATE
+                )?;
+            }
+        }
+
+        // always write the first frame, then however many frames should have passed since.
+        let due = ((runtime - recording.started_at) * CAPTURE_FRAME_RATE as f32) as u64 + 1;
+        if due <= recording.frames_written {
+            return Ok(());
+        }
+
+        recording.frame.clear();
+        if recording.y4m {
+            write_yuv444(pixels, recording.size, &mut recording.frame);
+        } else {
+            write_rgb24(pixels, recording.size, &mut recording.frame);
+        }
+
+        while recording.frames_written < due {
+            if recording.y4m {
+                recording.writer.write_all(b"FRAME\n")?;
+            }
+            recording.writer.write_all(&recording.frame)?;
+            recording.frames_written += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for PlatformCapture {
+    fn drop(&mut self) {
+        let _ = self.stop_recording();
+    }
+}
+
+impl Platform for PlatformCapture {
+    fn poll_events(&mut self) -> bool {
+        self.inner.poll_events()
+    }
+
+    fn set_pixels(&mut self, pixels: &Pixels) {
+        if let Err(e) = self.record_frame(pixels) {
+            // a full disk shouldn't take the game down with it.
+            self.error = Some(format!("stopped recording: {}", e));
+            self.recording = None;
+        }
+        self.inner.set_pixels(pixels);
+    }
+
+    fn input(&self) -> Input {
+        self.inner.input()
+    }
+
+    fn runtime(&self) -> f32 {
+        self.inner.runtime()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.inner.set_title(title);
+    }
+
+    fn window_size(&self) -> (usize, usize) {
+        self.inner.window_size()
+    }
+
+    fn set_window_size(&mut self, width: usize, height: usize) {
+        self.inner.set_window_size(width, height);
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        self.inner.window_mode()
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        self.inner.set_window_mode(mode);
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.inner.set_cursor_visible(visible);
+    }
+
+    fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        self.inner.set_cursor_grabbed(grabbed);
+    }
+
+    fn resized(&self) -> Option<(usize, usize)> {
+        self.inner.resized()
+    }
+
+    fn take_message(&mut self) -> Option<String> {
+        self.error.take().or_else(|| self.inner.take_message())
+    }
+}
+
+/// returns the pixel at a position in a frame of `size`, scaling if the pixels are a different size.
+fn sample(pixels: &Pixels, size: (usize, usize), x: usize, y: usize) -> (u8, u8, u8) {
+    if pixels.width() == 0 || pixels.height() == 0 {
+        return (0, 0, 0);
+    }
+    let x = x * pixels.width() / size.0;
+    let y = y * pixels.height() / size.1;
+    pixels.get_pixel((x, y))
+}
+
+fn write_rgb24(pixels: &Pixels, size: (usize, usize), frame: &mut Vec<u8>) {
+    for y in 0..size.1 {
+        for x in 0..size.0 {
+            let (r, g, b) = sample(pixels, size, x, y);
+            frame.extend_from_slice(&[r, g, b]);
+        }
+    }
+}
+
+/// writes the y, u and v planes of a frame, converted with bt.601 limited range like most players expect.
+fn write_yuv444(pixels: &Pixels, size: (usize, usize), frame: &mut Vec<u8>) {
+    let area = size.0 * size.1;
+    frame.resize(area * 3, 0);
+    let (luma, chroma) = frame.split_at_mut(area);
+    let (blue, red) = chroma.split_at_mut(area);
+
+    for y in 0..size.1 {
+        for x in 0..size.0 {
+            let (r, g, b) = sample(pixels, size, x, y);
+            let (r, g, b) = (r as f32, g as f32, b as f32);
+            let index = y * size.0 + x;
+
+            luma[index] = (16.0 + 0.257 * r + 0.504 * g + 0.098 * b).round() as u8;
+            blue[index] = (128.0 - 0.148 * r - 0.291 * g + 0.439 * b).round() as u8;
+            red[index] = (128.0 + 0.439 * r - 0.368 * g - 0.071 * b).round() as u8;
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use super::{Input, Pixels, Platform, WindowMode};
+
+/// a platform without a window or input, for running the game unattended.
+///
+/// time advances by a fixed step every frame rather than following the clock, so runs are
+/// repeatable and go as fast as the game can draw.
+pub struct PlatformHeadless {
+    size: (usize, usize),
+    time_step: f32,
+    frame: u64,
+    /// how many frames to run before quitting, if limited.
+    frame_limit: Option<u64>,
+}
+
+impl PlatformHeadless {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            size: (width, height),
+            time_step: 1.0 / 60.0,
+            frame: 0,
+            frame_limit: None,
+        }
+    }
+
+    /// quits after the given number of frames.
+    pub fn with_frame_limit(mut self, frames: u64) -> Self {
+        self.frame_limit = Some(frames);
+        self
+    }
+
+    /// sets how many seconds pass each frame.
+    pub fn with_time_step(mut self, time_step: f32) -> Self {
+        self.time_step = time_step;
+        self
+    }
+}
+
+impl Platform for PlatformHeadless {
+    fn poll_events(&mut self) -> bool {
+        self.frame += 1;
+        self.frame_limit.is_none_or(|limit| self.frame <= limit)
+    }
+
+    fn set_pixels(&mut self, _pixels: &Pixels) {}
+
+    fn input(&self) -> Input {
+        Input::default()
+    }
+
+    fn runtime(&self) -> f32 {
+        self.frame as f32 * self.time_step
+    }
+
+    fn set_title(&mut self, _title: &str) {}
+
+    fn window_size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn set_window_size(&mut self, width: usize, height: usize) {
+        self.size = (width, height);
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        WindowMode::Windowed
+    }
+
+    fn set_window_mode(&mut self, _mode: WindowMode) {}
+
+    fn set_cursor_visible(&mut self, _visible: bool) {}
+
+    fn set_cursor_grabbed(&mut self, _grabbed: bool) {}
+
+    fn resized(&self) -> Option<(usize, usize)> {
+        None
+    }
+}
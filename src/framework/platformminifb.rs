@@ -1,43 +1,286 @@
-use super::{Input, Pixels, Platform};
-use minifb::{Key, Window, WindowOptions};
+use super::{Input, Key, Pixels, Platform, WindowMode};
+use minifb::{KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use std::{cell::RefCell, rc::Rc, time::Instant};
+
+/// collects typed characters from minifb, which only reports text through a callback.
+struct TextCallback {
+    text: Rc<RefCell<String>>,
+}
+
+impl minifb::InputCallback for TextCallback {
+    fn add_char(&mut self, uni_char: u32) {
+        if let Some(c) = char::from_u32(uni_char) {
+            self.text.borrow_mut().push(c);
+        }
+    }
+}
 
 #[allow(dead_code)]
 pub struct PlatformMinifb {
     window: Window,
+    /// the size of the last pixels set, used to map mouse coordinates from the window to the pixels.
+    pixels_size: (usize, usize),
+    typed_text: Rc<RefCell<String>>,
+    frame_text: String,
+    frame_wheel: f32,
+    started_at: Instant,
+    title: String,
+    window_mode: WindowMode,
+    cursor_visible: bool,
+    /// the window size as of the last `poll_events`, to notice resizes.
+    window_size: (usize, usize),
+    resized: Option<(usize, usize)>,
 }
 
 impl PlatformMinifb {
     pub fn new(window_width: usize, window_height: usize) -> Self {
-        let window = Window::new("", window_width, window_height, WindowOptions::default())
-            .unwrap_or_else(|e| {
-                panic!("{}", e);
-            });
+        let typed_text = Rc::new(RefCell::new(String::new()));
+        let window = create_window(
+            "",
+            (window_width, window_height),
+            WindowMode::Windowed,
+            &typed_text,
+        );
 
-        Self { window }
+        Self {
+            window,
+            pixels_size: (window_width, window_height),
+            typed_text,
+            frame_text: String::new(),
+            frame_wheel: 0.0,
+            started_at: Instant::now(),
+            title: String::new(),
+            window_mode: WindowMode::Windowed,
+            cursor_visible: true,
+            window_size: (window_width, window_height),
+            resized: None,
+        }
+    }
+
+    /// minifb can't change a window's size or style once it's open, so replaces it with a new one.
+    fn recreate_window(&mut self, size: (usize, usize)) {
+        self.window = create_window(&self.title, size, self.window_mode, &self.typed_text);
+        self.window.set_cursor_visibility(self.cursor_visible);
     }
 }
 
+fn create_window(
+    title: &str,
+    size: (usize, usize),
+    mode: WindowMode,
+    typed_text: &Rc<RefCell<String>>,
+) -> Window {
+    // minifb has no fullscreen, so the closest it gets is a borderless window.
+    let options = WindowOptions {
+        borderless: mode != WindowMode::Windowed,
+        title: mode == WindowMode::Windowed,
+        resize: mode == WindowMode::Windowed,
+        topmost: mode == WindowMode::Fullscreen,
+        ..WindowOptions::default()
+    };
+
+    let mut window = Window::new(title, size.0, size.1, options).unwrap_or_else(|e| {
+        panic!("{}", e);
+    });
+    window.set_input_callback(Box::new(TextCallback {
+        text: typed_text.clone(),
+    }));
+    window
+}
+
 impl Platform for PlatformMinifb {
     fn poll_events(&mut self) -> bool {
-        self.window.is_open() && !self.window.is_key_down(Key::Escape)
+        // minifb processes events while presenting, so anything typed arrived during the last set_pixels.
+        self.frame_text = self.typed_text.borrow_mut().split_off(0);
+        self.frame_wheel = self.window.get_scroll_wheel().map_or(0.0, |(_, y)| y);
+
+        let window_size = self.window.get_size();
+        self.resized = (window_size != self.window_size).then_some(window_size);
+        self.window_size = window_size;
+
+        self.window.is_open()
     }
 
     fn set_pixels(&mut self, pixels: &Pixels) {
+        self.pixels_size = (pixels.width(), pixels.height());
         self.window
             .update_with_buffer(&pixels.as_bytes(), pixels.width(), pixels.height())
             .unwrap();
     }
 
     fn input(&self) -> Input {
+        let keys_down: Vec<Key> = self
+            .window
+            .get_keys()
+            .into_iter()
+            .filter_map(map_key)
+            .collect();
+        let keys_pressed = self
+            .window
+            .get_keys_pressed(KeyRepeat::Yes)
+            .into_iter()
+            .filter_map(map_key)
+            .collect();
+
+        let mut forward_move = 0.0;
+        let mut sideway_move = 0.0;
+
+        if keys_down.contains(&Key::W) {
+            forward_move += 1.0;
+        }
+        if keys_down.contains(&Key::S) {
+            forward_move -= 1.0;
+        }
+
+        if keys_down.contains(&Key::D) {
+            sideway_move += 1.0;
+        }
+        if keys_down.contains(&Key::A) {
+            sideway_move -= 1.0;
+        }
+
+        let (window_width, window_height) = self.window.get_size();
+        let (mouse_x, mouse_y) =
+            self.window
+                .get_unscaled_mouse_pos(MouseMode::Clamp)
+                .map_or((0, 0), |(x, y)| {
+                    (
+                        (x * self.pixels_size.0 as f32 / window_width.max(1) as f32) as u32,
+                        (y * self.pixels_size.1 as f32 / window_height.max(1) as f32) as u32,
+                    )
+                });
+
         Input {
-            forward_move: 0.0,
-            sideway_move: 0.0,
-            mouse_x: 0,
-            mouse_y: 0,
+            forward_move,
+            sideway_move,
+            mouse_x,
+            mouse_y,
+            mouse_left: self.window.get_mouse_down(MouseButton::Left),
+            mouse_right: self.window.get_mouse_down(MouseButton::Right),
+            mouse_wheel: self.frame_wheel,
+            keys_down,
+            keys_pressed,
+            text: self.frame_text.clone(),
         }
     }
 
     fn runtime(&self) -> f32 {
-        0.0
+        self.started_at.elapsed().as_secs_f32()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+        self.window.set_title(title);
+    }
+
+    fn window_size(&self) -> (usize, usize) {
+        self.window.get_size()
+    }
+
+    fn set_window_size(&mut self, width: usize, height: usize) {
+        if (width, height) != self.window.get_size() {
+            self.recreate_window((width, height));
+        }
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        self.window_mode
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        if mode != self.window_mode {
+            self.window_mode = mode;
+            self.recreate_window(self.window.get_size());
+        }
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor_visible = visible;
+        self.window.set_cursor_visibility(visible);
+    }
+
+    fn set_cursor_grabbed(&mut self, _grabbed: bool) {
+        // minifb can't confine the cursor to the window.
     }
+
+    fn resized(&self) -> Option<(usize, usize)> {
+        self.resized
+    }
+}
+
+fn map_key(key: minifb::Key) -> Option<Key> {
+    use minifb::Key as K;
+
+    Some(match key {
+        K::A => Key::A,
+        K::B => Key::B,
+        K::C => Key::C,
+        K::D => Key::D,
+        K::E => Key::E,
+        K::F => Key::F,
+        K::G => Key::G,
+        K::H => Key::H,
+        K::I => Key::I,
+        K::J => Key::J,
+        K::K => Key::K,
+        K::L => Key::L,
+        K::M => Key::M,
+        K::N => Key::N,
+        K::O => Key::O,
+        K::P => Key::P,
+        K::Q => Key::Q,
+        K::R => Key::R,
+        K::S => Key::S,
+        K::T => Key::T,
+        K::U => Key::U,
+        K::V => Key::V,
+        K::W => Key::W,
+        K::X => Key::X,
+        K::Y => Key::Y,
+        K::Z => Key::Z,
+        K::Key0 => Key::Num0,
+        K::Key1 => Key::Num1,
+        K::Key2 => Key::Num2,
+        K::Key3 => Key::Num3,
+        K::Key4 => Key::Num4,
+        K::Key5 => Key::Num5,
+        K::Key6 => Key::Num6,
+        K::Key7 => Key::Num7,
+        K::Key8 => Key::Num8,
+        K::Key9 => Key::Num9,
+        K::F1 => Key::F1,
+        K::F2 => Key::F2,
+        K::F3 => Key::F3,
+        K::F4 => Key::F4,
+        K::F5 => Key::F5,
+        K::F6 => Key::F6,
+        K::F7 => Key::F7,
+        K::F8 => Key::F8,
+        K::F9 => Key::F9,
+        K::F10 => Key::F10,
+        K::F11 => Key::F11,
+        K::F12 => Key::F12,
+        K::Up => Key::Up,
+        K::Down => Key::Down,
+        K::Left => Key::Left,
+        K::Right => Key::Right,
+        K::Space => Key::Space,
+        K::Enter | K::NumPadEnter => Key::Enter,
+        K::Escape => Key::Escape,
+        K::Backspace => Key::Backspace,
+        K::Delete => Key::Delete,
+        K::Tab => Key::Tab,
+        K::Home => Key::Home,
+        K::End => Key::End,
+        K::PageUp => Key::PageUp,
+        K::PageDown => Key::PageDown,
+        K::Backquote => Key::Backquote,
+        K::LeftShift => Key::LeftShift,
+        K::RightShift => Key::RightShift,
+        K::LeftCtrl => Key::LeftCtrl,
+        K::RightCtrl => Key::RightCtrl,
+        K::LeftAlt => Key::LeftAlt,
+        K::RightAlt => Key::RightAlt,
+        _ => return None,
+    })
 }
@@ -0,0 +1,146 @@
+use super::replay::{self, ReplayFrame};
+use super::{Input, Pixels, Platform, WindowMode};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// wraps another platform, recording its input to a `.replay` file that `PlatformReplay` can play back.
+///
+/// everything the game reads from the platform each frame is taken once in `poll_events`, so what
+/// the game sees is exactly what gets recorded, however often it asks.
+pub struct PlatformRecorder {
+    inner: Box<dyn Platform>,
+    /// none once writing has failed, so a full disk doesn't stop the game.
+    writer: Option<BufWriter<File>>,
+    /// the frame in progress, written once the next frame starts and its checksum is known.
+    frame: Option<ReplayFrame>,
+    runtime: f32,
+    /// the window size as recorded, which is what a replay will report.
+    window_size: (usize, usize),
+    /// scratch space for encoding frames.
+    bytes: Vec<u8>,
+    /// why recording stopped, until the game asks.
+    error: Option<String>,
+}
+
+impl PlatformRecorder {
+    pub fn new<P: AsRef<Path>>(inner: Box<dyn Platform>, path: P) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let runtime = inner.runtime();
+        let window_size = inner.window_size();
+
+        let mut header = Vec::new();
+        replay::write_header(&mut header, window_size, runtime);
+        writer.write_all(&header)?;
+
+        Ok(Self {
+            inner,
+            writer: Some(writer),
+            frame: None,
+            runtime,
+            window_size,
+            bytes: Vec::new(),
+            error: None,
+        })
+    }
+
+    /// writes the frame in progress, if there is one.
+    fn finish_frame(&mut self) {
+        let (Some(frame), Some(writer)) = (self.frame.take(), &mut self.writer) else {
+            return;
+        };
+
+        self.bytes.clear();
+        frame.write(&mut self.bytes);
+        if let Err(e) = writer.write_all(&self.bytes) {
+            self.error = Some(format!("stopped recording input: {}", e));
+            self.writer = None;
+        }
+    }
+}
+
+impl Drop for PlatformRecorder {
+    fn drop(&mut self) {
+        self.finish_frame();
+        if let Some(writer) = &mut self.writer {
+            let _ = writer.flush();
+        }
+    }
+}
+
+impl Platform for PlatformRecorder {
+    fn poll_events(&mut self) -> bool {
+        self.finish_frame();
+        if !self.inner.poll_events() {
+            // the game won't run this frame, so neither should a replay.
+            return false;
+        }
+
+        self.runtime = self.inner.runtime();
+        if let Some(size) = self.inner.resized() {
+            self.window_size = size;
+        }
+        self.frame = Some(ReplayFrame {
+            runtime: self.runtime,
+            input: self.inner.input(),
+            resized: self.inner.resized(),
+            checksum: None,
+        });
+        true
+    }
+
+    fn set_pixels(&mut self, pixels: &Pixels) {
+        if let Some(frame) = &mut self.frame {
+            frame.checksum = Some(replay::checksum(pixels));
+        }
+        self.inner.set_pixels(pixels);
+    }
+
+    fn input(&self) -> Input {
+        self.frame
+            .as_ref()
+            .map_or_else(Input::default, |frame| frame.input.clone())
+    }
+
+    fn runtime(&self) -> f32 {
+        self.runtime
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.inner.set_title(title);
+    }
+
+    fn window_size(&self) -> (usize, usize) {
+        self.window_size
+    }
+
+    fn set_window_size(&mut self, width: usize, height: usize) {
+        self.inner.set_window_size(width, height);
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        self.inner.window_mode()
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        self.inner.set_window_mode(mode);
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.inner.set_cursor_visible(visible);
+    }
+
+    fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        self.inner.set_cursor_grabbed(grabbed);
+    }
+
+    fn resized(&self) -> Option<(usize, usize)> {
+        self.frame.as_ref().and_then(|frame| frame.resized)
+    }
+
+    fn take_message(&mut self) -> Option<String> {
+        self.error.take().or_else(|| self.inner.take_message())
+    }
+}
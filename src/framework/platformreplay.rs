@@ -0,0 +1,138 @@
+use super::replay::{self, Replay, ReplayFrame};
+use super::{Input, Pixels, Platform, WindowMode};
+
+/// plays back a recording made by `PlatformRecorder`, showing frames on another platform.
+///
+/// given the same settings and starting state, the game draws exactly what it drew while
+/// recording; every presented frame is checked against the recording's checksum, and the first
+/// that differs is reported, which is where the game stopped being deterministic.
+///
+/// the profiler overlay shows how long frames really took, which no replay can repeat, so a
+/// recording with the overlay open diverges at the first frame it's drawn in; close it with f3
+/// before recording.
+pub struct PlatformReplay {
+    /// shows the frames; its input and timing are ignored.
+    display: Box<dyn Platform>,
+    replay: Replay,
+    /// how many frames have started, so the current frame is the one before this.
+    frames_started: usize,
+    window_size: (usize, usize),
+    /// the first frame whose pixels didn't match the recording, if any.
+    diverged_at: Option<usize>,
+    finished: bool,
+    /// how the replay is going, until the game asks.
+    message: Option<String>,
+}
+
+impl PlatformReplay {
+    pub fn new(replay: Replay, display: Box<dyn Platform>) -> Self {
+        Self {
+            display,
+            window_size: replay.window_size,
+            replay,
+            frames_started: 0,
+            diverged_at: None,
+            finished: false,
+            message: None,
+        }
+    }
+
+    /// returns the index of the first frame that didn't match the recording, if any.
+    pub fn diverged_at(&self) -> Option<usize> {
+        self.diverged_at
+    }
+
+    fn current_frame(&self) -> Option<&ReplayFrame> {
+        self.frames_started
+            .checked_sub(1)
+            .and_then(|index| self.replay.frames.get(index))
+    }
+}
+
+impl Platform for PlatformReplay {
+    fn poll_events(&mut self) -> bool {
+        // still lets the display's window be closed to stop early.
+        if !self.display.poll_events() {
+            return false;
+        }
+
+        if self.frames_started >= self.replay.frames.len() {
+            if !self.finished {
+                self.finished = true;
+                self.message = Some(match self.diverged_at {
+                    Some(frame) => format!("replay diverged from the recording at frame {}", frame),
+                    None => format!(
+                        "replay matched the recording for all {} frames",
+                        self.replay.frames.len()
+                    ),
+                });
+            }
+            return false;
+        }
+
+        self.frames_started += 1;
+        if let Some(size) = self.current_frame().and_then(|frame| frame.resized) {
+            self.window_size = size;
+        }
+        true
+    }
+
+    fn set_pixels(&mut self, pixels: &Pixels) {
+        let index = self.frames_started.saturating_sub(1);
+        let expected = self.current_frame().and_then(|frame| frame.checksum);
+        if self.diverged_at.is_none() && expected != Some(replay::checksum(pixels)) {
+            self.diverged_at = Some(index);
+            self.message = Some(format!(
+                "replay diverged from the recording at frame {}",
+                index
+            ));
+        }
+        self.display.set_pixels(pixels);
+    }
+
+    fn input(&self) -> Input {
+        self.current_frame()
+            .map_or_else(Input::default, |frame| frame.input.clone())
+    }
+
+    fn runtime(&self) -> f32 {
+        self.current_frame()
+            .map_or(self.replay.start_runtime, |frame| frame.runtime)
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.display.set_title(title);
+    }
+
+    fn window_size(&self) -> (usize, usize) {
+        self.window_size
+    }
+
+    fn set_window_size(&mut self, _width: usize, _height: usize) {
+        // the recording has the resizes that followed, which are what the game sees.
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        self.display.window_mode()
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        self.display.set_window_mode(mode);
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.display.set_cursor_visible(visible);
+    }
+
+    fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        self.display.set_cursor_grabbed(grabbed);
+    }
+
+    fn resized(&self) -> Option<(usize, usize)> {
+        self.current_frame().and_then(|frame| frame.resized)
+    }
+
+    fn take_message(&mut self) -> Option<String> {
+        self.message.take().or_else(|| self.display.take_message())
+    }
+}
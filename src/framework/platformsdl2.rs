@@ -1,9 +1,10 @@
-use super::{Input, Pixels, Platform};
+use super::{Input, Key, Pixels, Platform, WindowMode};
 use sdl2::{
-    event::Event,
-    keyboard::{Keycode, Scancode},
+    event::{Event, WindowEvent},
+    keyboard::Scancode,
+    mouse::MouseButton,
     render::{Canvas, Texture, TextureCreator},
-    video::{Window, WindowContext},
+    video::{FullscreenType, Window, WindowContext},
 };
 
 #[allow(dead_code)]
@@ -37,6 +38,13 @@ pub struct PlatformSDL2 {
     texture_creator: TextureCreator<WindowContext>,
     display_texture: Texture,
     performance_counter_at_start: u64,
+    /// the size of the last pixels set, used to map mouse coordinates from the window to the pixels.
+    pixels_size: (usize, usize),
+    frame_keys_pressed: Vec<Key>,
+    frame_text: String,
+    frame_wheel: f32,
+    window_mode: WindowMode,
+    resized: Option<(usize, usize)>,
 }
 
 impl PlatformSDL2 {
@@ -46,6 +54,7 @@ impl PlatformSDL2 {
             .video
             .window("", window_width, window_height)
             .position_centered()
+            .resizable()
             .build()
             .map_err(|e| e.to_string())
             .unwrap();
@@ -68,6 +77,12 @@ impl PlatformSDL2 {
             texture_creator,
             display_texture,
             performance_counter_at_start,
+            pixels_size: (window_width as usize, window_height as usize),
+            frame_keys_pressed: Vec::new(),
+            frame_text: String::new(),
+            frame_wheel: 0.0,
+            window_mode: WindowMode::Windowed,
+            resized: None,
         }
     }
 
@@ -85,13 +100,28 @@ impl PlatformSDL2 {
 
 impl Platform for PlatformSDL2 {
     fn poll_events(&mut self) -> bool {
+        self.frame_keys_pressed.clear();
+        self.frame_text.clear();
+        self.frame_wheel = 0.0;
+        self.resized = None;
+
         for event in self.sdl.event_pump.poll_iter() {
             match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
+                Event::Quit { .. } => return false,
+                Event::KeyDown {
+                    scancode: Some(scancode),
+                    ..
+                } => {
+                    if let Some(key) = map_scancode(scancode) {
+                        self.frame_keys_pressed.push(key);
+                    }
+                }
+                Event::TextInput { text, .. } => self.frame_text.push_str(&text),
+                Event::MouseWheel { y, .. } => self.frame_wheel += y as f32,
+                Event::Window {
+                    win_event: WindowEvent::SizeChanged(width, height),
                     ..
-                } => return false,
+                } => self.resized = Some((width.max(0) as usize, height.max(0) as usize)),
                 _ => {}
             }
         }
@@ -112,6 +142,7 @@ impl Platform for PlatformSDL2 {
             })
             .unwrap();
         */
+        self.pixels_size = (pixels.width(), pixels.height());
         self.canvas.copy(&self.display_texture, None, None).unwrap();
         self.canvas.present();
     }
@@ -119,6 +150,11 @@ impl Platform for PlatformSDL2 {
     fn input(&self) -> Input {
         let keyboard_state = self.sdl.event_pump.keyboard_state();
 
+        let keys_down: Vec<Key> = keyboard_state
+            .pressed_scancodes()
+            .filter_map(map_scancode)
+            .collect();
+
         let mut forward_move = 0.0;
         let mut sideway_move = 0.0;
 
@@ -136,11 +172,24 @@ impl Platform for PlatformSDL2 {
             sideway_move -= 1.0;
         }
 
+        let mouse_state = self.sdl.event_pump.mouse_state();
+        let (window_width, window_height) = self.canvas.window().size();
+        let mouse_x =
+            mouse_state.x().max(0) as u32 * self.pixels_size.0 as u32 / window_width.max(1);
+        let mouse_y =
+            mouse_state.y().max(0) as u32 * self.pixels_size.1 as u32 / window_height.max(1);
+
         Input {
             forward_move,
             sideway_move,
-            mouse_x: 0,
-            mouse_y: 0,
+            mouse_x,
+            mouse_y,
+            mouse_left: mouse_state.is_mouse_button_pressed(MouseButton::Left),
+            mouse_right: mouse_state.is_mouse_button_pressed(MouseButton::Right),
+            mouse_wheel: self.frame_wheel,
+            keys_down,
+            keys_pressed: self.frame_keys_pressed.clone(),
+            text: self.frame_text.clone(),
         }
     }
 
@@ -150,4 +199,126 @@ impl Platform for PlatformSDL2 {
         (performance_counter_now - self.performance_counter_at_start) as f32
             / performance_frequency as f32
     }
+
+    fn set_title(&mut self, title: &str) {
+        // only fails if the title contains a nul byte.
+        let _ = self.canvas.window_mut().set_title(title);
+    }
+
+    fn window_size(&self) -> (usize, usize) {
+        let (width, height) = self.canvas.window().size();
+        (width as usize, height as usize)
+    }
+
+    fn set_window_size(&mut self, width: usize, height: usize) {
+        let _ = self
+            .canvas
+            .window_mut()
+            .set_size(width as u32, height as u32);
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        self.window_mode
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        let window = self.canvas.window_mut();
+        let fullscreen = match mode {
+            WindowMode::Windowed => FullscreenType::Off,
+            WindowMode::Fullscreen => FullscreenType::True,
+            // a desktop-sized window without a border, without changing the display mode.
+            WindowMode::Borderless => FullscreenType::Desktop,
+        };
+        if window.set_fullscreen(fullscreen).is_ok() {
+            window.set_bordered(mode == WindowMode::Windowed);
+            self.window_mode = mode;
+        }
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.sdl.sdl.mouse().show_cursor(visible);
+    }
+
+    fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        self.canvas.window_mut().set_grab(grabbed);
+    }
+
+    fn resized(&self) -> Option<(usize, usize)> {
+        self.resized
+    }
+}
+
+fn map_scancode(scancode: Scancode) -> Option<Key> {
+    Some(match scancode {
+        Scancode::A => Key::A,
+        Scancode::B => Key::B,
+        Scancode::C => Key::C,
+        Scancode::D => Key::D,
+        Scancode::E => Key::E,
+        Scancode::F => Key::F,
+        Scancode::G => Key::G,
+        Scancode::H => Key::H,
+        Scancode::I => Key::I,
+        Scancode::J => Key::J,
+        Scancode::K => Key::K,
+        Scancode::L => Key::L,
+        Scancode::M => Key::M,
+        Scancode::N => Key::N,
+        Scancode::O => Key::O,
+        Scancode::P => Key::P,
+        Scancode::Q => Key::Q,
+        Scancode::R => Key::R,
+        Scancode::S => Key::S,
+        Scancode::T => Key::T,
+        Scancode::U => Key::U,
+        Scancode::V => Key::V,
+        Scancode::W => Key::W,
+        Scancode::X => Key::X,
+        Scancode::Y => Key::Y,
+        Scancode::Z => Key::Z,
+        Scancode::Num0 => Key::Num0,
+        Scancode::Num1 => Key::Num1,
+        Scancode::Num2 => Key::Num2,
+        Scancode::Num3 => Key::Num3,
+        Scancode::Num4 => Key::Num4,
+        Scancode::Num5 => Key::Num5,
+        Scancode::Num6 => Key::Num6,
+        Scancode::Num7 => Key::Num7,
+        Scancode::Num8 => Key::Num8,
+        Scancode::Num9 => Key::Num9,
+        Scancode::F1 => Key::F1,
+        Scancode::F2 => Key::F2,
+        Scancode::F3 => Key::F3,
+        Scancode::F4 => Key::F4,
+        Scancode::F5 => Key::F5,
+        Scancode::F6 => Key::F6,
+        Scancode::F7 => Key::F7,
+        Scancode::F8 => Key::F8,
+        Scancode::F9 => Key::F9,
+        Scancode::F10 => Key::F10,
+        Scancode::F11 => Key::F11,
+        Scancode::F12 => Key::F12,
+        Scancode::Up => Key::Up,
+        Scancode::Down => Key::Down,
+        Scancode::Left => Key::Left,
+        Scancode::Right => Key::Right,
+        Scancode::Space => Key::Space,
+        Scancode::Return | Scancode::KpEnter => Key::Enter,
+        Scancode::Escape => Key::Escape,
+        Scancode::Backspace => Key::Backspace,
+        Scancode::Delete => Key::Delete,
+        Scancode::Tab => Key::Tab,
+        Scancode::Home => Key::Home,
+        Scancode::End => Key::End,
+        Scancode::PageUp => Key::PageUp,
+        Scancode::PageDown => Key::PageDown,
+        Scancode::Grave => Key::Backquote,
+        Scancode::LShift => Key::LeftShift,
+        Scancode::RShift => Key::RightShift,
+        Scancode::LCtrl => Key::LeftCtrl,
+        Scancode::RCtrl => Key::RightCtrl,
+        Scancode::LAlt => Key::LeftAlt,
+        Scancode::RAlt => Key::RightAlt,
+        _ => return None,
+    })
 }
@@ -0,0 +1,473 @@
+use super::{Input, Key, Pixels, Platform, WindowMode};
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{self, Read, Write},
+    time::Instant,
+};
+
+/// terminals only report key presses, never releases, so a key counts as held for this many seconds
+/// after its last press or repeat. it's longer than the usual delay before keys start repeating, so
+/// holding a key doesn't let go of it in between.
+const KEY_HOLD_TIME: f32 = 0.6;
+
+/// draws into the terminal it's run from, using ansi escape codes.
+///
+/// every character cell shows two pixels: the top as the foreground colour of an upper half block,
+/// and the bottom as the background colour. only cells which changed since the last frame are
+/// redrawn, to keep the amount written down over slow connections like ssh.
+pub struct PlatformTerminal {
+    /// the terminal settings before raw mode was enabled, restored when dropped.
+    original_termios: libc::termios,
+    started_at: Instant,
+    /// the terminal's size in cells.
+    cells: (usize, usize),
+    /// the colours of every cell as last drawn, as packed top and bottom pixels.
+    screen: Vec<(u32, u32)>,
+    /// when each key was last seen, for working out which keys are held.
+    key_times: HashMap<Key, f32>,
+    frame_keys_pressed: Vec<Key>,
+    frame_text: String,
+    frame_wheel: f32,
+    /// set by ctrl+c, as raw mode stops the terminal sending interrupts.
+    quit: bool,
+    mouse_position: (u32, u32),
+    mouse_left: bool,
+    mouse_right: bool,
+    /// the size of the last pixels set, used to map mouse coordinates from cells to the pixels.
+    pixels_size: (usize, usize),
+    resized: Option<(usize, usize)>,
+}
+
+impl PlatformTerminal {
+    pub fn new() -> io::Result<Self> {
+        let fd = libc::STDIN_FILENO;
+
+        // safety: termios is plain data, and is only read after tcgetattr has filled it in.
+        let original_termios = unsafe {
+            let mut termios = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(fd, &mut termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            termios
+        };
+
+        // safety: the calls only touch the terminal's settings, which are restored when dropped.
+        unsafe {
+            let mut raw = original_termios;
+            libc::cfmakeraw(&mut raw);
+            // keep output processing, so newlines still return the cursor.
+            raw.c_oflag |= libc::OPOST;
+            // reads return straight away with whatever has been typed, even nothing. this rather
+            // than making stdin non-blocking, which usually makes stdout non-blocking with it.
+            raw.c_cc[libc::VMIN] = 0;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(fd, libc::TCSANOW, &raw) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        let cells = terminal_size();
+
+        // switch to the alternate screen, hide the cursor and report every mouse event.
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[?1049h\x1b[?25l\x1b[?1003h\x1b[?1006h\x1b[2J")?;
+        stdout.flush()?;
+
+        Ok(Self {
+            original_termios,
+            started_at: Instant::now(),
+            cells,
+            screen: Vec::new(),
+            key_times: HashMap::new(),
+            frame_keys_pressed: Vec::new(),
+            frame_text: String::new(),
+            frame_wheel: 0.0,
+            quit: false,
+            mouse_position: (0, 0),
+            mouse_left: false,
+            mouse_right: false,
+            pixels_size: (cells.0, cells.1 * 2),
+            resized: None,
+        })
+    }
+
+    /// reads everything typed since the last frame without blocking.
+    fn read_input(&mut self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut buffer = [0; 1024];
+        loop {
+            match io::stdin().read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(count) => bytes.extend_from_slice(&buffer[..count]),
+            }
+        }
+        bytes
+    }
+
+    fn press(&mut self, key: Key) {
+        self.frame_keys_pressed.push(key);
+        self.key_times.insert(key, self.runtime());
+    }
+
+    /// turns the raw bytes from the terminal into key presses, text and mouse events.
+    fn parse_input(&mut self, bytes: &[u8]) {
+        let mut index = 0;
+        while index < bytes.len() {
+            let rest = &bytes[index..];
+            index += match rest[0] {
+                0x1b => self.parse_escape(rest),
+                0x03 => {
+                    self.quit = true;
+                    1
+                }
+                b'\r' | b'\n' => {
+                    self.press(Key::Enter);
+                    1
+                }
+                b'\t' => {
+                    self.press(Key::Tab);
+                    1
+                }
+                0x7f | 0x08 => {
+                    self.press(Key::Backspace);
+                    1
+                }
+                // ctrl+a through ctrl+z.
+                byte @ 0x01..=0x1a => {
+                    self.press(Key::LeftCtrl);
+                    if let Some(key) = char_key((byte - 1 + b'a') as char) {
+                        self.press(key);
+                    }
+                    1
+                }
+                byte if byte < 0x80 => {
+                    let c = byte as char;
+                    if let Some(key) = char_key(c.to_ascii_lowercase()) {
+                        if c.is_ascii_uppercase() {
+                            self.press(Key::LeftShift);
+                        }
+                        self.press(key);
+                    }
+                    if !c.is_control() {
+                        self.frame_text.push(c);
+                    }
+                    1
+                }
+                byte => {
+                    // a multi-byte utf-8 character, which can only be typed text.
+                    let length = match byte {
+                        0xf0.. => 4,
+                        0xe0.. => 3,
+                        _ => 2,
+                    }
+                    .min(rest.len());
+                    if let Ok(text) = std::str::from_utf8(&rest[..length]) {
+                        self.frame_text.push_str(text);
+                    }
+                    length
+                }
+            };
+        }
+    }
+
+    /// parses an escape sequence, returning how many bytes it used.
+    fn parse_escape(&mut self, bytes: &[u8]) -> usize {
+        match bytes.get(1) {
+            // nothing follows, so escape itself was pressed.
+            None | Some(0x1b) => {
+                self.press(Key::Escape);
+                1
+            }
+            Some(b'[') => self.parse_csi(bytes),
+            Some(b'O') => {
+                let key = match bytes.get(2) {
+                    Some(b'P') => Some(Key::F1),
+                    Some(b'Q') => Some(Key::F2),
+                    Some(b'R') => Some(Key::F3),
+                    Some(b'S') => Some(Key::F4),
+                    Some(b'H') => Some(Key::Home),
+                    Some(b'F') => Some(Key::End),
+                    _ => None,
+                };
+                if let Some(key) = key {
+                    self.press(key);
+                }
+                bytes.len().min(3)
+            }
+            // escape followed by a character is how terminals send alt+character.
+            Some(_) => {
+                self.press(Key::LeftAlt);
+                1
+            }
+        }
+    }
+
+    /// parses a control sequence, `ESC [ parameters final`, returning how many bytes it used.
+    fn parse_csi(&mut self, bytes: &[u8]) -> usize {
+        let Some(end) = bytes[2..]
+            .iter()
+            .position(|byte| (0x40..=0x7e).contains(byte))
+        else {
+            return bytes.len();
+        };
+        let end = end + 2;
+        let parameters = String::from_utf8_lossy(&bytes[2..end]);
+        let final_byte = bytes[end];
+
+        if let Some(mouse) = parameters.strip_prefix('<') {
+            self.parse_mouse(mouse, final_byte == b'M');
+            return end + 1;
+        }
+
+        let key = match (final_byte, parameters.as_ref()) {
+            (b'A', _) => Some(Key::Up),
+            (b'B', _) => Some(Key::Down),
+            (b'C', _) => Some(Key::Right),
+            (b'D', _) => Some(Key::Left),
+            (b'H', _) => Some(Key::Home),
+            (b'F', _) => Some(Key::End),
+            (b'~', "1" | "7") => Some(Key::Home),
+            (b'~', "4" | "8") => Some(Key::End),
+            (b'~', "3") => Some(Key::Delete),
+            (b'~', "5") => Some(Key::PageUp),
+            (b'~', "6") => Some(Key::PageDown),
+            (b'~', "11") => Some(Key::F1),
+            (b'~', "12") => Some(Key::F2),
+            (b'~', "13") => Some(Key::F3),
+            (b'~', "14") => Some(Key::F4),
+            (b'~', "15") => Some(Key::F5),
+            (b'~', "17") => Some(Key::F6),
+            (b'~', "18") => Some(Key::F7),
+            (b'~', "19") => Some(Key::F8),
+            (b'~', "20") => Some(Key::F9),
+            (b'~', "21") => Some(Key::F10),
+            (b'~', "23") => Some(Key::F11),
+            (b'~', "24") => Some(Key::F12),
+            _ => None,
+        };
+        if let Some(key) = key {
+            self.press(key);
+        }
+
+        end + 1
+    }
+
+    /// parses an sgr mouse report, `button;column;row`.
+    fn parse_mouse(&mut self, parameters: &str, pressed: bool) {
+        let values: Vec<u32> = parameters
+            .split(';')
+            .filter_map(|value| value.parse().ok())
+            .collect();
+        let [button, column, row] = values[..] else {
+            return;
+        };
+
+        // cells are one pixel wide and two pixels tall.
+        let x = column.saturating_sub(1) as usize;
+        let y = row.saturating_sub(1) as usize * 2;
+        self.mouse_position = (
+            (x * self.pixels_size.0 / self.cells.0.max(1)) as u32,
+            (y * self.pixels_size.1 / (self.cells.1 * 2).max(1)) as u32,
+        );
+
+        match button {
+            64 => self.frame_wheel += 1.0,
+            65 => self.frame_wheel -= 1.0,
+            // motion reports don't change which buttons are held.
+            button if button & 32 != 0 => {}
+            button => match button & 3 {
+                0 => self.mouse_left = pressed,
+                2 => self.mouse_right = pressed,
+                _ => {}
+            },
+        }
+    }
+}
+
+impl Drop for PlatformTerminal {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x1b[0m\x1b[?1006l\x1b[?1003l\x1b[?25h\x1b[?1049l");
+        let _ = stdout.flush();
+
+        // safety: restores the settings read in `new`.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original_termios);
+        }
+    }
+}
+
+impl Platform for PlatformTerminal {
+    fn poll_events(&mut self) -> bool {
+        self.frame_keys_pressed.clear();
+        self.frame_text.clear();
+        self.frame_wheel = 0.0;
+
+        let bytes = self.read_input();
+        self.parse_input(&bytes);
+
+        let cells = terminal_size();
+        self.resized = None;
+        if cells != self.cells {
+            self.cells = cells;
+            self.screen.clear();
+            self.resized = Some(self.window_size());
+        }
+
+        !self.quit
+    }
+
+    fn set_pixels(&mut self, pixels: &Pixels) {
+        self.pixels_size = (pixels.width(), pixels.height());
+        let (columns, rows) = self.cells;
+        if pixels.width() == 0 || pixels.height() == 0 || columns == 0 || rows == 0 {
+            return;
+        }
+
+        // a resize forgets what was drawn, so everything is redrawn.
+        if self.screen.len() != columns * rows {
+            self.screen = vec![(u32::MAX, u32::MAX); columns * rows];
+        }
+
+        let sample = |x: usize, y: usize| {
+            let x = x * pixels.width() / columns;
+            let y = (y * pixels.height() / (rows * 2)).min(pixels.height() - 1);
+            pixels.as_bytes()[pixels.offset_of(x, y)] & 0x00ff_ffff
+        };
+
+        let mut output = String::new();
+        // where the terminal's cursor will be after what's been written so far, and its colours.
+        let mut cursor = None;
+        let mut colours = None;
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let cell = (sample(column, row * 2), sample(column, row * 2 + 1));
+                let index = row * columns + column;
+                if self.screen[index] == cell {
+                    continue;
+                }
+                self.screen[index] = cell;
+
+                if cursor != Some((column, row)) {
+                    let _ = write!(output, "\x1b[{};{}H", row + 1, column + 1);
+                }
+                if colours != Some(cell) {
+                    let (top, bottom) = cell;
+                    let _ = write!(
+                        output,
+                        "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+                        (top >> 16) & 0xff,
+                        (top >> 8) & 0xff,
+                        top & 0xff,
+                        (bottom >> 16) & 0xff,
+                        (bottom >> 8) & 0xff,
+                        bottom & 0xff
+                    );
+                    colours = Some(cell);
+                }
+                output.push('▀');
+                cursor = Some((column + 1, row));
+            }
+        }
+
+        if !output.is_empty() {
+            let mut stdout = io::stdout();
+            let written = stdout
+                .write_all(output.as_bytes())
+                .and_then(|()| stdout.flush());
+            // what's on screen isn't known if only some of it was written, so redraw it all.
+            if written.is_err() {
+                self.screen.clear();
+            }
+        }
+    }
+
+    fn input(&self) -> Input {
+        let now = self.runtime();
+        let keys_down: Vec<Key> = self
+            .key_times
+            .iter()
+            .filter(|(_, time)| now - **time < KEY_HOLD_TIME)
+            .map(|(key, _)| *key)
+            .collect();
+
+        let axis = |positive: Key, negative: Key| {
+            keys_down.contains(&positive) as i32 as f32
+                - keys_down.contains(&negative) as i32 as f32
+        };
+
+        Input {
+            forward_move: axis(Key::W, Key::S),
+            sideway_move: axis(Key::D, Key::A),
+            mouse_x: self.mouse_position.0,
+            mouse_y: self.mouse_position.1,
+            mouse_left: self.mouse_left,
+            mouse_right: self.mouse_right,
+            mouse_wheel: self.frame_wheel,
+            keys_pressed: self.frame_keys_pressed.clone(),
+            keys_down,
+            text: self.frame_text.clone(),
+        }
+    }
+
+    fn runtime(&self) -> f32 {
+        self.started_at.elapsed().as_secs_f32()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "\x1b]0;{}\x07", title);
+        let _ = stdout.flush();
+    }
+
+    /// returns the size of the terminal in pixels, at one pixel wide and two tall per cell.
+    fn window_size(&self) -> (usize, usize) {
+        (self.cells.0, self.cells.1 * 2)
+    }
+
+    fn set_window_size(&mut self, _width: usize, _height: usize) {
+        // the terminal emulator decides its own size.
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        WindowMode::Windowed
+    }
+
+    fn set_window_mode(&mut self, _mode: WindowMode) {}
+
+    fn set_cursor_visible(&mut self, _visible: bool) {
+        // the terminal's cursor is always hidden, and there's no mouse cursor to hide.
+    }
+
+    fn set_cursor_grabbed(&mut self, _grabbed: bool) {}
+
+    fn resized(&self) -> Option<(usize, usize)> {
+        self.resized
+    }
+}
+
+/// returns the terminal's size in character cells, falling back to 80x24 if it can't be read.
+fn terminal_size() -> (usize, usize) {
+    // safety: winsize is plain data, only read if the ioctl succeeds.
+    unsafe {
+        let mut size = std::mem::zeroed::<libc::winsize>();
+        if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col > 0 {
+            (size.ws_col as usize, size.ws_row as usize)
+        } else {
+            (80, 24)
+        }
+    }
+}
+
+/// returns the key for a lowercase letter, digit, space or backquote.
+fn char_key(c: char) -> Option<Key> {
+    match c {
+        'a'..='z' => Key::from_name(&c.to_string()),
+        '0'..='9' => Key::from_name(&format!("num{}", c)),
+        ' ' => Some(Key::Space),
+        '`' => Some(Key::Backquote),
+        _ => None,
+    }
+}
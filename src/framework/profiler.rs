@@ -0,0 +1,295 @@
+use super::Pixels;
+use std::{cell::RefCell, fs, io, path::Path, time::Instant};
+
+/// how many frames of timings are kept.
+const CAPACITY: usize = 240;
+
+/// a timed scope within a frame.
+#[derive(Clone)]
+pub struct ScopeTiming {
+    pub name: &'static str,
+    /// when the scope began, in seconds since the profiler was created.
+    pub start: f64,
+    /// how long the scope took, in seconds.
+    pub duration: f64,
+    /// how many scopes this one is nested inside of.
+    pub depth: usize,
+}
+
+/// the timings captured over a single frame.
+#[derive(Clone, Default)]
+pub struct FrameTiming {
+    /// when the frame began, in seconds since the profiler was created.
+    pub start: f64,
+    /// how long the frame took, in seconds.
+    pub duration: f64,
+    pub scopes: Vec<ScopeTiming>,
+}
+
+/// summary statistics over the captured frames, in seconds.
+pub struct FrameStats {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+/// collects named scope timings per frame into a ring buffer.
+///
+/// there is one profiler per thread, reached through the free functions in this module so that
+/// any code can be profiled without passing the profiler around:
+///
+/// ```ignore
+/// let _scope = profiler::scope("raycast");
+/// ```
+pub struct Profiler {
+    epoch: Instant,
+    frames: Vec<FrameTiming>,
+    /// where the next finished frame will be written in `frames`.
+    next: usize,
+    current: FrameTiming,
+    open_scopes: Vec<(&'static str, f64)>,
+    overlay_visible: bool,
+}
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
+}
+
+/// runs a closure with this thread's profiler.
+pub fn with<R>(f: impl FnOnce(&mut Profiler) -> R) -> R {
+    PROFILER.with(|profiler| f(&mut profiler.borrow_mut()))
+}
+
+/// begins a named scope which ends when the returned guard is dropped.
+pub fn scope(name: &'static str) -> Scope {
+    with(|profiler| profiler.begin_scope(name));
+    Scope { _private: () }
+}
+
+/// ends the scope it was created for when dropped.
+pub struct Scope {
+    _private: (),
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        with(|profiler| profiler.end_scope());
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            frames: Vec::with_capacity(CAPACITY),
+            next: 0,
+            current: FrameTiming::default(),
+            open_scopes: Vec::new(),
+            overlay_visible: false,
+        }
+    }
+
+    pub fn overlay_visible(&self) -> bool {
+        self.overlay_visible
+    }
+
+    pub fn set_overlay_visible(&mut self, visible: bool) {
+        self.overlay_visible = visible;
+    }
+
+    /// starts capturing a new frame.
+    pub fn begin_frame(&mut self) {
+        self.open_scopes.clear();
+        self.current = FrameTiming {
+            start: self.now(),
+            duration: 0.0,
+            scopes: Vec::new(),
+        };
+    }
+
+    /// finishes the current frame and stores it, overwriting the oldest frame if the buffer is full.
+    pub fn end_frame(&mut self) {
+        while !self.open_scopes.is_empty() {
+            self.end_scope();
+        }
+
+        let mut frame = std::mem::take(&mut self.current);
+        frame.duration = self.now() - frame.start;
+
+        if self.frames.len() < CAPACITY {
+            self.frames.push(frame);
+        } else {
+            self.frames[self.next] = frame;
+        }
+        self.next = (self.next + 1) % CAPACITY;
+    }
+
+    pub fn begin_scope(&mut self, name: &'static str) {
+        let now = self.now();
+        self.open_scopes.push((name, now));
+    }
+
+    pub fn end_scope(&mut self) {
+        if let Some((name, start)) = self.open_scopes.pop() {
+            let duration = self.now() - start;
+            self.current.scopes.push(ScopeTiming {
+                name,
+                start,
+                duration,
+                depth: self.open_scopes.len(),
+            });
+        }
+    }
+
+    /// returns the captured frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &FrameTiming> {
+        let split = if self.frames.len() < CAPACITY {
+            0
+        } else {
+            self.next
+        };
+        self.frames[split..]
+            .iter()
+            .chain(self.frames[..split].iter())
+    }
+
+    /// returns statistics over the captured frame durations, or none if nothing has been captured yet.
+    pub fn stats(&self) -> Option<FrameStats> {
+        let mut durations: Vec<f64> = self.frames.iter().map(|frame| frame.duration).collect();
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort_by(|a, b| a.total_cmp(b));
+
+        let percentile = |p: f64| durations[((durations.len() - 1) as f64 * p).round() as usize];
+        Some(FrameStats {
+            min: durations[0],
+            avg: durations.iter().sum::<f64>() / durations.len() as f64,
+            max: durations[durations.len() - 1],
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+        })
+    }
+
+    /// returns the average time spent in each named scope per frame, in first-seen order.
+    pub fn scope_averages(&self) -> Vec<(&'static str, f64)> {
+        let mut totals: Vec<(&'static str, f64)> = Vec::new();
+        for scope in self.frames.iter().flat_map(|frame| &frame.scopes) {
+            match totals.iter_mut().find(|(name, _)| *name == scope.name) {
+                Some(total) => total.1 += scope.duration,
+                None => totals.push((scope.name, scope.duration)),
+            }
+        }
+
+        let frame_count = self.frames.len().max(1) as f64;
+        for total in &mut totals {
+            total.1 /= frame_count;
+        }
+        totals
+    }
+
+    /// writes the captured frames as a chrome trace, viewable in `chrome://tracing` or perfetto.
+    pub fn write_chrome_trace<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut events = Vec::new();
+        for frame in self.frames() {
+            events.push(trace_event("frame", frame.start, frame.duration));
+            for scope in &frame.scopes {
+                events.push(trace_event(scope.name, scope.start, scope.duration));
+            }
+        }
+
+        fs::write(
+            path,
+            format!("{{\"traceEvents\":[\n{}\n]}}\n", events.join(",\n")),
+        )
+    }
+
+    /// draws a frame time graph and statistics into the bottom left of the pixels.
+    pub fn draw_overlay(&self, pixels: &mut Pixels) {
+        const GRAPH_WIDTH: usize = 120;
+        const GRAPH_HEIGHT: usize = 32;
+        /// the frame time at the top of the graph, in seconds.
+        const GRAPH_SCALE: f64 = 1.0 / 30.0;
+
+        let Some(stats) = self.stats() else {
+            return;
+        };
+        let scopes = self.scope_averages();
+
+        let lines = 3 + scopes.len();
+        let height = GRAPH_HEIGHT + 4 + lines * 8 + 4;
+        let width = (GRAPH_WIDTH + 4).max(8 * 22);
+        let top = pixels.height() as i32 - height as i32;
+        pixels.fill_rect((0, top), (width, height), (16, 16, 24));
+
+        // one bar per frame, newest on the right, with a line marking 60fps.
+        let graph_bottom = top + 2 + GRAPH_HEIGHT as i32;
+        let frames: Vec<&FrameTiming> = self.frames().collect();
+        let shown = &frames[frames.len().saturating_sub(GRAPH_WIDTH)..];
+        for (index, frame) in shown.iter().enumerate() {
+            let bar = ((frame.duration / GRAPH_SCALE).min(1.0) * GRAPH_HEIGHT as f64) as usize;
+            let colour = if frame.duration > 1.0 / 30.0 {
+                (224, 64, 64)
+            } else if frame.duration > 1.0 / 60.0 {
+                (224, 176, 64)
+            } else {
+                (64, 192, 64)
+            };
+            let x = 2 + (GRAPH_WIDTH - shown.len() + index) as i32;
+            pixels.fill_rect((x, graph_bottom - bar as i32), (1, bar), colour);
+        }
+        let target_y = graph_bottom - ((1.0 / 60.0) / GRAPH_SCALE * GRAPH_HEIGHT as f64) as i32;
+        pixels.fill_rect((2, target_y), (GRAPH_WIDTH, 1), (96, 96, 128));
+
+        let mut y = graph_bottom + 4;
+        let mut line = |pixels: &mut Pixels, text: String| {
+            pixels.draw_text_coloured((2, y), &text, (255, 255, 255), None);
+            y += 8;
+        };
+        line(
+            pixels,
+            format!("fps {:.0} avg", 1.0 / stats.avg.max(f64::EPSILON)),
+        );
+        line(
+            pixels,
+            format!(
+                "ms {:.1}/{:.1}/{:.1}",
+                stats.min * 1000.0,
+                stats.avg * 1000.0,
+                stats.max * 1000.0
+            ),
+        );
+        line(
+            pixels,
+            format!(
+                "p95 {:.1} p99 {:.1}",
+                stats.p95 * 1000.0,
+                stats.p99 * 1000.0
+            ),
+        );
+        for (name, average) in scopes {
+            line(pixels, format!("{:<8} {:.2}ms", name, average * 1000.0));
+        }
+    }
+
+    fn now(&self) -> f64 {
+        self.epoch.elapsed().as_secs_f64()
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn trace_event(name: &str, start: f64, duration: f64) -> String {
+    format!(
+        "{{\"name\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":0,\"tid\":0}}",
+        name,
+        start * 1_000_000.0,
+        duration * 1_000_000.0
+    )
+}
@@ -0,0 +1,337 @@
+//! the `.replay` input recording format, written by `PlatformRecorder` and played by `PlatformReplay`.
+//!
+//! all numbers are little endian. a file starts with a header:
+//!
+//! | bytes | contents                                           |
+//! |-------|----------------------------------------------------|
+//! | 4     | the magic bytes `ARCR`                             |
+//! | 2     | major version                                      |
+//! | 2     | minor version                                      |
+//! | 8     | the window size when recording started, two `u32`s |
+//! | 4     | the platform's runtime when recording started      |
+//!
+//! followed by frames until the end of the file, each being:
+//!
+//! | bytes | contents                                                              |
+//! |-------|-----------------------------------------------------------------------|
+//! | 4     | the runtime, as an `f32`, which the game takes its delta time from     |
+//! | 1     | flags: 1 left mouse, 2 right mouse, 4 resized, 8 has a checksum        |
+//! | 12    | forward move, sideway move and mouse wheel, as `f32`s                  |
+//! | 4     | the mouse position, as two `u16`s                                     |
+//! | 1 + n | a `u8` count of keys held down, then each key's index in `Key::ALL`    |
+//! | 1 + n | the same for keys pressed                                             |
+//! | 2 + n | typed text, as a `u16` length followed by utf-8 bytes                 |
+//! | 8     | the new window size as two `u32`s, if resized                          |
+//! | 4     | a checksum of the pixels presented this frame, if any were             |
+
+use super::{Input, Key, Pixels};
+use crate::error::ErrorType;
+use crate::reader::{corrupt, Reader};
+use std::{fs, path::Path};
+
+const MAGIC: &[u8; 4] = b"ARCR";
+const VERSION_MAJOR: u16 = 1;
+const VERSION_MINOR: u16 = 0;
+
+const FLAG_MOUSE_LEFT: u8 = 1;
+const FLAG_MOUSE_RIGHT: u8 = 2;
+const FLAG_RESIZED: u8 = 4;
+const FLAG_CHECKSUM: u8 = 8;
+
+/// one frame of a recording: everything the platform told the game, and what the game drew.
+#[derive(Clone)]
+pub struct ReplayFrame {
+    pub runtime: f32,
+    pub input: Input,
+    pub resized: Option<(usize, usize)>,
+    /// the `checksum` of the pixels presented, or none if the game didn't present that frame.
+    pub checksum: Option<u32>,
+}
+
+/// a whole recording, loaded from a `.replay` file.
+pub struct Replay {
+    pub window_size: (usize, usize),
+    pub start_runtime: f32,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay, ErrorType> {
+        Replay::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ErrorType> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(4)? != MAGIC {
+            return Err(corrupt("not a replay file"));
+        }
+        let major = reader.u16()?;
+        let minor = reader.u16()?;
+        if major > VERSION_MAJOR {
+            return Err(ErrorType::UnsupportedVersion(major, minor));
+        }
+
+        let window_size = (reader.u32()? as usize, reader.u32()? as usize);
+        let start_runtime = reader.f32()?;
+
+        let mut frames = Vec::new();
+        while !reader.is_empty() {
+            frames.push(read_frame(&mut reader)?);
+        }
+
+        Ok(Replay {
+            window_size,
+            start_runtime,
+            frames,
+        })
+    }
+}
+
+/// encodes the header a recording starts with.
+pub fn write_header(bytes: &mut Vec<u8>, window_size: (usize, usize), start_runtime: f32) {
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION_MAJOR.to_le_bytes());
+    bytes.extend_from_slice(&VERSION_MINOR.to_le_bytes());
+    bytes.extend_from_slice(&(window_size.0 as u32).to_le_bytes());
+    bytes.extend_from_slice(&(window_size.1 as u32).to_le_bytes());
+    bytes.extend_from_slice(&start_runtime.to_le_bytes());
+}
+
+impl ReplayFrame {
+    /// encodes the frame, to be appended after the header and any earlier frames.
+    pub fn write(&self, bytes: &mut Vec<u8>) {
+        let input = &self.input;
+        let mut flags = 0;
+        if input.mouse_left {
+            flags |= FLAG_MOUSE_LEFT;
+        }
+        if input.mouse_right {
+            flags |= FLAG_MOUSE_RIGHT;
+        }
+        if self.resized.is_some() {
+            flags |= FLAG_RESIZED;
+        }
+        if self.checksum.is_some() {
+            flags |= FLAG_CHECKSUM;
+        }
+
+        bytes.extend_from_slice(&self.runtime.to_le_bytes());
+        bytes.push(flags);
+        bytes.extend_from_slice(&input.forward_move.to_le_bytes());
+        bytes.extend_from_slice(&input.sideway_move.to_le_bytes());
+        bytes.extend_from_slice(&input.mouse_wheel.to_le_bytes());
+        bytes.extend_from_slice(&(input.mouse_x.min(u16::MAX as u32) as u16).to_le_bytes());
+        bytes.extend_from_slice(&(input.mouse_y.min(u16::MAX as u32) as u16).to_le_bytes());
+        write_keys(bytes, &input.keys_down);
+        write_keys(bytes, &input.keys_pressed);
+
+        let mut length = input.text.len().min(u16::MAX as usize);
+        while !input.text.is_char_boundary(length) {
+            length -= 1;
+        }
+        bytes.extend_from_slice(&(length as u16).to_le_bytes());
+        bytes.extend_from_slice(&input.text.as_bytes()[..length]);
+
+        if let Some((width, height)) = self.resized {
+            bytes.extend_from_slice(&(width as u32).to_le_bytes());
+            bytes.extend_from_slice(&(height as u32).to_le_bytes());
+        }
+        if let Some(checksum) = self.checksum {
+            bytes.extend_from_slice(&checksum.to_le_bytes());
+        }
+    }
+}
+
+/// a 32 bit fnv-1a hash of the pixels and their size, to tell whether two runs drew the same frame.
+pub fn checksum(pixels: &Pixels) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut add = |value: u32| {
+        for byte in value.to_le_bytes() {
+            hash = (hash ^ byte as u32).wrapping_mul(0x0100_0193);
+        }
+    };
+
+    add(pixels.width() as u32);
+    add(pixels.height() as u32);
+    for pixel in pixels.as_bytes() {
+        add(*pixel);
+    }
+    hash
+}
+
+fn write_keys(bytes: &mut Vec<u8>, keys: &[Key]) {
+    let indices: Vec<u8> = keys
+        .iter()
+        .filter_map(|key| Key::ALL.iter().position(|other| other == key))
+        .take(u8::MAX as usize)
+        .map(|index| index as u8)
+        .collect();
+    bytes.push(indices.len() as u8);
+    bytes.extend_from_slice(&indices);
+}
+
+fn read_frame(reader: &mut Reader) -> Result<ReplayFrame, ErrorType> {
+    let runtime = reader.f32()?;
+    let flags = reader.u8()?;
+    let forward_move = reader.f32()?;
+    let sideway_move = reader.f32()?;
+    let mouse_wheel = reader.f32()?;
+    let mouse_x = reader.u16()? as u32;
+    let mouse_y = reader.u16()? as u32;
+    let keys_down = read_keys(reader)?;
+    let keys_pressed = read_keys(reader)?;
+    let text_length = reader.u16()? as usize;
+    let text = String::from_utf8(reader.take(text_length)?.to_vec())
+        .map_err(|_| corrupt("typed text is not utf-8"))?;
+
+    let resized = if flags & FLAG_RESIZED != 0 {
+        Some((reader.u32()? as usize, reader.u32()? as usize))
+    } else {
+        None
+    };
+    let checksum = if flags & FLAG_CHECKSUM != 0 {
+        Some(reader.u32()?)
+    } else {
+        None
+    };
+
+    Ok(ReplayFrame {
+        runtime,
+        input: Input {
+            forward_move,
+            sideway_move,
+            mouse_x,
+            mouse_y,
+            mouse_left: flags & FLAG_MOUSE_LEFT != 0,
+            mouse_right: flags & FLAG_MOUSE_RIGHT != 0,
+            mouse_wheel,
+            keys_down,
+            keys_pressed,
+            text,
+        },
+        resized,
+        checksum,
+    })
+}
+
+fn read_keys(reader: &mut Reader) -> Result<Vec<Key>, ErrorType> {
+    let count = reader.u8()? as usize;
+    reader
+        .take(count)?
+        .iter()
+        .map(|index| {
+            Key::ALL
+                .get(*index as usize)
+                .copied()
+                .ok_or_else(|| corrupt("key doesn't exist"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::testing::{assert_truncations_are_corrupt, is_corrupt};
+
+    fn sample_frames() -> Vec<ReplayFrame> {
+        let input = Input {
+            forward_move: 1.0,
+            mouse_x: 12,
+            mouse_y: 34,
+            mouse_left: true,
+            keys_down: vec![Key::W, Key::LeftShift],
+            keys_pressed: vec![Key::Space],
+            text: "héllo".to_string(),
+            ..Input::default()
+        };
+        vec![
+            ReplayFrame {
+                runtime: 0.5,
+                input,
+                resized: Some((640, 480)),
+                checksum: Some(0xdead_beef),
+            },
+            ReplayFrame {
+                runtime: 0.6,
+                input: Input::default(),
+                resized: None,
+                checksum: None,
+            },
+        ]
+    }
+
+    /// returns a recording of the sample frames, and where each frame ends in it.
+    fn sample_bytes() -> (Vec<u8>, Vec<usize>) {
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, (320, 240), 0.25);
+        let mut boundaries = vec![bytes.len()];
+        for frame in sample_frames() {
+            frame.write(&mut bytes);
+            boundaries.push(bytes.len());
+        }
+        (bytes, boundaries)
+    }
+
+    #[test]
+    fn round_trips() {
+        let replay = Replay::from_bytes(&sample_bytes().0).unwrap();
+        assert_eq!(replay.window_size, (320, 240));
+        assert_eq!(replay.start_runtime, 0.25);
+        assert_eq!(replay.frames.len(), 2);
+
+        for (loaded, frame) in replay.frames.iter().zip(sample_frames()) {
+            assert_eq!(loaded.runtime, frame.runtime);
+            assert_eq!(loaded.resized, frame.resized);
+            assert_eq!(loaded.checksum, frame.checksum);
+
+            let (a, b) = (&loaded.input, &frame.input);
+            assert_eq!(a.forward_move, b.forward_move);
+            assert_eq!((a.mouse_x, a.mouse_y), (b.mouse_x, b.mouse_y));
+            assert_eq!((a.mouse_left, a.mouse_right), (b.mouse_left, b.mouse_right));
+            assert_eq!(a.keys_down, b.keys_down);
+            assert_eq!(a.keys_pressed, b.keys_pressed);
+            assert_eq!(a.text, b.text);
+        }
+    }
+
+    #[test]
+    fn truncated_files_are_corrupt() {
+        let (bytes, boundaries) = sample_bytes();
+        assert_truncations_are_corrupt(&bytes, &boundaries, Replay::from_bytes);
+    }
+
+    #[test]
+    fn corrupt_files_are_rejected() {
+        let (mut bytes, _) = sample_bytes();
+        bytes[0] = b'X';
+        assert!(is_corrupt(Replay::from_bytes(&bytes)));
+
+        // the first frame's first key held down, after its runtime, flags, moves and mouse.
+        let (mut bytes, boundaries) = sample_bytes();
+        bytes[boundaries[0] + 4 + 1 + 12 + 4 + 1] = u8::MAX;
+        assert!(is_corrupt(Replay::from_bytes(&bytes)));
+    }
+
+    #[test]
+    fn newer_major_versions_are_unsupported() {
+        let (mut bytes, _) = sample_bytes();
+        bytes[4..6].copy_from_slice(&(VERSION_MAJOR + 1).to_le_bytes());
+        assert_eq!(
+            Replay::from_bytes(&bytes).err(),
+            Some(ErrorType::UnsupportedVersion(
+                VERSION_MAJOR + 1,
+                VERSION_MINOR
+            ))
+        );
+    }
+
+    #[test]
+    fn checksums_tell_frames_apart() {
+        let mut pixels = Pixels::new(4, 4);
+        let blank = checksum(&pixels);
+        pixels.draw_pixel((1, 2), (255, 0, 0));
+        assert_ne!(checksum(&pixels), blank);
+        assert_ne!(checksum(&Pixels::new(2, 8)), checksum(&Pixels::new(4, 4)));
+    }
+}
@@ -0,0 +1,166 @@
+use super::Pixels;
+
+/// how the game's pixels are stretched to fill the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpscaleMode {
+    /// scales by the largest whole number that fits, with black bars around the rest, so every
+    /// pixel stays square and sharp.
+    Nearest,
+    /// scales to fill as much of the window as the aspect ratio allows, blending between pixels.
+    Bilinear,
+    /// like `Nearest`, but darkens the bottom of every pixel row like an old monitor's scanlines.
+    Crt,
+}
+
+impl UpscaleMode {
+    pub const ALL: [UpscaleMode; 3] = [
+        UpscaleMode::Nearest,
+        UpscaleMode::Bilinear,
+        UpscaleMode::Crt,
+    ];
+
+    /// returns the mode's name, as used in settings files and the console.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UpscaleMode::Nearest => "nearest",
+            UpscaleMode::Bilinear => "bilinear",
+            UpscaleMode::Crt => "crt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<UpscaleMode> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// returns where `source` is drawn within a target of the given size, as a position and size.
+    pub fn viewport(
+        &self,
+        source: (usize, usize),
+        target: (usize, usize),
+    ) -> ((usize, usize), (usize, usize)) {
+        let (source_width, source_height) = (source.0.max(1), source.1.max(1));
+        let scale_x = target.0 as f32 / source_width as f32;
+        let scale_y = target.1 as f32 / source_height as f32;
+
+        let mut scale = scale_x.min(scale_y);
+        // whole number scales keep pixels square, unless the source doesn't fit even once.
+        if *self != UpscaleMode::Bilinear && scale >= 1.0 {
+            scale = scale.floor();
+        }
+
+        let width = ((source_width as f32 * scale) as usize).min(target.0);
+        let height = ((source_height as f32 * scale) as usize).min(target.1);
+        (
+            ((target.0 - width) / 2, (target.1 - height) / 2),
+            (width, height),
+        )
+    }
+
+    /// converts a position in the target, such as the mouse, to the pixel of `source` drawn there.
+    pub fn to_source(
+        &self,
+        source: (usize, usize),
+        target: (usize, usize),
+        position: (u32, u32),
+    ) -> (u32, u32) {
+        let ((x, y), (width, height)) = self.viewport(source, target);
+        let map = |position: u32, start: usize, length: usize, source_length: usize| {
+            let offset = (position as f32 - start as f32).clamp(0.0, length.max(1) as f32 - 1.0);
+            (offset * source_length as f32 / length.max(1) as f32) as u32
+        };
+
+        (
+            map(position.0, x, width, source.0),
+            map(position.1, y, height, source.1),
+        )
+    }
+
+    /// stretches `source` over `target`, clearing anything outside of the viewport to black.
+    pub fn apply(&self, source: &Pixels, target: &mut Pixels) {
+        target.fill((0, 0, 0));
+        if source.width() == 0 || source.height() == 0 {
+            return;
+        }
+
+        let ((start_x, start_y), (width, height)) = self.viewport(
+            (source.width(), source.height()),
+            (target.width(), target.height()),
+        );
+        let step_x = source.width() as f32 / width.max(1) as f32;
+        let step_y = source.height() as f32 / height.max(1) as f32;
+
+        let source_bytes = source.as_bytes();
+        let source_pitch = source.pitch();
+        let target_pitch = target.pitch();
+        let target_bytes = target.as_bytes_mut();
+
+        for y in 0..height {
+            let row = (start_y + y) * target_pitch + start_x;
+
+            match self {
+                UpscaleMode::Nearest | UpscaleMode::Crt => {
+                    let sy = ((y as f32 * step_y) as usize).min(source.height() - 1);
+                    // the last output row of each source row is a darker scanline.
+                    let next_sy = ((y + 1) as f32 * step_y) as usize;
+                    let scanline = *self == UpscaleMode::Crt && next_sy != sy && step_y < 1.0;
+
+                    for x in 0..width {
+                        let sx = ((x as f32 * step_x) as usize).min(source.width() - 1);
+                        let pixel = source_bytes[sy * source_pitch + sx];
+                        target_bytes[row + x] = if scanline {
+                            scale_pixel(pixel, SCANLINE_BRIGHTNESS)
+                        } else {
+                            pixel
+                        };
+                    }
+                }
+                UpscaleMode::Bilinear => {
+                    let source_y = (y as f32 + 0.5) * step_y - 0.5;
+                    let (y0, y1, ty) = sample_axis(source_y, source.height());
+                    for x in 0..width {
+                        let source_x = (x as f32 + 0.5) * step_x - 0.5;
+                        let (x0, x1, tx) = sample_axis(source_x, source.width());
+
+                        let top = lerp_pixel(
+                            source_bytes[y0 * source_pitch + x0],
+                            source_bytes[y0 * source_pitch + x1],
+                            tx,
+                        );
+                        let bottom = lerp_pixel(
+                            source_bytes[y1 * source_pitch + x0],
+                            source_bytes[y1 * source_pitch + x1],
+                            tx,
+                        );
+                        target_bytes[row + x] = lerp_pixel(top, bottom, ty);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// how bright the scanlines of `UpscaleMode::Crt` are.
+const SCANLINE_BRIGHTNESS: f32 = 0.55;
+
+/// returns the two source pixels either side of a position along one axis, and how far between them it is.
+fn sample_axis(position: f32, length: usize) -> (usize, usize, f32) {
+    let position = position.clamp(0.0, (length - 1) as f32);
+    let first = position.floor() as usize;
+    let second = (first + 1).min(length - 1);
+    (first, second, position - first as f32)
+}
+
+/// blends two packed pixels channel by channel.
+fn lerp_pixel(a: u32, b: u32, t: f32) -> u32 {
+    let channel = |shift: u32| {
+        let a = ((a >> shift) & 0xff) as f32;
+        let b = ((b >> shift) & 0xff) as f32;
+        ((a + (b - a) * t) as u32) << shift
+    };
+    0xff00_0000 | channel(16) | channel(8) | channel(0)
+}
+
+fn scale_pixel(pixel: u32, brightness: f32) -> u32 {
+    let channel = |shift: u32| ((((pixel >> shift) & 0xff) as f32 * brightness) as u32) << shift;
+    0xff00_0000 | channel(16) | channel(8) | channel(0)
+}
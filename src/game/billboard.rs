@@ -0,0 +1,42 @@
+use crate::framework::Pixels;
+use glam::{Vec2, Vec3};
+
+/// the colour left out when drawing a billboard, so images without alpha can have holes.
+pub const TRANSPARENT: (u8, u8, u8) = (255, 0, 255);
+
+/// a flat image standing in the world and always turned towards the camera, drawn over the world
+/// by `Renderer::draw_billboards`.
+pub struct Billboard<'a> {
+    /// the middle of the image's bottom edge, so it stands on whatever it's placed on.
+    pub position: Vec3,
+    /// how wide and tall the image is in the world, in tiles.
+    pub size: Vec2,
+    pub image: &'a Pixels,
+    /// the part of the image to draw as (x, y, width, height) in pixels, e.g. one frame of a sheet.
+    pub source: (usize, usize, usize, usize),
+}
+
+impl<'a> Billboard<'a> {
+    /// shows the whole of an image.
+    pub fn new(position: Vec3, size: Vec2, image: &'a Pixels) -> Self {
+        Self {
+            position,
+            size,
+            image,
+            source: (0, 0, image.width(), image.height()),
+        }
+    }
+
+    /// returns the colour at a point on the billboard, from (0, 0) at the top left to (1, 1) at
+    /// the bottom right, or nothing where it's transparent or the image is empty.
+    pub fn sample(&self, uv: Vec2) -> Option<(u8, u8, u8)> {
+        let (x, y, width, height) = self.source;
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let u = ((uv.x * width as f32) as usize).min(width.saturating_sub(1));
+        let v = ((uv.y * height as f32) as usize).min(height.saturating_sub(1));
+        let colour = self.image.get_pixel((x + u, y + v));
+        (colour != TRANSPARENT).then_some(colour)
+    }
+}
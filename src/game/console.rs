@@ -0,0 +1,296 @@
+use crate::framework::{Input, Key, Pixels};
+use std::collections::BTreeMap;
+
+/// how many lines of output are kept before the oldest are discarded.
+const SCROLLBACK_LINES: usize = 256;
+/// how many submitted lines are kept for the up / down history.
+const HISTORY_LINES: usize = 64;
+
+struct Command {
+    help: String,
+}
+
+struct CVar {
+    value: String,
+    help: String,
+}
+
+/// a drop-down developer console, toggled with the backquote key.
+///
+/// the console only knows the names and help text of commands- running them is left to whoever
+/// registered them, which for game states is `GameState::on_command`. console variables are stored
+/// by the console itself, and typing a cvar's name on its own prints its value while following it
+/// with a value sets it.
+pub struct Console {
+    open: bool,
+    line: String,
+    scrollback: Vec<String>,
+    /// how many lines up from the bottom the scrollback is scrolled.
+    scroll: usize,
+    history: Vec<String>,
+    /// the position in `history` being browsed with up / down, if any.
+    history_index: Option<usize>,
+    commands: BTreeMap<String, Command>,
+    cvars: BTreeMap<String, CVar>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console {
+    pub fn new() -> Self {
+        let mut console = Self {
+            open: false,
+            line: String::new(),
+            scrollback: Vec::new(),
+            scroll: 0,
+            history: Vec::new(),
+            history_index: None,
+            commands: BTreeMap::new(),
+            cvars: BTreeMap::new(),
+        };
+
+        console.clear_commands();
+        console
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    /// registers a command so it shows up in `help` and tab completion.
+    pub fn register_command(&mut self, name: &str, help: &str) {
+        self.commands.insert(
+            name.to_string(),
+            Command {
+                help: help.to_string(),
+            },
+        );
+    }
+
+    /// removes every registered command except the console's own, keeping cvars and their values.
+    pub fn clear_commands(&mut self) {
+        self.commands.clear();
+        self.register_command("help", "lists every command");
+        self.register_command("cvars", "lists every console variable");
+        self.register_command("clear", "clears the console output");
+        self.register_command("echo", "prints its arguments");
+    }
+
+    /// registers a console variable with its current value.
+    pub fn register_cvar(&mut self, name: &str, value: &str, help: &str) {
+        self.cvars.insert(
+            name.to_string(),
+            CVar {
+                value: value.to_string(),
+                help: help.to_string(),
+            },
+        );
+    }
+
+    pub fn is_cvar(&self, name: &str) -> bool {
+        self.cvars.contains_key(name)
+    }
+
+    pub fn cvar(&self, name: &str) -> Option<&str> {
+        self.cvars.get(name).map(|cvar| cvar.value.as_str())
+    }
+
+    /// sets a cvar's value, returning false if no such cvar is registered.
+    pub fn set_cvar(&mut self, name: &str, value: &str) -> bool {
+        match self.cvars.get_mut(name) {
+            Some(cvar) => {
+                cvar.value = value.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// adds a line of output to the scrollback.
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            self.scrollback.push(line.to_string());
+        }
+        if self.scrollback.len() > SCROLLBACK_LINES {
+            let excess = self.scrollback.len() - SCROLLBACK_LINES;
+            self.scrollback.drain(0..excess);
+        }
+        self.scroll = 0;
+    }
+
+    /// handles typing into the console, returning a line when one is submitted with enter.
+    pub fn update(&mut self, input: &Input) -> Option<String> {
+        // the toggle key also arrives as text, which shouldn't end up in the line.
+        self.line
+            .extend(input.text.chars().filter(|c| !c.is_control() && *c != '`'));
+
+        let mut submitted = None;
+        for key in &input.keys_pressed {
+            match key {
+                Key::Backspace => {
+                    self.line.pop();
+                }
+                Key::Enter => {
+                    let line = std::mem::take(&mut self.line);
+                    if !line.trim().is_empty() {
+                        self.history.push(line.clone());
+                        if self.history.len() > HISTORY_LINES {
+                            self.history.remove(0);
+                        }
+                    }
+                    self.history_index = None;
+                    submitted = Some(line);
+                }
+                Key::Tab => self.complete(),
+                Key::Up => self.browse_history(true),
+                Key::Down => self.browse_history(false),
+                Key::PageUp => self.scroll_by(8),
+                Key::PageDown => self.scroll = self.scroll.saturating_sub(8),
+                _ => {}
+            }
+        }
+
+        if input.mouse_wheel > 0.0 {
+            self.scroll_by(2);
+        } else if input.mouse_wheel < 0.0 {
+            self.scroll = self.scroll.saturating_sub(2);
+        }
+
+        submitted
+    }
+
+    /// runs the console's own commands and prints cvars, returning false if the line wasn't for the console.
+    pub fn execute_builtin(&mut self, args: &[&str]) -> bool {
+        match args {
+            ["help"] => {
+                self.print("commands:");
+                let lines: Vec<String> = self
+                    .commands
+                    .iter()
+                    .map(|(name, command)| format!("  {} - {}", name, command.help))
+                    .collect();
+                for line in lines {
+                    self.print(&line);
+                }
+                self.print("type `cvars` to list console variables.");
+            }
+            ["cvars"] => {
+                let lines: Vec<String> = self
+                    .cvars
+                    .iter()
+                    .map(|(name, cvar)| format!("  {} = {} - {}", name, cvar.value, cvar.help))
+                    .collect();
+                for line in lines {
+                    self.print(&line);
+                }
+            }
+            ["clear"] => {
+                self.scrollback.clear();
+                self.scroll = 0;
+            }
+            ["echo", rest @ ..] => self.print(&rest.join(" ")),
+            [name] if self.is_cvar(name) => {
+                let line = format!("{} = {}", name, self.cvar(name).unwrap_or_default());
+                self.print(&line);
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// draws the console over the top half of the pixels, if it is open.
+    pub fn draw(&self, pixels: &mut Pixels) {
+        if !self.open {
+            return;
+        }
+
+        let height = pixels.height() / 2;
+        let width = pixels.width();
+        pixels.fill_rect((0, 0), (width, height), (16, 16, 24));
+        pixels.fill_rect((0, height as i32), (width, 1), (96, 96, 128));
+
+        // the input line sits at the bottom, with the newest output right above it.
+        let columns = width / 8;
+        let mut shown = format!("> {}_", self.line);
+        let skip = shown.chars().count().saturating_sub(columns);
+        shown = shown.chars().skip(skip).collect();
+        let input_y = height as i32 - 10;
+        pixels.draw_text_coloured((0, input_y), &shown, (224, 176, 64), None);
+
+        let rows = (height.saturating_sub(10)) / 8;
+        let end = self.scrollback.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(rows);
+        for (row, line) in self.scrollback[start..end].iter().rev().enumerate() {
+            let y = input_y - 8 * (row as i32 + 1);
+            pixels.draw_text_coloured((0, y), line, (255, 255, 255), None);
+        }
+    }
+
+    fn scroll_by(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.scrollback.len().saturating_sub(1));
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        self.history_index = match (self.history_index, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < self.history.len() => Some(index + 1),
+            (Some(_), false) => None,
+        };
+
+        self.line = match self.history_index {
+            Some(index) => self.history[index].clone(),
+            None => String::new(),
+        };
+    }
+
+    /// completes the first word of the line against commands and cvars.
+    fn complete(&mut self) {
+        if self.line.contains(' ') {
+            return;
+        }
+
+        let matches: Vec<&String> = self
+            .commands
+            .keys()
+            .chain(self.cvars.keys())
+            .filter(|name| name.starts_with(self.line.as_str()))
+            .collect();
+
+        match matches.as_slice() {
+            [] => {}
+            [name] => self.line = format!("{} ", name),
+            _ => {
+                // extend to the longest prefix shared by every match, and list them.
+                let mut prefix = matches[0].clone();
+                for name in &matches[1..] {
+                    while !name.starts_with(prefix.as_str()) {
+                        prefix.pop();
+                    }
+                }
+                let listing = matches
+                    .iter()
+                    .map(|name| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                self.line = prefix;
+                self.print(&listing);
+            }
+        }
+    }
+}
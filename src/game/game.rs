@@ -1,30 +1,101 @@
-use super::{GameState, GameStateUpdate};
+use super::{Console, GameState, GameStateUpdate, Settings, MAX_RENDER_SIZE};
 use crate::framework::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// the smallest fraction of the render size dynamic resolution will drop to.
+const MIN_RESOLUTION_SCALE: f32 = 0.25;
 
 pub struct Game {
-    platform: Box<dyn Platform>,
+    /// the platform the game was created with, wrapped so any frame can be recorded.
+    platform: PlatformCapture,
     states: Vec<Box<dyn GameState>>,
+    /// what states draw into, at the render resolution.
     pixels: Pixels,
+    /// `pixels` upscaled to the size of the window, which is what's actually presented.
+    output: Pixels,
+    upscale: UpscaleMode,
+    /// the render resolution before dynamic resolution scaling.
+    render_size: (usize, usize),
+    dynamic_resolution: bool,
+    /// the frame time dynamic resolution aims for, in milliseconds.
+    target_frame_time: f32,
+    /// how much of `render_size` is currently being drawn, from `MIN_RESOLUTION_SCALE` to 1.0.
+    resolution_scale: f32,
+    /// a smoothed frame time in milliseconds, so dynamic resolution doesn't jump around every frame.
+    average_frame_time: f32,
+    /// the settings as last read from disk or saved from the settings menu.
+    settings: Settings,
+    console: Console,
+    /// set by the `quit` console command.
+    quit_requested: bool,
+    /// whether the cursor was last hidden and grabbed for the top state.
+    cursor_grabbed: bool,
+    /// set by the `screenshot` console command; taken after the next frame is drawn.
+    screenshot_path: Option<String>,
 }
 
 impl Game {
-    /// creates a new game.
+    /// creates a new game, in a window.
     pub fn new() -> Self {
-        Self {
-            platform: Box::new(PlatformMinifb::new(768, 768)),
+        let settings = Settings::load();
+        Self::with_platform(Box::new(PlatformMinifb::new(
+            settings.window_size.0,
+            settings.window_size.1,
+        )))
+    }
+
+    /// creates a new game which runs on the given platform.
+    pub fn with_platform(platform: Box<dyn Platform>) -> Self {
+        let settings = Settings::load();
+
+        let mut game = Self {
+            platform: PlatformCapture::new(platform),
             states: Vec::new(),
-            pixels: Pixels::new(256, 256),
-        }
+            pixels: Pixels::new(settings.render_size.0, settings.render_size.1),
+            output: Pixels::new(settings.window_size.0, settings.window_size.1),
+            upscale: settings.upscale,
+            render_size: settings.render_size,
+            dynamic_resolution: settings.dynamic_resolution,
+            target_frame_time: settings.target_frame_time,
+            resolution_scale: 1.0,
+            average_frame_time: settings.target_frame_time,
+            settings,
+            console: Console::new(),
+            quit_requested: false,
+            cursor_grabbed: false,
+            screenshot_path: None,
+        };
+        game.platform.set_title("arcana");
+        game.platform.set_window_mode(game.settings.window_mode);
+        let (width, height) = game.platform.window_size();
+        game.output.resize(width.max(1), height.max(1));
+        game.register_cvars();
+        game
     }
 
     /// pushes a new state onto the game state stack.
     pub fn push_state(&mut self, state: Box<dyn GameState>) {
         self.states.push(state);
+        self.refresh_commands();
     }
 
-    /// pops the top state off of the stack.
+    /// pops the top state off of the stack, resuming the state beneath it.
     pub fn pop_state(&mut self) {
-        self.states.pop();
+        let popped = self.states.pop();
+        // only settings that were actually saved are taken, so cvars changed from the console
+        // aren't reset by closing a menu.
+        if let Some(settings) = popped.as_ref().and_then(|state| state.saved_settings()) {
+            let settings = settings.clone();
+            self.apply_settings(&settings);
+            for state in &mut self.states {
+                state.on_settings_changed(&settings);
+            }
+            self.settings = settings;
+        }
+        if let Some(state) = self.states.last_mut() {
+            state.on_resume();
+        }
+        self.refresh_commands();
     }
 
     /// begins the main game loop, which runs until the game is exited manually or an error is encountered.
@@ -34,14 +105,71 @@ impl Game {
             if self.platform.poll_events() == false {
                 break 'game_loop;
             }
+            while let Some(message) = self.platform.take_message() {
+                self.console.print(&message);
+            }
+            if let Some((width, height)) = self.platform.resized() {
+                self.output.resize(width.max(1), height.max(1));
+            }
+
+            profiler::with(|profiler| profiler.begin_frame());
 
             let current_runtime = self.platform.runtime();
             let delta_time = current_runtime - previous_runtime;
             previous_runtime = current_runtime;
+            self.update_resolution(delta_time);
+
+            let mut input = self.platform.input();
+            // the platform reports the mouse over the upscaled output, but states want render pixels.
+            (input.mouse_x, input.mouse_y) = self.upscale.to_source(
+                (self.pixels.width(), self.pixels.height()),
+                (self.output.width(), self.output.height()),
+                (input.mouse_x, input.mouse_y),
+            );
+            if input.is_key_pressed(Key::F3) {
+                profiler::with(|profiler| {
+                    profiler.set_overlay_visible(!profiler.overlay_visible())
+                });
+            }
+            if input.is_key_pressed(Key::F9) {
+                self.toggle_recording();
+            }
+            if input.is_key_pressed(Key::F11) {
+                let mode = match self.platform.window_mode() {
+                    WindowMode::Windowed => WindowMode::Fullscreen,
+                    _ => WindowMode::Windowed,
+                };
+                self.set_window_mode(mode);
+            }
+            if input.is_key_pressed(Key::Backquote) {
+                self.console.set_open(!self.console.is_open());
+            } else if self.console.is_open() {
+                if let Some(line) = self.console.update(&input) {
+                    self.execute_command(&line);
+                }
+            }
+            if self.quit_requested {
+                break 'game_loop;
+            }
+
+            if self.console.is_open() {
+                // states keep running under the console, but shouldn't see what's typed into it.
+                input = Input {
+                    mouse_x: input.mouse_x,
+                    mouse_y: input.mouse_y,
+                    ..Input::default()
+                };
+            }
 
             let state_result = if let Some(state) = self.states.last_mut() {
-                let state_result = state.on_update(delta_time, &self.platform.input());
-                state.on_draw(delta_time, &mut self.pixels);
+                let state_result = {
+                    let _scope = profiler::scope("update");
+                    state.on_update(delta_time, &input)
+                };
+                {
+                    let _scope = profiler::scope("draw");
+                    state.on_draw(delta_time, &mut self.pixels);
+                }
 
                 state_result
             } else {
@@ -57,15 +185,398 @@ impl Game {
                     self.pop_state();
                 }
                 GameStateUpdate::Replace(result_state) => {
-                    self.pop_state();
+                    self.states.pop();
+                    self.push_state(result_state);
+                }
+                GameStateUpdate::Reset(result_state) => {
+                    self.states.clear();
                     self.push_state(result_state);
                 }
                 GameStateUpdate::Quit => break 'game_loop,
             }
 
-            self.platform.set_pixels(&self.pixels);
+            let cursor_grabbed = self
+                .states
+                .last()
+                .is_some_and(|state| state.wants_cursor_grabbed());
+            if cursor_grabbed != self.cursor_grabbed {
+                self.cursor_grabbed = cursor_grabbed;
+                self.platform.set_cursor_grabbed(cursor_grabbed);
+                self.platform.set_cursor_visible(!cursor_grabbed);
+            }
+
+            if let Some(path) = self.screenshot_path.take() {
+                match self.pixels.save_ppm(&path) {
+                    Ok(()) => self.console.print(&format!("saved screenshot to {}", path)),
+                    Err(e) => self
+                        .console
+                        .print(&format!("couldn't save screenshot: {}", e)),
+                }
+            }
+            if profiler::with(|profiler| profiler.overlay_visible()) {
+                profiler::with(|profiler| profiler.draw_overlay(&mut self.pixels));
+            }
+            self.console.draw(&mut self.pixels);
+
+            {
+                let _scope = profiler::scope("present");
+                self.upscale.apply(&self.pixels, &mut self.output);
+                self.platform.set_pixels(&self.output);
+            }
+            profiler::with(|profiler| profiler.end_frame());
+        }
+
+        // the console is gone, but anything said while stopping, like how a replay went, still
+        // matters.
+        while let Some(message) = self.platform.take_message() {
+            eprintln!("{}", message);
         }
 
         Ok(())
     }
+
+    /// runs a line typed into the console.
+    fn execute_command(&mut self, line: &str) {
+        self.console.print(&format!("> {}", line));
+
+        let args: Vec<&str> = line.split_whitespace().collect();
+        match args.as_slice() {
+            [] => {}
+            ["quit"] => self.quit_requested = true,
+            ["screenshot"] => {
+                let seconds = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_secs());
+                self.screenshot_path = Some(format!("screenshot-{}.ppm", seconds));
+            }
+            ["screenshot", path] => self.screenshot_path = Some(path.to_string()),
+            ["record"] => self.toggle_recording(),
+            ["record", "stop"] => self.stop_recording(),
+            ["record", path] => self.start_recording(path),
+            ["perf"] => {
+                profiler::with(|profiler| profiler.set_overlay_visible(!profiler.overlay_visible()))
+            }
+            ["perf_dump"] => self.dump_profile("profile.json"),
+            ["perf_dump", path] => self.dump_profile(path),
+            _ if self.console.execute_builtin(&args) => {}
+            [name, value @ ..] if self.console.is_cvar(name) => {
+                // whoever owns the cvar sets it to the value it accepted, which may have been
+                // clamped, and leaves it alone if the value was rejected.
+                let value = value.join(" ");
+                self.on_cvar(name, &value);
+                let cvar_args = [*name, value.as_str()];
+                for state in self.states.iter_mut().rev() {
+                    state.on_command(&cvar_args, &mut self.console);
+                }
+
+                let line = format!("{} = {}", name, self.console.cvar(name).unwrap_or_default());
+                self.console.print(&line);
+            }
+            _ => {
+                for state in self.states.iter_mut().rev() {
+                    if state.on_command(&args, &mut self.console) {
+                        return;
+                    }
+                }
+                self.console
+                    .print(&format!("unknown command `{}`; try `help`", args[0]));
+            }
+        }
+    }
+
+    /// registers the console variables the game itself handles.
+    fn register_cvars(&mut self) {
+        self.console.register_cvar(
+            "window_mode",
+            self.settings.window_mode.name(),
+            "how the window is shown: windowed, fullscreen or borderless",
+        );
+        self.console.register_cvar(
+            "r_resolution",
+            &format!("{} {}", self.render_size.0, self.render_size.1),
+            "the size the game is drawn at: <width> <height>",
+        );
+        self.console.register_cvar(
+            "r_upscale",
+            self.upscale.name(),
+            "how the game is stretched to the window: nearest, bilinear or crt",
+        );
+        self.console.register_cvar(
+            "r_dynamic",
+            &(self.dynamic_resolution as u8).to_string(),
+            "lowers the resolution to keep up with r_target_ms, 0 or 1",
+        );
+        self.console.register_cvar(
+            "r_target_ms",
+            &self.target_frame_time.to_string(),
+            "the frame time dynamic resolution aims for, in milliseconds",
+        );
+    }
+
+    /// applies a console variable the game itself handles.
+    fn on_cvar(&mut self, name: &str, value: &str) {
+        match name {
+            "r_resolution" => {
+                let size: Vec<usize> = value
+                    .split_whitespace()
+                    .filter_map(|part| part.parse().ok())
+                    .collect();
+                match size.as_slice() {
+                    [width, height] if *width > 0 && *height > 0 => {
+                        self.render_size = (
+                            (*width).min(MAX_RENDER_SIZE),
+                            (*height).min(MAX_RENDER_SIZE),
+                        );
+                        let (width, height) = self.render_size;
+                        self.console
+                            .set_cvar("r_resolution", &format!("{} {}", width, height));
+                    }
+                    _ => self.console.print("usage: r_resolution <width> <height>"),
+                }
+            }
+            "r_upscale" => match UpscaleMode::from_name(value) {
+                Some(mode) => {
+                    self.upscale = mode;
+                    self.console.set_cvar("r_upscale", self.upscale.name());
+                }
+                None => self
+                    .console
+                    .print("r_upscale must be nearest, bilinear or crt"),
+            },
+            "window_mode" => match WindowMode::from_name(value) {
+                Some(mode) => self.set_window_mode(mode),
+                None => self
+                    .console
+                    .print("window_mode must be windowed, fullscreen or borderless"),
+            },
+            "r_dynamic" => {
+                self.dynamic_resolution = value != "0";
+                self.console
+                    .set_cvar("r_dynamic", &(self.dynamic_resolution as u8).to_string());
+            }
+            "r_target_ms" => match value.parse::<f32>() {
+                Ok(time) => {
+                    self.target_frame_time = time.max(1.0);
+                    self.console
+                        .set_cvar("r_target_ms", &self.target_frame_time.to_string());
+                }
+                Err(_) => self.console.print("r_target_ms must be a number"),
+            },
+            _ => {}
+        }
+    }
+
+    /// takes the render settings from `settings`, which may have been changed in the settings menu.
+    fn apply_settings(&mut self, settings: &Settings) {
+        if settings.window_size != self.settings.window_size {
+            self.platform
+                .set_window_size(settings.window_size.0, settings.window_size.1);
+        }
+        if settings.window_mode != self.platform.window_mode() {
+            self.set_window_mode(settings.window_mode);
+        }
+        self.render_size = settings.render_size;
+        self.upscale = settings.upscale;
+        self.dynamic_resolution = settings.dynamic_resolution;
+        self.target_frame_time = settings.target_frame_time;
+
+        self.console.set_cvar(
+            "r_resolution",
+            &format!("{} {}", self.render_size.0, self.render_size.1),
+        );
+        self.console.set_cvar("r_upscale", self.upscale.name());
+        self.console
+            .set_cvar("r_dynamic", &(self.dynamic_resolution as u8).to_string());
+        self.console
+            .set_cvar("r_target_ms", &self.target_frame_time.to_string());
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        self.platform.set_window_mode(mode);
+        self.console
+            .set_cvar("window_mode", self.platform.window_mode().name());
+    }
+
+    /// resizes `pixels` to the render size, scaled down by dynamic resolution when frames are slow.
+    fn update_resolution(&mut self, delta_time: f32) {
+        if self.dynamic_resolution {
+            self.average_frame_time += (delta_time * 1000.0 - self.average_frame_time) * 0.1;
+            if self.average_frame_time > self.target_frame_time * 1.1 {
+                self.resolution_scale = (self.resolution_scale * 0.95).max(MIN_RESOLUTION_SCALE);
+            } else if self.average_frame_time < self.target_frame_time * 0.8 {
+                self.resolution_scale = (self.resolution_scale * 1.02).min(1.0);
+            }
+        } else {
+            self.resolution_scale = 1.0;
+        }
+
+        let width = (self.render_size.0 as f32 * self.resolution_scale).round() as usize;
+        let height = (self.render_size.1 as f32 * self.resolution_scale).round() as usize;
+        let size = (width.max(1), height.max(1));
+        if size != (self.pixels.width(), self.pixels.height()) {
+            self.pixels.resize(size.0, size.1);
+        }
+    }
+
+    /// writes the profiler's captured frames to a chrome trace file.
+    fn dump_profile(&mut self, path: &str) {
+        match profiler::with(|profiler| profiler.write_chrome_trace(path)) {
+            Ok(()) => self.console.print(&format!("wrote profile to {}", path)),
+            Err(e) => self
+                .console
+                .print(&format!("couldn't write profile: {}", e)),
+        }
+    }
+
+    /// starts recording to a timestamped file, or stops the current recording.
+    fn toggle_recording(&mut self) {
+        if self.platform.is_recording() {
+            self.stop_recording();
+        } else {
+            let seconds = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs());
+            self.start_recording(&format!("capture-{}.y4m", seconds));
+        }
+    }
+
+    /// records every frame presented to a video file; see `PlatformCapture`.
+    pub fn start_recording(&mut self, path: &str) {
+        match self.platform.start_recording(path) {
+            Ok(()) => self.console.print(&format!(
+                "recording to {} at {}fps",
+                path, CAPTURE_FRAME_RATE
+            )),
+            Err(e) => self
+                .console
+                .print(&format!("couldn't start recording: {}", e)),
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        match self.platform.stop_recording() {
+            Ok(Some(path)) => self
+                .console
+                .print(&format!("saved recording to {}", path.display())),
+            Ok(None) => self.console.print("not recording"),
+            Err(e) => self
+                .console
+                .print(&format!("couldn't finish recording: {}", e)),
+        }
+    }
+
+    /// re-registers console commands from every state, as the stack has changed.
+    fn refresh_commands(&mut self) {
+        self.console.clear_commands();
+        self.console.register_command("quit", "exits the game");
+        self.console.register_command(
+            "screenshot",
+            "saves the screen to a .ppm file: screenshot [path]",
+        );
+        self.console.register_command(
+            "record",
+            "records video, .y4m or raw rgb, or toggles with f9: record [path|stop]",
+        );
+        self.console
+            .register_command("perf", "toggles the frame profiler overlay");
+        self.console.register_command(
+            "perf_dump",
+            "saves profiled frames as a chrome trace: perf_dump [path]",
+        );
+
+        for state in &self.states {
+            state.register_commands(&mut self.console);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameStateGameplay;
+    use std::{cell::RefCell, rc::Rc};
+
+    /// lets a test look at a platform after the game that owned it has finished.
+    struct Shared<P: Platform>(Rc<RefCell<P>>);
+
+    impl<P: Platform> Platform for Shared<P> {
+        fn poll_events(&mut self) -> bool {
+            self.0.borrow_mut().poll_events()
+        }
+
+        fn set_pixels(&mut self, pixels: &Pixels) {
+            self.0.borrow_mut().set_pixels(pixels);
+        }
+
+        fn input(&self) -> Input {
+            self.0.borrow().input()
+        }
+
+        fn runtime(&self) -> f32 {
+            self.0.borrow().runtime()
+        }
+
+        fn set_title(&mut self, title: &str) {
+            self.0.borrow_mut().set_title(title);
+        }
+
+        fn window_size(&self) -> (usize, usize) {
+            self.0.borrow().window_size()
+        }
+
+        fn set_window_size(&mut self, width: usize, height: usize) {
+            self.0.borrow_mut().set_window_size(width, height);
+        }
+
+        fn window_mode(&self) -> WindowMode {
+            self.0.borrow().window_mode()
+        }
+
+        fn set_window_mode(&mut self, mode: WindowMode) {
+            self.0.borrow_mut().set_window_mode(mode);
+        }
+
+        fn set_cursor_visible(&mut self, visible: bool) {
+            self.0.borrow_mut().set_cursor_visible(visible);
+        }
+
+        fn set_cursor_grabbed(&mut self, grabbed: bool) {
+            self.0.borrow_mut().set_cursor_grabbed(grabbed);
+        }
+
+        fn resized(&self) -> Option<(usize, usize)> {
+            self.0.borrow().resized()
+        }
+
+        fn take_message(&mut self) -> Option<String> {
+            self.0.borrow_mut().take_message()
+        }
+    }
+
+    fn run_gameplay(platform: Box<dyn Platform>) {
+        let mut game = Game::with_platform(platform);
+        game.push_state(Box::new(GameStateGameplay::new()));
+        game.run().unwrap();
+    }
+
+    #[test]
+    fn replays_match_their_recording() {
+        let path = std::env::temp_dir().join(format!("arcana-test-{}.replay", std::process::id()));
+
+        let headless = PlatformHeadless::new(160, 120).with_frame_limit(10);
+        run_gameplay(Box::new(
+            PlatformRecorder::new(Box::new(headless), &path).unwrap(),
+        ));
+
+        let replay = Replay::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replay.frames.len(), 10);
+
+        let player = Rc::new(RefCell::new(PlatformReplay::new(
+            replay,
+            Box::new(PlatformHeadless::new(160, 120)),
+        )));
+        run_gameplay(Box::new(Shared(player.clone())));
+        assert_eq!(player.borrow().diverged_at(), None);
+    }
 }
@@ -1,3 +1,4 @@
+use super::{Console, Settings};
 use crate::framework::{Input, Pixels};
 
 pub enum GameStateUpdate {
@@ -9,6 +10,8 @@ pub enum GameStateUpdate {
     Pop,
     /// replaces the current game state entirely.
     Replace(Box<dyn GameState>),
+    /// pops every game state off the stack, then pushes a new one- e.g. quitting to the main menu.
+    Reset(Box<dyn GameState>),
     /// exits the game entirely.
     Quit,
 }
@@ -16,4 +19,33 @@ pub enum GameStateUpdate {
 pub trait GameState {
     fn on_update(&mut self, delta_time: f32, input: &Input) -> GameStateUpdate;
     fn on_draw(&mut self, delta_time: f32, pixels: &mut Pixels);
+
+    /// called when the state above this one is popped, making this the top state again.
+    fn on_resume(&mut self) {}
+
+    /// returns the settings the state saved, if it did, for the game to apply once it's popped.
+    fn saved_settings(&self) -> Option<&Settings> {
+        None
+    }
+
+    /// called on every state when settings are saved, e.g. from the settings menu.
+    fn on_settings_changed(&mut self, _settings: &Settings) {}
+
+    /// returns true while the state wants the cursor hidden and kept in the window, e.g. for
+    /// mouse look.
+    fn wants_cursor_grabbed(&self) -> bool {
+        false
+    }
+
+    /// registers the state's console commands and cvars; called whenever the state stack changes.
+    fn register_commands(&self, _console: &mut Console) {}
+
+    /// runs a console command, returning true if the state handled it.
+    ///
+    /// states are offered commands from the top of the stack down, until one handles it. when a
+    /// cvar is set, every state is given the cvar's name and the value typed instead, and the state
+    /// which owns the cvar sets it to the value it accepted, or leaves it alone if it's rejected.
+    fn on_command(&mut self, _args: &[&str], _console: &mut Console) -> bool {
+        false
+    }
 }
@@ -0,0 +1,816 @@
+use super::{
+    Console, GameState, GameStateGameplay, GameStatePause, GameStateUpdate, RegionCommands,
+    Renderer, Settings,
+};
+use crate::framework::{profiler, Input, Key, Pixels};
+use crate::math::Transform;
+use crate::ui::{Rect, Ui};
+use crate::world::{tiles, Axis, EditHistory, Region, Tile, World};
+use glam::{IVec3, Vec3};
+use std::collections::HashSet;
+
+/// how fast the camera flies, in tiles per second.
+const FLY_SPEED: f32 = 8.0;
+
+/// the largest brush, in tiles across.
+const MAX_BRUSH_SIZE: i32 = 9;
+
+/// how wide the tool panel is, in pixels.
+const PANEL_WIDTH: usize = 124;
+
+/// the size of the world the editor starts with.
+const DEFAULT_SIZE: (usize, usize, usize) = (64, 32, 64);
+
+const GRID_COLOUR: (u8, u8, u8) = (72, 72, 96);
+const BOUNDS_COLOUR: (u8, u8, u8) = (160, 160, 200);
+const BRUSH_COLOUR: (u8, u8, u8) = (255, 255, 255);
+const SELECTION_COLOUR: (u8, u8, u8) = (224, 176, 64);
+
+/// what dragging the left mouse button over the world does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    /// places the selected tile against the face under the mouse.
+    Paint,
+    /// removes the tiles under the mouse.
+    Erase,
+    /// drags out a region for the selection buttons and region commands.
+    Select,
+}
+
+impl Tool {
+    const ALL: [Tool; 3] = [Tool::Paint, Tool::Erase, Tool::Select];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::Paint => "paint",
+            Tool::Erase => "erase",
+            Tool::Select => "select",
+        }
+    }
+}
+
+/// the shape of the tiles painted or erased at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BrushShape {
+    Cube,
+    Sphere,
+    /// a cylinder standing upright.
+    Cylinder,
+}
+
+impl BrushShape {
+    const ALL: [BrushShape; 3] = [BrushShape::Cube, BrushShape::Sphere, BrushShape::Cylinder];
+
+    fn name(&self) -> &'static str {
+        match self {
+            BrushShape::Cube => "cube",
+            BrushShape::Sphere => "sphere",
+            BrushShape::Cylinder => "cylinder",
+        }
+    }
+}
+
+/// what the mouse is pointing at.
+struct Cursor {
+    /// the tile under the mouse, or the cell just below the grid if the mouse is over empty grid.
+    position: IVec3,
+    /// the face of `position` the mouse is over.
+    normal: IVec3,
+    /// false if the mouse is over the grid rather than a tile.
+    on_tile: bool,
+}
+
+impl Cursor {
+    /// returns the tile in front of the face under the mouse, where painted tiles go.
+    fn adjacent(&self) -> IVec3 {
+        self.position + self.normal
+    }
+
+    /// returns the tile being pointed at: the one under the mouse, or the one on the grid.
+    fn tile(&self) -> IVec3 {
+        if self.on_tile {
+            self.position
+        } else {
+            self.adjacent()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DialogKind {
+    Save,
+    Load,
+}
+
+/// a save or load dialog asking for a path.
+struct Dialog {
+    kind: DialogKind,
+    path: String,
+    /// the error from the last attempt, if it failed.
+    error: Option<String>,
+}
+
+/// a level editor: fly around a world, paint tiles with a brush, work on selected regions, save
+/// and load `.arc` files, and play-test the level with f5.
+pub struct GameStateEditor {
+    world: World,
+    history: EditHistory,
+    /// the region commands, whose clipboard is shared with copy and paste in the panel.
+    region_commands: RegionCommands,
+    camera: Transform,
+    renderer: Renderer,
+    ui: Ui,
+    settings: Settings,
+    /// the mouse position last frame, for mouse look.
+    previous_mouse: (u32, u32),
+    /// whether the right mouse button is held to look around, which grabs the cursor.
+    mouse_looking: bool,
+
+    tool: Tool,
+    /// the index in `tiles::ALL` of the tile to paint.
+    palette: usize,
+    brush_shape: BrushShape,
+    brush_size: i32,
+    cursor: Option<Cursor>,
+    mouse_left_was_down: bool,
+    /// the tiles painted since the mouse went down, which the cursor looks through so painting
+    /// follows a surface instead of piling up towards the camera.
+    stroke: Option<HashSet<IVec3>>,
+
+    selection: Option<Region>,
+    /// the corner the selection is being dragged from.
+    selecting_from: Option<IVec3>,
+
+    show_grid: bool,
+    /// the height of the grid, which tiles can be painted on where there's nothing else.
+    grid_height: i32,
+
+    dialog: Option<Dialog>,
+    /// where the world was last saved or loaded.
+    path: String,
+    /// shown at the bottom of the screen, e.g. after saving.
+    status: String,
+}
+
+impl GameStateEditor {
+    pub fn new() -> Self {
+        let (size_x, size_y, size_z) = DEFAULT_SIZE;
+        Self::with_world(World::with_size(size_x, size_y, size_z))
+    }
+
+    pub fn with_world(world: World) -> Self {
+        let mut editor = Self {
+            world,
+            history: EditHistory::new(),
+            region_commands: RegionCommands::new(),
+            camera: Transform::identity(),
+            renderer: Renderer::new(),
+            ui: Ui::new(),
+            settings: Settings::load(),
+            previous_mouse: (0, 0),
+            mouse_looking: false,
+            tool: Tool::Paint,
+            palette: 1,
+            brush_shape: BrushShape::Cube,
+            brush_size: 1,
+            cursor: None,
+            mouse_left_was_down: false,
+            stroke: None,
+            selection: None,
+            selecting_from: None,
+            show_grid: true,
+            grid_height: 0,
+            dialog: None,
+            path: "level.arc".to_string(),
+            status: String::new(),
+        };
+        editor.reset_camera();
+        editor
+    }
+
+    /// puts the camera at the front of the world, looking down over it.
+    fn reset_camera(&mut self) {
+        let size = Vec3::new(
+            self.world.size_x() as f32,
+            self.world.size_y() as f32,
+            self.world.size_z() as f32,
+        );
+        self.camera = Transform::new(
+            Vec3::new(size.x * 0.5, size.y * 0.75, -size.z * 0.25),
+            Vec3::new(30.0, 0.0, 0.0),
+            Vec3::ONE,
+        );
+    }
+
+    /// swaps in a different world, forgetting everything about the old one.
+    fn set_world(&mut self, world: World) {
+        self.world = world;
+        self.history.clear();
+        self.selection = None;
+        self.grid_height = 0;
+        self.reset_camera();
+    }
+
+    fn update_camera(&mut self, delta_time: f32, input: &Input) {
+        // look around while the right mouse button is held.
+        let mouse = (input.mouse_x, input.mouse_y);
+        if input.mouse_right {
+            let delta_x = mouse.0 as f32 - self.previous_mouse.0 as f32;
+            let delta_y = mouse.1 as f32 - self.previous_mouse.1 as f32;
+            let sensitivity = self.settings.mouse_sensitivity;
+
+            let mut rotation = self.camera.rotation();
+            rotation.y += delta_x * sensitivity;
+            rotation.x = (rotation.x + delta_y * sensitivity).clamp(-89.0, 89.0);
+            self.camera.set_rotation(rotation);
+        }
+        self.previous_mouse = mouse;
+        self.mouse_looking = input.mouse_right;
+
+        if self.ui.wants_keyboard() || self.dialog.is_some() || is_ctrl_down(input) {
+            return;
+        }
+
+        let bindings = &self.settings.key_bindings;
+        let movement = self.camera.forward() * input.axis(bindings.forward, bindings.backward)
+            + self.camera.right() * input.axis(bindings.right, bindings.left)
+            + Vec3::Y * input.axis(bindings.up, bindings.down);
+        self.camera
+            .set_position(self.camera.position() + movement * FLY_SPEED * delta_time);
+    }
+
+    /// handles the editor's shortcuts, which are ignored while typing into a text field.
+    fn update_shortcuts(&mut self, input: &Input) -> GameStateUpdate {
+        if self.ui.wants_keyboard() {
+            return GameStateUpdate::Continue;
+        }
+
+        if input.is_key_pressed(Key::Escape) {
+            if self.dialog.is_some() {
+                self.dialog = None;
+            } else if self.selection.is_some() {
+                self.selection = None;
+            } else {
+                return GameStateUpdate::Push(Box::new(GameStatePause::new()));
+            }
+        }
+        if self.dialog.is_some() {
+            return GameStateUpdate::Continue;
+        }
+
+        if input.is_key_pressed(Key::F5) {
+            return self.play_test();
+        }
+
+        if is_ctrl_down(input) {
+            let shift = input.is_key_down(Key::LeftShift) || input.is_key_down(Key::RightShift);
+            if input.is_key_pressed(Key::Y) || shift && input.is_key_pressed(Key::Z) {
+                self.history.redo(&mut self.world);
+            } else if input.is_key_pressed(Key::Z) {
+                self.history.undo(&mut self.world);
+            }
+            if input.is_key_pressed(Key::C) {
+                self.copy_selection();
+            }
+            if input.is_key_pressed(Key::V) {
+                self.paste_at_cursor();
+            }
+            if input.is_key_pressed(Key::S) {
+                self.open_dialog(DialogKind::Save);
+            }
+            if input.is_key_pressed(Key::O) {
+                self.open_dialog(DialogKind::Load);
+            }
+            return GameStateUpdate::Continue;
+        }
+
+        if input.is_key_pressed(Key::Tab) {
+            self.tool = next(&Tool::ALL, self.tool);
+        }
+        if input.is_key_pressed(Key::R) {
+            self.brush_shape = next(&BrushShape::ALL, self.brush_shape);
+        }
+        if input.is_key_pressed(Key::PageUp) {
+            self.brush_size = (self.brush_size + 1).min(MAX_BRUSH_SIZE);
+        }
+        if input.is_key_pressed(Key::PageDown) {
+            self.brush_size = (self.brush_size - 1).max(1);
+        }
+        if input.is_key_pressed(Key::G) {
+            self.show_grid = !self.show_grid;
+        }
+        if input.is_key_pressed(Key::Home) {
+            self.grid_height = (self.grid_height + 1).min(self.world.size_y() as i32);
+        }
+        if input.is_key_pressed(Key::End) {
+            self.grid_height = (self.grid_height - 1).max(0);
+        }
+        if input.is_key_pressed(Key::Delete) {
+            self.fill_selection(tiles::AIR);
+        }
+        if input.is_key_pressed(Key::F) {
+            self.fill_selection(tiles::ALL[self.palette]);
+        }
+
+        GameStateUpdate::Continue
+    }
+
+    /// works out what the mouse is over: the nearest tile, or failing that the grid.
+    fn update_cursor(&mut self, input: &Input) {
+        let origin = self.camera.position();
+        let direction = self.renderer.screen_ray(
+            &self.camera,
+            self.settings.fov,
+            self.ui.screen_size(),
+            (input.mouse_x as usize, input.mouse_y as usize),
+        );
+
+        let stroke = self.stroke.as_ref();
+        let hit = self
+            .world
+            .raycast_all(origin, direction, self.renderer.view_distance)
+            .find(|hit| !stroke.is_some_and(|stroke| stroke.contains(&hit.position)));
+        self.cursor = match hit {
+            Some(hit) => Some(Cursor {
+                position: hit.position,
+                normal: hit.normal.as_ivec3(),
+                on_tile: true,
+            }),
+            None if self.show_grid => self.grid_cursor(origin, direction),
+            None => None,
+        };
+    }
+
+    /// returns the cell of the grid a ray crosses, if it crosses the grid inside the world.
+    fn grid_cursor(&self, origin: Vec3, direction: Vec3) -> Option<Cursor> {
+        let height = self.grid_height as f32;
+        let distance = (height - origin.y) / direction.y;
+        if !distance.is_finite() || distance <= 0.0 {
+            return None;
+        }
+
+        let point = origin + direction * distance;
+        let (x, z) = (point.x.floor() as i32, point.z.floor() as i32);
+        if x < 0 || z < 0 || x >= self.world.size_x() as i32 || z >= self.world.size_z() as i32 {
+            return None;
+        }
+
+        // the cell on the far side of the grid, facing the camera, so painting goes on the near side.
+        let (y, normal) = if origin.y >= height {
+            (self.grid_height - 1, IVec3::Y)
+        } else {
+            (self.grid_height, IVec3::NEG_Y)
+        };
+        Some(Cursor {
+            position: IVec3::new(x, y, z),
+            normal,
+            on_tile: false,
+        })
+    }
+
+    /// paints, erases or selects while the left mouse button is held over the world.
+    fn update_tool(&mut self, input: &Input) {
+        let pressed = input.mouse_left && !self.mouse_left_was_down;
+        self.mouse_left_was_down = input.mouse_left;
+
+        if !input.mouse_left {
+            if self.stroke.take().is_some() {
+                self.history.end();
+            }
+            self.selecting_from = None;
+            return;
+        }
+        // drags that started over the panels aren't for the world.
+        if !pressed && self.stroke.is_none() && self.selecting_from.is_none() {
+            return;
+        }
+
+        match self.tool {
+            Tool::Paint | Tool::Erase => {
+                if self.stroke.is_none() {
+                    self.history.begin();
+                    self.stroke = Some(HashSet::new());
+                }
+
+                let tile = match self.tool {
+                    Tool::Paint => tiles::ALL[self.palette],
+                    _ => tiles::AIR,
+                };
+                if let Some(region) = self.brush_region() {
+                    let changes = match self.brush_shape {
+                        BrushShape::Cube => self.world.fill(region, tile),
+                        BrushShape::Sphere => self.world.fill_sphere(region, tile),
+                        BrushShape::Cylinder => self.world.fill_cylinder(region, Axis::Y, tile),
+                    };
+                    for change in changes {
+                        if let Some(stroke) = &mut self.stroke {
+                            let (x, y, z) = change.position;
+                            stroke.insert(IVec3::new(x as i32, y as i32, z as i32));
+                        }
+                        self.history.record(change);
+                    }
+                }
+            }
+            Tool::Select => {
+                let Some(cursor) = &self.cursor else {
+                    return;
+                };
+                let corner = cursor.tile();
+                let from = *self.selecting_from.get_or_insert(corner);
+                self.selection = Some(Region::new(from, corner));
+            }
+        }
+    }
+
+    /// returns the tiles the brush covers at the cursor, if it's over anything.
+    fn brush_region(&self) -> Option<Region> {
+        let cursor = self.cursor.as_ref()?;
+        let centre = match self.tool {
+            // pushed out along the face, so the brush rests against it rather than sinking in.
+            Tool::Paint => cursor.adjacent() + cursor.normal * ((self.brush_size - 1) / 2),
+            Tool::Erase if cursor.on_tile => cursor.position,
+            _ => return None,
+        };
+
+        let below = IVec3::splat((self.brush_size - 1) / 2);
+        let above = IVec3::splat(self.brush_size / 2);
+        Some(Region::new(centre - below, centre + above))
+    }
+
+    fn fill_selection(&mut self, tile: Tile) {
+        if let Some(selection) = self.selection {
+            let changes = self.world.fill(selection, tile);
+            self.history.record_all(changes);
+        }
+    }
+
+    fn copy_selection(&mut self) {
+        let Some(selection) = self.selection else {
+            return;
+        };
+        self.status = match self.world.copy(selection) {
+            Some(clipboard) => {
+                let (x, y, z) = clipboard.size();
+                self.region_commands.clipboard = Some(clipboard);
+                format!("copied {} by {} by {} tiles", x, y, z)
+            }
+            None => "the selection is too big to copy".to_string(),
+        };
+    }
+
+    fn paste_at_cursor(&mut self) {
+        let (Some(clipboard), Some(cursor)) = (&self.region_commands.clipboard, &self.cursor)
+        else {
+            return;
+        };
+        let changes = self.world.paste(clipboard, cursor.adjacent(), false);
+        self.history.record_all(changes);
+    }
+
+    fn open_dialog(&mut self, kind: DialogKind) {
+        self.dialog = Some(Dialog {
+            kind,
+            path: self.path.clone(),
+            error: None,
+        });
+    }
+
+    /// saves or loads the dialog's path, closing it if that worked.
+    fn confirm_dialog(&mut self) {
+        let Some(dialog) = &mut self.dialog else {
+            return;
+        };
+
+        let result = match dialog.kind {
+            DialogKind::Save => self.world.save(&dialog.path).map(|()| None),
+            DialogKind::Load => World::load(&dialog.path).map(Some),
+        };
+        match result {
+            Ok(loaded) => {
+                let path = dialog.path.clone();
+                self.dialog = None;
+                self.status = match loaded {
+                    Some(world) => {
+                        self.set_world(world);
+                        format!("loaded {}", path)
+                    }
+                    None => format!("saved {}", path),
+                };
+                self.path = path;
+            }
+            Err(e) => dialog.error = Some(e.to_string()),
+        }
+    }
+
+    /// plays the level from where the camera is, on a copy of the world so nothing done while
+    /// playing changes the level.
+    fn play_test(&mut self) -> GameStateUpdate {
+        GameStateUpdate::Push(Box::new(GameStateGameplay::play_test(
+            self.world.clone(),
+            self.camera.clone(),
+        )))
+    }
+
+    /// declares the tool panel, status bar and any open dialog.
+    fn update_ui(&mut self, input: &Input) -> GameStateUpdate {
+        let (width, height) = self.ui.screen_size();
+        let mut result = GameStateUpdate::Continue;
+
+        self.ui.begin(input);
+        self.ui
+            .begin_panel("editor", Rect::new(4, 4, PANEL_WIDTH, 236));
+        if self.ui.button(&format!("tool: {}##tool", self.tool.name())) {
+            self.tool = next(&Tool::ALL, self.tool);
+        }
+        if self
+            .ui
+            .button(&format!("shape: {}##shape", self.brush_shape.name()))
+        {
+            self.brush_shape = next(&BrushShape::ALL, self.brush_shape);
+        }
+        self.ui.begin_row();
+        if self.ui.button("-##size") {
+            self.brush_size = (self.brush_size - 1).max(1);
+        }
+        if self.ui.button("+##size") {
+            self.brush_size = (self.brush_size + 1).min(MAX_BRUSH_SIZE);
+        }
+        self.ui.label(&format!("size {}", self.brush_size));
+        self.ui.end_row();
+
+        // air is left out of the palette, since that's what erasing is for.
+        let names: Vec<&str> = tiles::ALL[1..]
+            .iter()
+            .map(|tile| tiles::name(*tile))
+            .collect();
+        let mut selected = self.palette - 1;
+        if self.ui.list("palette", &names, &mut selected, 6) {
+            self.palette = selected + 1;
+            self.tool = Tool::Paint;
+        }
+        self.ui.checkbox(
+            &format!("grid at y {}##grid", self.grid_height),
+            &mut self.show_grid,
+        );
+        self.ui.separator();
+
+        self.ui.begin_row();
+        if self.ui.button("fill") {
+            self.fill_selection(tiles::ALL[self.palette]);
+        }
+        if self.ui.button("clear") {
+            self.fill_selection(tiles::AIR);
+        }
+        if self.ui.button("copy") {
+            self.copy_selection();
+        }
+        self.ui.end_row();
+        self.ui.begin_row();
+        if self.ui.button("undo") {
+            self.history.undo(&mut self.world);
+        }
+        if self.ui.button("redo") {
+            self.history.redo(&mut self.world);
+        }
+        self.ui.end_row();
+        self.ui.begin_row();
+        if self.ui.button("save") {
+            self.open_dialog(DialogKind::Save);
+        }
+        if self.ui.button("load") {
+            self.open_dialog(DialogKind::Load);
+        }
+        if self.ui.button("play") {
+            result = self.play_test();
+        }
+        self.ui.end_row();
+        self.ui.end_panel();
+
+        let status = match (&self.cursor, self.selection) {
+            _ if !self.status.is_empty() => self.status.clone(),
+            (_, Some(selection)) => {
+                // selections are made from tiles in the world, so their size always fits.
+                let size = selection.size().unwrap_or_default();
+                format!("selected {} by {} by {}", size.x, size.y, size.z)
+            }
+            (Some(cursor), None) => {
+                let position = cursor.tile();
+                format!("{} {} {}", position.x, position.y, position.z)
+            }
+            (None, None) => String::new(),
+        };
+        self.ui
+            .begin_panel("", Rect::new(4, height as i32 - 20, width - 8, 16));
+        self.ui.label(&status);
+        self.ui.end_panel();
+
+        if let Some(dialog) = &mut self.dialog {
+            let title = match dialog.kind {
+                DialogKind::Save => "save world",
+                DialogKind::Load => "load world",
+            };
+            self.ui
+                .begin_panel(title, Rect::centred(width, height, 192, 72));
+            self.ui.text_field("path", &mut dialog.path);
+            let mut confirmed = input.is_key_pressed(Key::Enter);
+            let mut cancelled = false;
+            self.ui.begin_row();
+            confirmed |= self.ui.button(match dialog.kind {
+                DialogKind::Save => "save##confirm",
+                DialogKind::Load => "load##confirm",
+            });
+            cancelled |= self.ui.button("cancel");
+            self.ui.end_row();
+            if let Some(error) = &dialog.error {
+                self.ui.label_coloured(error, (255, 96, 96));
+            }
+            self.ui.end_panel();
+
+            if cancelled {
+                self.dialog = None;
+            } else if confirmed {
+                self.confirm_dialog();
+            }
+        }
+        self.ui.end();
+
+        result
+    }
+
+    /// draws a box around a region of tiles.
+    fn draw_region(&self, pixels: &mut Pixels, region: Region, colour: (u8, u8, u8)) {
+        // pushed out slightly, so it isn't hidden in the faces of the tiles.
+        let min = region.min.as_vec3() - Vec3::splat(0.002);
+        let max = (region.max + IVec3::ONE).as_vec3() + Vec3::splat(0.002);
+        let corner = |index: usize| {
+            Vec3::new(
+                if index & 1 == 0 { min.x } else { max.x },
+                if index & 2 == 0 { min.y } else { max.y },
+                if index & 4 == 0 { min.z } else { max.z },
+            )
+        };
+
+        // every pair of corners differing along a single axis is an edge.
+        for from in 0..8 {
+            for axis in [1, 2, 4] {
+                if from & axis == 0 {
+                    self.draw_line(pixels, corner(from), corner(from | axis), colour);
+                }
+            }
+        }
+    }
+
+    fn draw_grid(&self, pixels: &mut Pixels) {
+        let y = self.grid_height as f32;
+        let (size_x, size_z) = (self.world.size_x(), self.world.size_z());
+        for x in 0..=size_x {
+            let x = x as f32;
+            let (from, to) = (Vec3::new(x, y, 0.0), Vec3::new(x, y, size_z as f32));
+            self.draw_line(pixels, from, to, GRID_COLOUR);
+        }
+        for z in 0..=size_z {
+            let z = z as f32;
+            let (from, to) = (Vec3::new(0.0, y, z), Vec3::new(size_x as f32, y, z));
+            self.draw_line(pixels, from, to, GRID_COLOUR);
+        }
+    }
+
+    fn draw_line(&self, pixels: &mut Pixels, from: Vec3, to: Vec3, colour: (u8, u8, u8)) {
+        self.renderer
+            .draw_line(&self.camera, self.settings.fov, pixels, from, to, colour);
+    }
+}
+
+impl Default for GameStateEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState for GameStateEditor {
+    fn on_update(&mut self, delta_time: f32, input: &Input) -> GameStateUpdate {
+        let mut result = self.update_ui(input);
+        if let GameStateUpdate::Continue = result {
+            result = self.update_shortcuts(input);
+        }
+
+        self.update_camera(delta_time, input);
+        self.history.advance(delta_time);
+
+        // the mouse is busy with the panels while it's over them.
+        let over_ui = self.ui.wants_mouse() || self.dialog.is_some();
+        if over_ui && self.stroke.is_none() && self.selecting_from.is_none() {
+            self.cursor = None;
+            self.mouse_left_was_down = input.mouse_left;
+        } else {
+            self.update_cursor(input);
+            self.update_tool(input);
+        }
+
+        if input.mouse_left {
+            self.status.clear();
+        }
+
+        result
+    }
+
+    fn on_draw(&mut self, _delta_time: f32, pixels: &mut Pixels) {
+        {
+            let _scope = profiler::scope("raycast");
+            self.renderer
+                .render(&self.world, &self.camera, self.settings.fov, pixels);
+        }
+
+        if self.show_grid {
+            self.draw_grid(pixels);
+        }
+        self.draw_region(pixels, self.world.bounds(), BOUNDS_COLOUR);
+        if let Some(region) = self.brush_region() {
+            self.draw_region(pixels, region, BRUSH_COLOUR);
+        } else if let (Some(cursor), Tool::Select) = (&self.cursor, self.tool) {
+            let corner = cursor.tile();
+            self.draw_region(pixels, Region::new(corner, corner), BRUSH_COLOUR);
+        }
+        if let Some(selection) = self.selection {
+            self.draw_region(pixels, selection, SELECTION_COLOUR);
+        }
+
+        self.ui.draw(pixels);
+    }
+
+    fn wants_cursor_grabbed(&self) -> bool {
+        self.mouse_looking
+    }
+
+    fn on_settings_changed(&mut self, settings: &Settings) {
+        self.settings = settings.clone();
+    }
+
+    fn register_commands(&self, console: &mut Console) {
+        self.region_commands.register_commands(console);
+        console.register_command("undo", "undoes the last edit to the world, also ctrl+z");
+        console.register_command("redo", "redoes the last undone edit, also ctrl+y");
+        console.register_command(
+            "new",
+            "starts editing an empty world: new <size x> <size y> <size z>",
+        );
+        console.register_command("load", "loads a world from a .arc file: load <path>");
+        console.register_command("save", "saves the world to a .arc file: save <path>");
+    }
+
+    fn on_command(&mut self, args: &[&str], console: &mut Console) -> bool {
+        if self
+            .region_commands
+            .on_command(args, &mut self.world, &mut self.history, console)
+        {
+            return true;
+        }
+
+        match args {
+            ["undo"] => {
+                if !self.history.undo(&mut self.world) {
+                    console.print("nothing to undo");
+                }
+            }
+            ["redo"] => {
+                if !self.history.redo(&mut self.world) {
+                    console.print("nothing to redo");
+                }
+            }
+            ["new", x, y, z] => match (x.parse(), y.parse(), z.parse()) {
+                (Ok(x), Ok(y), Ok(z)) if World::is_valid_size(x, y, z) => {
+                    self.set_world(World::with_size(x, y, z))
+                }
+                _ => console.print("usage: new <size x> <size y> <size z>"),
+            },
+            ["load", path] => match World::load(path) {
+                Ok(world) => {
+                    self.set_world(world);
+                    self.path = path.to_string();
+                    console.print(&format!("loaded {}", path));
+                }
+                Err(e) => console.print(&format!("couldn't load {}: {}", path, e)),
+            },
+            ["save", path] => match self.world.save(path) {
+                Ok(()) => {
+                    self.path = path.to_string();
+                    console.print(&format!("saved {}", path));
+                }
+                Err(e) => console.print(&format!("couldn't save {}: {}", path, e)),
+            },
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+fn is_ctrl_down(input: &Input) -> bool {
+    input.is_key_down(Key::LeftCtrl) || input.is_key_down(Key::RightCtrl)
+}
+
+/// returns the item after `current` in a list, wrapping around to the first.
+fn next<T: Copy + PartialEq>(items: &[T], current: T) -> T {
+    let index = items.iter().position(|item| *item == current).unwrap_or(0);
+    items[(index + 1) % items.len()]
+}
@@ -1,12 +1,95 @@
-use super::{GameState, GameStateUpdate};
-use crate::framework::{Input, Pixels};
+use super::{
+    Billboard, Console, Emitter, Fog, GameState, GameStatePause, GameStateUpdate, Particles,
+    RegionCommands, Renderer, Settings, MAX_BOUNCES,
+};
+use crate::ecs::{self, systems, Lifetime, Registry, Sprite, System};
+use crate::framework::{profiler, Input, Key, Pixels};
 use crate::math::Transform;
-use crate::world::World;
-use glam::{Mat4, Vec2, Vec3, Vec4};
+use crate::physics::{Aabb, Body, FixedTimestep, PHYSICS_TIME_STEP};
+use crate::ui::{Rect, Ui};
+use crate::world::{tiles, EditHistory, RaycastResult, Region, Tile, World};
+use crate::worldgen::{Generator, GeneratorConfig};
+use glam::{IVec3, Vec2, Vec3};
+use std::rc::Rc;
+
+/// how fast the camera flies with noclip, in tiles per second.
+const MOVE_SPEED: f32 = 4.0;
+
+/// how far above the player's feet the camera is.
+const EYE_HEIGHT: f32 = 1.6;
+
+/// how far away tiles can be broken or placed.
+const REACH: f32 = 5.0;
+
+/// the tiles that can be placed, picked with the number keys or the mouse wheel.
+const HOTBAR: [Tile; 9] = [
+    tiles::STONE,
+    tiles::DIRT,
+    tiles::GRASS,
+    tiles::SAND,
+    tiles::WOOD,
+    tiles::LEAVES,
+    tiles::GLASS,
+    tiles::LAMP,
+    tiles::POLISHED_STONE,
+];
+
+/// the entity systems run every frame, in order.
+const SYSTEMS: [System; 2] = [systems::movement, systems::lifetimes];
+
+/// the entity systems run at the physics rate, alongside the player's body.
+const FIXED_SYSTEMS: [System; 1] = [systems::bodies];
+
+/// how long entities dropped with the `spawn` command last, in seconds.
+const SPAWNED_LIFETIME: f32 = 30.0;
+/// the most entities one `spawn` command can drop.
+const MAX_SPAWN: usize = 1000;
+/// how wide, tall and deep spawned crates are, in tiles.
+const CRATE_SIZE: f32 = 0.5;
+/// how many particles can be alive at once.
+const MAX_PARTICLES: usize = 4096;
+/// how many bits of debris fly from each tile an explosion destroys.
+const EXPLOSION_DEBRIS: usize = 2;
+
+/// how big each tile shown in the hotbar is, in pixels.
+const SLOT_SIZE: usize = 10;
+
+/// how many pixels the mouse can move with the right button held and still count as a click
+/// rather than looking around.
+const CLICK_SLOP: u32 = 3;
 
 pub struct GameStateGameplay {
     world: World,
+    /// every edit made to `world`, for undo and redo.
+    history: EditHistory,
+    /// everything in the world that isn't a tile.
+    entities: Registry,
+    /// what spawned crates look like, unless another image is given to `spawn`.
+    crate_image: Rc<Pixels>,
+    particles: Particles,
+    /// fill, copy, paste and the other region commands, with their clipboard.
+    region_commands: RegionCommands,
     camera: Transform,
+    player: Body,
+    timestep: FixedTimestep,
+    /// flies the camera through tiles instead of walking.
+    noclip: bool,
+    renderer: Renderer,
+    ui: Ui,
+    settings: Settings,
+    /// the mouse position last frame, for mouse look.
+    previous_mouse: (u32, u32),
+    /// whether the right mouse button is held to look around, which grabs the cursor.
+    mouse_looking: bool,
+    /// the tile under the crosshair, if it's within reach.
+    target: Option<RaycastResult>,
+    /// the index in `HOTBAR` of the tile to place.
+    selected: usize,
+    mouse_left_was_down: bool,
+    /// where the right mouse button went down, to tell clicks from mouse look.
+    right_pressed_at: Option<(u32, u32)>,
+    /// whether this was pushed from the editor, which is popped back to when play-testing stops.
+    play_test: bool,
 }
 
 impl GameStateGameplay {
@@ -27,73 +110,823 @@ impl GameStateGameplay {
             Vec3::ONE,
         );
 
-        Self { world, camera }
+        Self::with_world(world, camera)
+    }
+
+    /// starts playing in an existing world, standing where the camera is.
+    pub fn with_world(world: World, camera: Transform) -> Self {
+        Self {
+            world,
+            history: EditHistory::new(),
+            entities: Registry::new(),
+            crate_image: Rc::new(crate_image()),
+            particles: Particles::new(MAX_PARTICLES),
+            region_commands: RegionCommands::new(),
+            player: Body::new(camera.position() - Vec3::Y * EYE_HEIGHT),
+            camera,
+            timestep: FixedTimestep::new(PHYSICS_TIME_STEP),
+            noclip: false,
+            renderer: Renderer::new(),
+            ui: Ui::new(),
+            settings: Settings::load(),
+            previous_mouse: (0, 0),
+            mouse_looking: false,
+            target: None,
+            selected: 0,
+            mouse_left_was_down: false,
+            right_pressed_at: None,
+            play_test: false,
+        }
     }
 
-    pub fn render_world(&self, pixels: &mut Pixels) {
-        let view = self.camera.view();
-        let aspect = pixels.width() as f32 / pixels.height() as f32;
-        let proj = Mat4::perspective_infinite_lh(90.0_f32.to_radians(), aspect, 0.001);
+    /// starts a play-test of a world from the editor, which f5 returns to.
+    pub fn play_test(world: World, camera: Transform) -> Self {
+        Self {
+            play_test: true,
+            ..Self::with_world(world, camera)
+        }
+    }
 
-        for y in 0..pixels.width() {
-            for x in 0..pixels.height() {
-                let screen_coord = Vec2::new(
-                    2.0 * x as f32 / pixels.width() as f32 - 1.0,
-                    2.0 * y as f32 / pixels.height() as f32 - 1.0,
-                );
-                let dir = {
-                    let clip_space = Vec4::new(screen_coord.x, screen_coord.y, 1.0, 1.0);
-                    let direction = (proj * view).inverse() * clip_space;
-                    Vec3::new(direction.x, direction.y, direction.z).normalize()
-                };
+    pub fn render_world(&mut self, pixels: &mut Pixels) {
+        {
+            let _scope = profiler::scope("raycast");
+            self.renderer
+                .render(&self.world, &self.camera, self.settings.fov, pixels);
+        }
 
-                if let Some(result) = self.world.raycast(self.camera.position(), dir, 16) {
-                    pixels.draw_pixel(
-                        (x, y),
-                        (
-                            (result.normal.x * 255.0).floor() as u8,
-                            (result.normal.y * 255.0).floor() as u8,
-                            (result.normal.z * 255.0).floor() as u8,
-                        ),
-                    );
-                }
+        let _scope = profiler::scope("billboards");
+        let mut billboards = Vec::new();
+        for entity in self.entities.query::<(ecs::Transform, Sprite)>() {
+            let (Some(transform), Some(sprite)) = (
+                self.entities.get::<ecs::Transform>(entity),
+                self.entities.get::<Sprite>(entity),
+            ) else {
+                continue;
+            };
+            let position = transform.position();
+            let to_camera = self.camera.position() - position;
+            billboards.push(Billboard {
+                source: sprite.frame(transform.rotation().y, to_camera),
+                ..Billboard::new(position, sprite.size, &sprite.image)
+            });
+        }
+        self.renderer.draw_billboards(
+            &self.world,
+            &self.camera,
+            self.settings.fov,
+            pixels,
+            &mut billboards,
+        );
+        self.renderer
+            .draw_particles(&self.camera, self.settings.fov, pixels, &self.particles);
+    }
+
+    /// looks around and flies with noclip, returning which way the player wants to walk and
+    /// whether they want to jump, for `update_physics`.
+    pub fn update_camera(&mut self, delta_time: f32, input: &Input) -> (Vec2, bool) {
+        // look around while the right mouse button is held.
+        let mouse = (input.mouse_x, input.mouse_y);
+        if input.mouse_right {
+            let delta_x = mouse.0 as f32 - self.previous_mouse.0 as f32;
+            let delta_y = mouse.1 as f32 - self.previous_mouse.1 as f32;
+            let sensitivity = self.settings.mouse_sensitivity;
+
+            let mut rotation = self.camera.rotation();
+            rotation.y += delta_x * sensitivity;
+            rotation.x = (rotation.x + delta_y * sensitivity).clamp(-89.0, 89.0);
+            self.camera.set_rotation(rotation);
+        }
+        self.previous_mouse = mouse;
+        self.mouse_looking = input.mouse_right;
+
+        let bindings = &self.settings.key_bindings;
+        let forward_move = input.axis(bindings.forward, bindings.backward);
+        let sideway_move = input.axis(bindings.right, bindings.left);
+
+        if self.noclip {
+            let vertical_move = input.axis(bindings.up, bindings.down);
+            let movement = self.camera.forward() * forward_move
+                + self.camera.right() * sideway_move
+                + Vec3::Y * vertical_move;
+            self.camera
+                .set_position(self.camera.position() + movement * MOVE_SPEED * delta_time);
+            return (Vec2::ZERO, false);
+        }
+
+        // walks along the ground whichever way the camera is pitched.
+        let flatten = |direction: Vec3| Vec2::new(direction.x, direction.z).normalize_or_zero();
+        let walk = (flatten(self.camera.forward()) * forward_move
+            + flatten(self.camera.right()) * sideway_move)
+            .clamp_length_max(1.0);
+        let jump = input.is_key_down(bindings.up);
+        (walk, jump)
+    }
+
+    /// steps the player's body and the entities' fixed rate systems at the physics rate.
+    pub fn update_physics(&mut self, delta_time: f32, walk: Vec2, jump: bool) {
+        for _ in 0..self.timestep.advance(delta_time) {
+            let step = self.timestep.step();
+            if !self.noclip {
+                self.player.step(&self.world, walk, jump, step);
+            }
+            for system in FIXED_SYSTEMS {
+                system(&mut self.entities, &self.world, step);
             }
         }
+
+        if !self.noclip {
+            self.camera.set_position(
+                self.player.interpolated_position(self.timestep.alpha()) + Vec3::Y * EYE_HEIGHT,
+            );
+        }
     }
 
-    pub fn render_debug_ui(&self, delta_time: f32, pixels: &mut Pixels) {
-        pixels.draw_text((8, 8), &format!("fps: {:.0}", (1.0 / delta_time).floor()));
-        pixels.draw_text(
-            (8, 16),
-            &format!(
-                "pos: {:.2} {:.2} {:.2}",
-                self.camera.position().x,
-                self.camera.position().y,
-                self.camera.position().z
-            ),
+    /// runs the systems which don't need a fixed rate, and moves particles.
+    pub fn update_entities(&mut self, delta_time: f32) {
+        let _scope = profiler::scope("entities");
+        for system in SYSTEMS {
+            system(&mut self.entities, &self.world, delta_time);
+        }
+        self.particles.update(&self.world, delta_time);
+    }
+
+    /// breaks the targeted tile on left click, and places the selected tile against it on right click.
+    pub fn update_building(&mut self, input: &Input) {
+        self.target = self
+            .world
+            .raycast_all(
+                self.camera.position(),
+                self.camera.forward(),
+                REACH.ceil() as usize + 1,
+            )
+            .take_while(|hit| hit.distance <= REACH)
+            .find(|hit| tiles::is_solid(hit.tile));
+
+        let number_keys = [
+            Key::Num1,
+            Key::Num2,
+            Key::Num3,
+            Key::Num4,
+            Key::Num5,
+            Key::Num6,
+            Key::Num7,
+            Key::Num8,
+            Key::Num9,
+        ];
+        if let Some(index) = number_keys
+            .iter()
+            .position(|key| input.is_key_pressed(*key))
+        {
+            self.selected = index;
+        }
+        if input.mouse_wheel > 0.0 {
+            self.selected = (self.selected + HOTBAR.len() - 1) % HOTBAR.len();
+        } else if input.mouse_wheel < 0.0 {
+            self.selected = (self.selected + 1) % HOTBAR.len();
+        }
+
+        let left_clicked = input.mouse_left && !self.mouse_left_was_down;
+        self.mouse_left_was_down = input.mouse_left;
+
+        let mouse = (input.mouse_x, input.mouse_y);
+        let right_clicked = match (input.mouse_right, self.right_pressed_at) {
+            (true, None) => {
+                self.right_pressed_at = Some(mouse);
+                false
+            }
+            (false, Some(pressed_at)) => {
+                self.right_pressed_at = None;
+                pressed_at.0.abs_diff(mouse.0) + pressed_at.1.abs_diff(mouse.1) <= CLICK_SLOP
+            }
+            _ => false,
+        };
+
+        let ctrl = input.is_key_down(Key::LeftCtrl) || input.is_key_down(Key::RightCtrl);
+        let shift = input.is_key_down(Key::LeftShift) || input.is_key_down(Key::RightShift);
+        if ctrl && (input.is_key_pressed(Key::Y) || shift && input.is_key_pressed(Key::Z)) {
+            self.history.redo(&mut self.world);
+        } else if ctrl && input.is_key_pressed(Key::Z) {
+            self.history.undo(&mut self.world);
+        }
+
+        if left_clicked {
+            if let Some(target) = &self.target {
+                let position = target.position;
+                let debris = Emitter::debris(tiles::colour(target.tile));
+                self.particles
+                    .emit(&debris, position.as_vec3() + Vec3::splat(0.5));
+                self.history.set(
+                    &mut self.world,
+                    position.x as usize,
+                    position.y as usize,
+                    position.z as usize,
+                    tiles::AIR,
+                );
+            }
+        } else if right_clicked {
+            if let Some(position) = self.placement() {
+                self.history.set(
+                    &mut self.world,
+                    position.x as usize,
+                    position.y as usize,
+                    position.z as usize,
+                    HOTBAR[self.selected],
+                );
+            }
+        }
+    }
+
+    /// returns where a tile would be placed against the target, if there's room for one.
+    fn placement(&self) -> Option<IVec3> {
+        let position = self.target.as_ref()?.adjacent();
+        if position.min_element() < 0 {
+            return None;
+        }
+
+        let tile = self.world.get(
+            position.x as usize,
+            position.y as usize,
+            position.z as usize,
+        )?;
+        if tiles::is_solid(tile) {
+            return None;
+        }
+
+        // tiles can't be placed where the player is standing, unless they're flying through tiles anyway.
+        let bounds = Aabb::new(position.as_vec3(), position.as_vec3() + Vec3::ONE);
+        if !self.noclip && bounds.intersects(&self.player.aabb()) {
+            return None;
+        }
+
+        Some(position)
+    }
+
+    /// draws the outline of the targeted face, the crosshair and the hotbar.
+    fn draw_building(&self, pixels: &mut Pixels) {
+        if let Some(target) = &self.target {
+            let normal = target.normal;
+            let axis = if normal.x != 0.0 {
+                0
+            } else if normal.y != 0.0 {
+                1
+            } else {
+                2
+            };
+            let (u, v) = (Vec3::AXES[(axis + 1) % 3], Vec3::AXES[(axis + 2) % 3]);
+
+            // lifted slightly off the face, so it isn't hidden in the tile's edge.
+            let mut corner = target.position.as_vec3() + normal * 0.002;
+            if normal[axis] > 0.0 {
+                corner += Vec3::AXES[axis];
+            }
+            let corners = [corner, corner + u, corner + u + v, corner + v];
+            for (index, from) in corners.iter().enumerate() {
+                let to = corners[(index + 1) % corners.len()];
+                self.renderer.draw_line(
+                    &self.camera,
+                    self.settings.fov,
+                    pixels,
+                    *from,
+                    to,
+                    (255, 255, 255),
+                );
+            }
+        }
+
+        let (centre_x, centre_y) = (pixels.width() as i32 / 2, pixels.height() as i32 / 2);
+        pixels.draw_line(
+            (centre_x - 3, centre_y),
+            (centre_x + 3, centre_y),
+            (255, 255, 255),
         );
-        pixels.draw_text(
-            (8, 24),
-            &format!(
-                "dir: {:.2} {:.2} {:.2}",
-                self.camera.rotation().x,
-                self.camera.rotation().y,
-                self.camera.rotation().z
-            ),
+        pixels.draw_line(
+            (centre_x, centre_y - 3),
+            (centre_x, centre_y + 3),
+            (255, 255, 255),
         );
+
+        let width = HOTBAR.len() * (SLOT_SIZE + 2);
+        let left = (pixels.width() as i32 - width as i32) / 2;
+        let top = pixels.height() as i32 - SLOT_SIZE as i32 - 4;
+        for (index, tile) in HOTBAR.iter().enumerate() {
+            let x = left + (index * (SLOT_SIZE + 2)) as i32;
+            pixels.fill_rect((x, top), (SLOT_SIZE, SLOT_SIZE), tiles::colour(*tile));
+            let border = if index == self.selected {
+                (255, 255, 255)
+            } else {
+                (32, 32, 32)
+            };
+            pixels.draw_rect((x - 1, top - 1), (SLOT_SIZE + 2, SLOT_SIZE + 2), border);
+        }
+    }
+
+    /// drops a small body a little way in front of the camera, spread out sideways by `index`.
+    fn spawn_crate(&mut self, index: usize, sprite: Sprite) {
+        let side = if index.is_multiple_of(2) { -1.0 } else { 1.0 };
+        let offset = side * index.div_ceil(2) as f32 * 0.6;
+        let position =
+            self.camera.position() + self.camera.forward() * 2.0 + self.camera.right() * offset;
+
+        let mut body = Body::new(position);
+        body.width = CRATE_SIZE;
+        body.height = CRATE_SIZE;
+
+        // turned to face the camera, so directional sprites start off showing their front.
+        let mut transform = ecs::Transform::new(position);
+        transform.set_rotation(Vec3::new(0.0, self.camera.rotation().y + 180.0, 0.0));
+
+        let entity = self.entities.spawn();
+        self.entities.insert(entity, transform);
+        self.entities.insert(entity, body);
+        self.entities.insert(entity, sprite);
+        self.entities.insert(entity, Lifetime(SPAWNED_LIFETIME));
+    }
+
+    /// blows a sphere out of the world around a tile, scattering debris from everything destroyed.
+    fn explode(&mut self, centre: IVec3, radius: i32) {
+        // nothing is further away than the world is big, and it keeps the corners from overflowing.
+        let largest = self
+            .world
+            .size_x()
+            .max(self.world.size_y())
+            .max(self.world.size_z());
+        let radius = radius.min(largest as i32);
+        let region = Region::new(centre - IVec3::splat(radius), centre + IVec3::splat(radius));
+        let changes = self.world.fill_sphere(region, tiles::AIR);
+
+        let middle = centre.as_vec3() + Vec3::splat(0.5);
+        for change in &changes {
+            let (x, y, z) = change.position;
+            let position = Vec3::new(x as f32, y as f32, z as f32) + Vec3::splat(0.5);
+            let debris = Emitter {
+                count: EXPLOSION_DEBRIS,
+                velocity: (position - middle) * 4.0,
+                ..Emitter::debris(tiles::colour(change.old))
+            };
+            self.particles.emit(&debris, position);
+        }
+        self.particles
+            .emit(&Emitter::explosion(radius as f32), middle);
+        self.history.record_all(changes);
+    }
+
+    /// puts the player on top of the middle of the world, e.g. after loading a new one.
+    fn respawn(&mut self) {
+        let (x, z) = (self.world.size_x() / 2, self.world.size_z() / 2);
+        let y = (0..self.world.size_y())
+            .rev()
+            .find(|y| self.world.get(x, *y, z).is_some_and(tiles::is_solid))
+            .map_or(0, |y| y + 1);
+
+        let feet = Vec3::new(x as f32 + 0.5, y as f32, z as f32 + 0.5);
+        self.player.teleport(feet);
+        self.camera.set_position(feet + Vec3::Y * EYE_HEIGHT);
+    }
+
+    pub fn update_debug_ui(&mut self, delta_time: f32, input: &Input) {
+        self.ui.begin(input);
+        let height = if self.play_test { 62 } else { 48 };
+        self.ui.begin_panel("", Rect::new(4, 4, 184, height));
+        self.ui
+            .label(&format!("fps: {:.0}", (1.0 / delta_time).floor()));
+        self.ui.label(&format!(
+            "pos: {:.2} {:.2} {:.2}",
+            self.camera.position().x,
+            self.camera.position().y,
+            self.camera.position().z
+        ));
+        self.ui.label(&format!(
+            "dir: {:.2} {:.2} {:.2}",
+            self.camera.rotation().x,
+            self.camera.rotation().y,
+            self.camera.rotation().z
+        ));
+        if self.play_test {
+            self.ui
+                .label_coloured("play-testing, f5 to stop", self.ui.theme().accent);
+        }
+        self.ui.end_panel();
+        self.ui.end();
     }
 }
 
 impl GameState for GameStateGameplay {
     fn on_update(&mut self, delta_time: f32, input: &Input) -> GameStateUpdate {
-        self.camera.set_position(
-            self.camera.position() + self.camera.forward() * delta_time * input.forward_move,
-        );
+        if input.is_key_pressed(Key::Escape) {
+            return GameStateUpdate::Push(Box::new(GameStatePause::new()));
+        }
+        if self.play_test && input.is_key_pressed(Key::F5) {
+            return GameStateUpdate::Pop;
+        }
+
+        let (walk, jump) = self.update_camera(delta_time, input);
+        self.update_physics(delta_time, walk, jump);
+        self.update_entities(delta_time);
+        self.history.advance(delta_time);
+        self.update_building(input);
+        self.update_debug_ui(delta_time, input);
         GameStateUpdate::Continue
     }
 
-    fn on_draw(&mut self, delta_time: f32, pixels: &mut Pixels) {
+    fn on_draw(&mut self, _delta_time: f32, pixels: &mut Pixels) {
         self.render_world(pixels);
-        self.render_debug_ui(delta_time, pixels);
+        self.draw_building(pixels);
+        self.ui.draw(pixels);
+    }
+
+    fn wants_cursor_grabbed(&self) -> bool {
+        self.mouse_looking
+    }
+
+    fn on_settings_changed(&mut self, settings: &Settings) {
+        self.settings = settings.clone();
+    }
+
+    fn register_commands(&self, console: &mut Console) {
+        console.register_command("tp", "moves the camera: tp <x> <y> <z>");
+        console.register_command("set", "sets a tile: set tile <x> <y> <z> <tile>");
+        console.register_command("noclip", "toggles flying through tiles");
+        console.register_command(
+            "generate",
+            "replaces the world with a generated one: generate <seed> [size x] [size y] [size z]",
+        );
+        console.register_command(
+            "spawn",
+            "drops crates in front of the camera to test entities: spawn [count] [image.ppm]",
+        );
+        console.register_command(
+            "entities",
+            "lists the entities in the world, or despawns them all: entities [clear]",
+        );
+        console.register_command(
+            "explode",
+            "blows up the targeted tile with debris flying everywhere: explode [radius]",
+        );
+        console.register_command("undo", "undoes the last edit to the world, also ctrl+z");
+        console.register_command("redo", "redoes the last undone edit, also ctrl+y");
+        self.region_commands.register_commands(console);
+        console.register_command(
+            "load",
+            "loads a world from a .arc or .vox file: load <path>",
+        );
+        console.register_command(
+            "save",
+            "saves the world to a .arc or .vox file: save <path>",
+        );
+        console.register_cvar(
+            "fov",
+            &self.settings.fov.to_string(),
+            "field of view, in degrees",
+        );
+        console.register_cvar(
+            "r_sun",
+            &format!(
+                "{} {} {}",
+                self.renderer.sun_direction.x,
+                self.renderer.sun_direction.y,
+                self.renderer.sun_direction.z
+            ),
+            "direction towards the sun: <x> <y> <z>",
+        );
+        console.register_cvar(
+            "r_lambert",
+            &(self.renderer.lambert as u8).to_string(),
+            "shades faces by the angle of the sun, 0 or 1",
+        );
+        console.register_cvar(
+            "r_shadows",
+            &(self.renderer.shadows as u8).to_string(),
+            "casts shadows from the sun, 0 or 1",
+        );
+        console.register_cvar(
+            "r_ao",
+            &(self.renderer.ambient_occlusion as u8).to_string(),
+            "darkens creases between tiles, 0 or 1",
+        );
+        console.register_cvar(
+            "r_underwater",
+            &(self.renderer.underwater_tint as u8).to_string(),
+            "tints the view while under water, 0 or 1",
+        );
+        console.register_cvar(
+            "r_bounces",
+            &self.renderer.max_bounces.to_string(),
+            "how many times rays reflect off shiny tiles, 0 to turn reflections off",
+        );
+        console.register_cvar(
+            "r_view_distance",
+            &self.renderer.view_distance.to_string(),
+            "how many tiles a ray crosses before showing sky",
+        );
+        console.register_cvar(
+            "r_fog",
+            &fog_cvar(self.renderer.fog),
+            "distance fog: none, linear <start> <end> or exp <density>",
+        );
+        console.register_command(
+            "skybox",
+            "draws a cubemap as the sky, from <prefix>_px.ppm etc: skybox <prefix> or skybox off",
+        );
+    }
+
+    fn on_command(&mut self, args: &[&str], console: &mut Console) -> bool {
+        if self
+            .region_commands
+            .on_command(args, &mut self.world, &mut self.history, console)
+        {
+            return true;
+        }
+
+        match args {
+            ["tp", x, y, z] => match (x.parse(), y.parse(), z.parse()) {
+                (Ok(x), Ok(y), Ok(z)) => {
+                    let position = Vec3::new(x, y, z);
+                    self.camera.set_position(position);
+                    self.player.teleport(position - Vec3::Y * EYE_HEIGHT);
+                }
+                _ => console.print("usage: tp <x> <y> <z>"),
+            },
+            ["set", "tile", x, y, z, tile] => {
+                match (x.parse(), y.parse(), z.parse(), tile.parse()) {
+                    (Ok(x), Ok(y), Ok(z), Ok(tile)) if self.world.in_bounds(x, y, z) => {
+                        self.history.set(&mut self.world, x, y, z, tile)
+                    }
+                    _ => console.print("usage: set tile <x> <y> <z> <tile>, inside of the world"),
+                }
+            }
+            ["generate", seed, size @ ..] => {
+                let size = match size {
+                    [] => Some((64, 32, 64)),
+                    [x, y, z] => match (x.parse(), y.parse(), z.parse()) {
+                        (Ok(x), Ok(y), Ok(z)) => Some((x, y, z)),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+                .filter(|&(x, y, z)| World::is_valid_size(x, y, z));
+
+                match (seed.parse(), size) {
+                    (Ok(seed), Some((size_x, size_y, size_z))) => {
+                        let mut world = World::with_size(size_x, size_y, size_z);
+                        Generator::new(GeneratorConfig {
+                            seed,
+                            ..GeneratorConfig::default()
+                        })
+                        .generate(&mut world);
+                        self.world = world;
+                        self.history.clear();
+                        self.entities.clear();
+                        self.particles.clear();
+                        self.respawn();
+                        console.print(&format!("generated world from seed {}", seed));
+                    }
+                    _ => console.print("usage: generate <seed> [size x] [size y] [size z]"),
+                }
+            }
+            ["load", path] => {
+                let result = if path.ends_with(".vox") {
+                    World::load_vox(path)
+                } else {
+                    World::load(path)
+                };
+                match result {
+                    Ok(world) => {
+                        self.world = world;
+                        self.history.clear();
+                        self.entities.clear();
+                        self.particles.clear();
+                        self.respawn();
+                        console.print(&format!("loaded {}", path));
+                    }
+                    Err(e) => console.print(&format!("couldn't load {}: {}", path, e)),
+                }
+            }
+            ["save", path] => {
+                let result = if path.ends_with(".vox") {
+                    self.world.save_vox(path, None)
+                } else {
+                    self.world.save(path)
+                };
+                match result {
+                    Ok(()) => console.print(&format!("saved {}", path)),
+                    Err(e) => console.print(&format!("couldn't save {}: {}", path, e)),
+                }
+            }
+            ["spawn", args @ ..] => {
+                let (count, path) = match args {
+                    [] => (Some(1), None),
+                    [count] => (count.parse().ok(), None),
+                    [count, path] => (count.parse().ok(), Some(*path)),
+                    _ => (None, None),
+                };
+                let Some(count) = count.filter(|count| *count <= MAX_SPAWN) else {
+                    console.print(&format!(
+                        "usage: spawn [count, up to {}] [image.ppm]",
+                        MAX_SPAWN
+                    ));
+                    return true;
+                };
+
+                let size = Vec2::splat(CRATE_SIZE);
+                let sprite = match path {
+                    None => Sprite::new(self.crate_image.clone(), size),
+                    Some(path) => match Pixels::load_ppm(path) {
+                        Ok(image) if image.width() == 0 || image.height() == 0 => {
+                            console.print(&format!("{} is empty", path));
+                            return true;
+                        }
+                        // sheets of 8 square frames turn with the entity.
+                        Ok(image) if image.width() == image.height() * 8 => {
+                            Sprite::directional(Rc::new(image), size)
+                        }
+                        Ok(image) => Sprite::new(Rc::new(image), size),
+                        Err(e) => {
+                            console.print(&format!("couldn't load {}: {}", path, e));
+                            return true;
+                        }
+                    },
+                };
+                for index in 0..count {
+                    self.spawn_crate(index, sprite.clone());
+                }
+            }
+            ["entities"] => {
+                console.print(&format!("{} entities", self.entities.len()));
+                for entity in self.entities.entities().take(10) {
+                    let line = match self.entities.get::<ecs::Transform>(entity) {
+                        Some(transform) => {
+                            let position = transform.position();
+                            format!(
+                                "{}: {:.2} {:.2} {:.2}",
+                                entity, position.x, position.y, position.z
+                            )
+                        }
+                        None => entity.to_string(),
+                    };
+                    console.print(&line);
+                }
+            }
+            ["entities", "clear"] => self.entities.clear(),
+            ["explode", radius @ ..] => {
+                let radius = match radius {
+                    [] => Some(3),
+                    [radius] => radius.parse().ok().filter(|radius| *radius > 0),
+                    _ => None,
+                };
+                match (radius, &self.target) {
+                    (None, _) => console.print("usage: explode [radius]"),
+                    (Some(_), None) => console.print("nothing targeted to explode"),
+                    (Some(radius), Some(target)) => {
+                        let centre = target.position;
+                        self.explode(centre, radius);
+                    }
+                }
+            }
+            ["undo"] => {
+                if !self.history.undo(&mut self.world) {
+                    console.print("nothing to undo");
+                }
+            }
+            ["redo"] => {
+                if !self.history.redo(&mut self.world) {
+                    console.print("nothing to redo");
+                }
+            }
+            ["noclip"] => {
+                self.noclip = !self.noclip;
+                if !self.noclip {
+                    // lands wherever the camera flew to.
+                    self.player
+                        .teleport(self.camera.position() - Vec3::Y * EYE_HEIGHT);
+                }
+                console.print(if self.noclip {
+                    "noclip on"
+                } else {
+                    "noclip off"
+                });
+            }
+            ["fov", value] => match value.parse::<f32>() {
+                Ok(fov) => {
+                    self.settings.fov = fov.clamp(30.0, 150.0);
+                    console.set_cvar("fov", &self.settings.fov.to_string());
+                }
+                Err(_) => console.print("fov must be a number"),
+            },
+            ["r_sun", value] => {
+                let parts: Vec<f32> = value
+                    .split_whitespace()
+                    .filter_map(|part| part.parse().ok())
+                    .collect();
+                match parts.as_slice() {
+                    [x, y, z] => {
+                        self.renderer.sun_direction = Vec3::new(*x, *y, *z);
+                        console.set_cvar("r_sun", &format!("{} {} {}", x, y, z));
+                    }
+                    _ => console.print("usage: r_sun <x> <y> <z>"),
+                }
+            }
+            ["r_lambert", value] => {
+                self.renderer.lambert = *value != "0";
+                console.set_cvar("r_lambert", &(self.renderer.lambert as u8).to_string());
+            }
+            ["r_shadows", value] => {
+                self.renderer.shadows = *value != "0";
+                console.set_cvar("r_shadows", &(self.renderer.shadows as u8).to_string());
+            }
+            ["r_ao", value] => {
+                self.renderer.ambient_occlusion = *value != "0";
+                console.set_cvar("r_ao", &(self.renderer.ambient_occlusion as u8).to_string());
+            }
+            ["r_underwater", value] => {
+                self.renderer.underwater_tint = *value != "0";
+                console.set_cvar(
+                    "r_underwater",
+                    &(self.renderer.underwater_tint as u8).to_string(),
+                );
+            }
+            ["r_bounces", value] => match value.parse::<u32>() {
+                Ok(bounces) => {
+                    if bounces > MAX_BOUNCES {
+                        console.print(&format!("r_bounces can't be more than {}", MAX_BOUNCES));
+                    }
+                    self.renderer.max_bounces = bounces.min(MAX_BOUNCES);
+                    console.set_cvar("r_bounces", &self.renderer.max_bounces.to_string());
+                }
+                Err(_) => console.print("r_bounces must be a whole number"),
+            },
+            ["r_view_distance", value] => match value.parse() {
+                Ok(distance) => {
+                    self.renderer.view_distance = distance;
+                    console.set_cvar("r_view_distance", &distance.to_string());
+                }
+                Err(_) => console.print("r_view_distance must be a whole number"),
+            },
+            ["r_fog", value] => {
+                let parts: Vec<&str> = value.split_whitespace().collect();
+                let fog = match parts.as_slice() {
+                    ["none"] => Some(Fog::None),
+                    ["linear", start, end] => match (start.parse(), end.parse()) {
+                        (Ok(start), Ok(end)) => Some(Fog::Linear { start, end }),
+                        _ => None,
+                    },
+                    ["exp", density] => density
+                        .parse()
+                        .ok()
+                        .map(|density| Fog::Exponential { density }),
+                    _ => None,
+                };
+                match fog {
+                    Some(fog) => {
+                        self.renderer.fog = fog;
+                        console.set_cvar("r_fog", &fog_cvar(fog));
+                    }
+                    None => console.print(
+                        "usage: r_fog none, r_fog linear <start> <end> or r_fog exp <density>",
+                    ),
+                }
+            }
+            ["skybox", "off"] => self.renderer.sky.cubemap = None,
+            ["skybox", prefix] => match self.renderer.sky.load_cubemap(prefix) {
+                Ok(()) => console.print(&format!("loaded skybox {}", prefix)),
+                Err(e) => console.print(&format!("couldn't load skybox {}: {}", prefix, e)),
+            },
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+/// returns fog as it's written for the `r_fog` cvar.
+fn fog_cvar(fog: Fog) -> String {
+    match fog {
+        Fog::None => "none".to_string(),
+        Fog::Linear { start, end } => format!("linear {} {}", start, end),
+        Fog::Exponential { density } => format!("exp {}", density),
+    }
+}
+
+/// draws the picture of a wooden crate spawned entities are shown with by default.
+fn crate_image() -> Pixels {
+    const SIZE: usize = 16;
+    const FRAME: (u8, u8, u8) = (96, 60, 28);
+    const PLANK: (u8, u8, u8) = (170, 118, 62);
+    const GAP: (u8, u8, u8) = (128, 84, 40);
+
+    let mut image = Pixels::new(SIZE, SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let edge = x < 2 || y < 2 || x >= SIZE - 2 || y >= SIZE - 2;
+            let brace = x.abs_diff(y) <= 1;
+            let colour = if edge || brace {
+                FRAME
+            } else if y % 4 == 1 {
+                GAP
+            } else {
+                PLANK
+            };
+            image.draw_pixel((x, y), colour);
+        }
     }
+    image
 }
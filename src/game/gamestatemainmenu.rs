@@ -0,0 +1,52 @@
+use super::{GameState, GameStateEditor, GameStateGameplay, GameStateSettings, GameStateUpdate};
+use crate::framework::{Input, Pixels};
+use crate::ui::{Rect, Ui};
+
+/// the title screen shown when the game starts.
+pub struct GameStateMainMenu {
+    ui: Ui,
+}
+
+impl GameStateMainMenu {
+    pub fn new() -> Self {
+        Self { ui: Ui::new() }
+    }
+}
+
+impl Default for GameStateMainMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState for GameStateMainMenu {
+    fn on_update(&mut self, _delta_time: f32, input: &Input) -> GameStateUpdate {
+        let (width, height) = self.ui.screen_size();
+        let mut result = GameStateUpdate::Continue;
+
+        self.ui.begin(input);
+        self.ui
+            .begin_panel("arcana", Rect::centred(width, height, 112, 72));
+        if self.ui.button("play") {
+            result = GameStateUpdate::Push(Box::new(GameStateGameplay::new()));
+        }
+        if self.ui.button("editor") {
+            result = GameStateUpdate::Push(Box::new(GameStateEditor::new()));
+        }
+        if self.ui.button("settings") {
+            result = GameStateUpdate::Push(Box::new(GameStateSettings::new()));
+        }
+        if self.ui.button("quit") {
+            result = GameStateUpdate::Quit;
+        }
+        self.ui.end_panel();
+        self.ui.end();
+
+        result
+    }
+
+    fn on_draw(&mut self, _delta_time: f32, pixels: &mut Pixels) {
+        pixels.fill((16, 16, 24));
+        self.ui.draw(pixels);
+    }
+}
@@ -0,0 +1,67 @@
+use super::{GameState, GameStateMainMenu, GameStateSettings, GameStateUpdate};
+use crate::framework::{Input, Key, Pixels};
+use crate::ui::{Rect, Ui};
+
+/// pushed over gameplay when escape is pressed; the frozen gameplay frame stays visible behind it.
+pub struct GameStatePause {
+    ui: Ui,
+    /// the frame that was on screen when the game was paused.
+    background: Option<Pixels>,
+}
+
+impl GameStatePause {
+    pub fn new() -> Self {
+        Self {
+            ui: Ui::new(),
+            background: None,
+        }
+    }
+}
+
+impl Default for GameStatePause {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState for GameStatePause {
+    fn on_update(&mut self, _delta_time: f32, input: &Input) -> GameStateUpdate {
+        let (width, height) = self.ui.screen_size();
+        let mut result = GameStateUpdate::Continue;
+
+        if input.is_key_pressed(Key::Escape) {
+            result = GameStateUpdate::Pop;
+        }
+
+        self.ui.begin(input);
+        self.ui
+            .begin_panel("paused", Rect::centred(width, height, 128, 58));
+        if self.ui.button("resume") {
+            result = GameStateUpdate::Pop;
+        }
+        if self.ui.button("settings") {
+            result = GameStateUpdate::Push(Box::new(GameStateSettings::new()));
+        }
+        if self.ui.button("quit to menu") {
+            result = GameStateUpdate::Reset(Box::new(GameStateMainMenu::new()));
+        }
+        self.ui.end_panel();
+        self.ui.end();
+
+        result
+    }
+
+    fn on_draw(&mut self, _delta_time: f32, pixels: &mut Pixels) {
+        match &self.background {
+            Some(background)
+                if background.width() == pixels.width()
+                    && background.height() == pixels.height() =>
+            {
+                pixels.as_bytes_mut().copy_from_slice(background.as_bytes());
+            }
+            _ => self.background = Some(pixels.clone()),
+        }
+
+        self.ui.draw(pixels);
+    }
+}
@@ -0,0 +1,181 @@
+use super::{settings::RESOLUTIONS, GameState, GameStateUpdate, Settings};
+use crate::framework::{Input, Key, Pixels, UpscaleMode};
+use crate::ui::{Rect, Ui};
+
+/// edits and saves `Settings`; pushed from both the main menu and the pause menu.
+pub struct GameStateSettings {
+    ui: Ui,
+    settings: Settings,
+    resolution: usize,
+    /// the index of the key binding waiting for a key press, if any.
+    rebinding: Option<usize>,
+    /// the error from the last failed save, if any.
+    error: Option<String>,
+    saved: bool,
+}
+
+impl GameStateSettings {
+    pub fn new() -> Self {
+        let settings = Settings::load();
+        let resolution = resolution_index(&settings);
+
+        Self {
+            ui: Ui::new(),
+            settings,
+            resolution,
+            rebinding: None,
+            error: None,
+            saved: false,
+        }
+    }
+
+    /// consumes key presses while rebinding; escape cancels.
+    fn update_rebinding(&mut self, index: usize, input: &Input) {
+        let Some(key) = input.keys_pressed.first() else {
+            return;
+        };
+
+        if *key != Key::Escape {
+            if let Some((_, binding)) = self
+                .settings
+                .key_bindings
+                .actions_mut()
+                .into_iter()
+                .nth(index)
+            {
+                *binding = *key;
+            }
+        }
+        self.rebinding = None;
+    }
+}
+
+impl Default for GameStateSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState for GameStateSettings {
+    fn on_update(&mut self, _delta_time: f32, input: &Input) -> GameStateUpdate {
+        let (width, height) = self.ui.screen_size();
+        let mut result = GameStateUpdate::Continue;
+
+        match self.rebinding {
+            Some(index) => self.update_rebinding(index, input),
+            None if input.is_key_pressed(Key::Escape) => return GameStateUpdate::Pop,
+            None => {}
+        }
+
+        self.ui.begin(input);
+        self.ui
+            .begin_panel("settings", Rect::centred(width, height, 208, 240));
+
+        self.ui.label("resolution");
+        let resolutions: Vec<String> = RESOLUTIONS
+            .iter()
+            .map(|(w, h)| format!("{}x{}", w, h))
+            .collect();
+        if self
+            .ui
+            .list("resolution", &resolutions, &mut self.resolution, 3)
+        {
+            self.settings.window_size = RESOLUTIONS[self.resolution];
+        }
+
+        self.ui.slider("fov", &mut self.settings.fov, 30.0, 150.0);
+        self.ui.slider(
+            "sensitivity",
+            &mut self.settings.mouse_sensitivity,
+            0.05,
+            2.0,
+        );
+        self.ui.begin_row();
+        if self
+            .ui
+            .button(&format!("{}##upscale", self.settings.upscale.name()))
+        {
+            // cycles through every mode.
+            let modes = UpscaleMode::ALL;
+            let index = modes
+                .iter()
+                .position(|mode| *mode == self.settings.upscale)
+                .unwrap_or(0);
+            self.settings.upscale = modes[(index + 1) % modes.len()];
+        }
+        self.ui
+            .checkbox("dynamic res", &mut self.settings.dynamic_resolution);
+        self.ui.end_row();
+        self.ui.separator();
+
+        let rebinding = self.rebinding;
+        let mut start_rebinding = None;
+        for (index, (name, key)) in self
+            .settings
+            .key_bindings
+            .actions_mut()
+            .into_iter()
+            .enumerate()
+        {
+            self.ui.begin_row();
+            self.ui.label(&format!("{:<9}", name));
+            let text = match rebinding {
+                Some(rebinding) if rebinding == index => "press a key".to_string(),
+                _ => key.name(),
+            };
+            if self.ui.button(&format!("{}##{}", text, name)) {
+                start_rebinding = Some(index);
+            }
+            self.ui.end_row();
+        }
+        if start_rebinding.is_some() {
+            self.rebinding = start_rebinding;
+        }
+        self.ui.separator();
+
+        self.ui.begin_row();
+        if self.ui.button("save") {
+            match self.settings.save() {
+                Ok(()) => {
+                    self.saved = true;
+                    result = GameStateUpdate::Pop;
+                }
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+        if self.ui.button("cancel") {
+            result = GameStateUpdate::Pop;
+        }
+        if self.ui.button("defaults") {
+            self.settings = Settings::default();
+            self.resolution = resolution_index(&self.settings);
+        }
+        self.ui.end_row();
+
+        if let Some(error) = &self.error {
+            self.ui.label_coloured(error, (255, 96, 96));
+        }
+
+        self.ui.end_panel();
+        self.ui.end();
+
+        result
+    }
+
+    fn on_draw(&mut self, _delta_time: f32, pixels: &mut Pixels) {
+        pixels.fill((16, 16, 24));
+        self.ui.draw(pixels);
+    }
+
+    fn saved_settings(&self) -> Option<&Settings> {
+        self.saved.then_some(&self.settings)
+    }
+}
+
+/// returns the index of the settings' window size in `RESOLUTIONS`, or the first if it isn't listed.
+fn resolution_index(settings: &Settings) -> usize {
+    RESOLUTIONS
+        .iter()
+        .position(|size| *size == settings.window_size)
+        .unwrap_or(0)
+}
@@ -1,9 +1,31 @@
+mod billboard;
+mod console;
 mod game;
 mod gamestate;
+mod gamestateeditor;
 mod gamestategameplay;
 mod gamestateinit;
+mod gamestatemainmenu;
+mod gamestatepause;
+mod gamestatesettings;
+mod particles;
+mod regioncommands;
+mod renderer;
+mod settings;
+mod sky;
 
+pub use billboard::{Billboard, TRANSPARENT};
+pub use console::Console;
 pub use game::Game;
 pub use gamestate::{GameState, GameStateUpdate};
+pub use gamestateeditor::GameStateEditor;
 pub use gamestategameplay::GameStateGameplay;
 pub use gamestateinit::GameStateInit;
+pub use gamestatemainmenu::GameStateMainMenu;
+pub use gamestatepause::GameStatePause;
+pub use gamestatesettings::GameStateSettings;
+pub use particles::{Emitter, Particle, Particles};
+pub use regioncommands::RegionCommands;
+pub use renderer::{Fog, Renderer, MAX_BOUNCES};
+pub use settings::{KeyBindings, Settings, MAX_RENDER_SIZE};
+pub use sky::Sky;
@@ -0,0 +1,239 @@
+use super::sky::{from_vec3, to_vec3};
+use crate::math::Rng;
+use crate::world::{tiles, World};
+use glam::Vec3;
+
+/// how much of a particle's speed is kept when it bounces off a tile.
+const BOUNCE: f32 = 0.3;
+/// how much of a particle's sideways speed is kept each time it hits the ground.
+const FRICTION: f32 = 0.6;
+
+/// a single speck of debris, smoke or spark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// how many seconds the particle has been alive.
+    pub age: f32,
+    /// how many seconds the particle lives for in total.
+    pub lifetime: f32,
+    /// the colour when the particle is spawned, fading to `end_colour` as it dies.
+    pub start_colour: (u8, u8, u8),
+    pub end_colour: (u8, u8, u8),
+    /// how wide the particle is in tiles when spawned, changing to `end_size` as it dies.
+    pub start_size: f32,
+    pub end_size: f32,
+    /// how fast the particle falls, in tiles per second per second.
+    pub gravity: f32,
+    /// whether the particle bounces off solid tiles, rather than flying through them.
+    pub collides: bool,
+}
+
+impl Particle {
+    /// returns how far through its life the particle is, from 0.0 to 1.0.
+    pub fn life(&self) -> f32 {
+        (self.age / self.lifetime.max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+
+    pub fn colour(&self) -> (u8, u8, u8) {
+        from_vec3(to_vec3(self.start_colour).lerp(to_vec3(self.end_colour), self.life()))
+    }
+
+    pub fn size(&self) -> f32 {
+        self.start_size + (self.end_size - self.start_size) * self.life()
+    }
+
+    /// moves the particle along its velocity, bouncing off solid tiles if it collides.
+    fn step(&mut self, world: &World, delta_time: f32) {
+        self.velocity.y -= self.gravity * delta_time;
+        let delta = self.velocity * delta_time;
+        if !self.collides {
+            self.position += delta;
+            return;
+        }
+
+        // one axis at a time, so a particle sliding along the ground only loses its fall.
+        for axis in 0..3 {
+            let mut next = self.position;
+            next[axis] += delta[axis];
+            if !is_solid_at(world, next) {
+                self.position = next;
+                continue;
+            }
+
+            self.velocity[axis] *= -BOUNCE;
+            if axis == 1 {
+                self.velocity.x *= FRICTION;
+                self.velocity.z *= FRICTION;
+            }
+        }
+    }
+}
+
+/// how to spawn a burst of particles, e.g. the debris from a broken tile.
+///
+/// each property given as (min, max) is picked at random between the two for every particle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Emitter {
+    /// how many particles a burst spawns.
+    pub count: usize,
+    /// how far from the middle of the burst particles can start, in tiles along each axis.
+    pub spread: f32,
+    /// the velocity every particle starts with, before it's scattered by `speed`.
+    pub velocity: Vec3,
+    /// how fast particles fly off in a random direction, in tiles per second.
+    pub speed: (f32, f32),
+    pub lifetime: (f32, f32),
+    pub start_colour: (u8, u8, u8),
+    pub end_colour: (u8, u8, u8),
+    pub start_size: f32,
+    pub end_size: f32,
+    pub gravity: f32,
+    pub collides: bool,
+}
+
+impl Emitter {
+    /// chunks of a tile which fall and bounce around, e.g. when it's broken.
+    pub fn debris(colour: (u8, u8, u8)) -> Self {
+        Self {
+            count: 12,
+            spread: 0.4,
+            velocity: Vec3::Y * 2.0,
+            speed: (1.0, 3.0),
+            lifetime: (0.6, 1.2),
+            start_colour: colour,
+            end_colour: colour,
+            start_size: 0.12,
+            end_size: 0.04,
+            gravity: 24.0,
+            collides: true,
+        }
+    }
+
+    /// a flash of fire which drifts up and fades into smoke.
+    pub fn explosion(radius: f32) -> Self {
+        Self {
+            count: 48,
+            spread: radius * 0.5,
+            velocity: Vec3::Y,
+            speed: (radius, radius * 3.0),
+            lifetime: (0.4, 1.0),
+            start_colour: (255, 220, 120),
+            end_colour: (60, 60, 60),
+            start_size: 0.3,
+            end_size: 0.6,
+            gravity: -1.0,
+            collides: false,
+        }
+    }
+}
+
+/// every particle in the world.
+///
+/// particles live in one buffer allocated up front, and dead ones are swapped out of it, so
+/// spawning and dying never allocate. once it's full, new particles are dropped.
+pub struct Particles {
+    particles: Vec<Particle>,
+    capacity: usize,
+    rng: Rng,
+}
+
+impl Particles {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            particles: Vec::with_capacity(capacity),
+            capacity,
+            rng: Rng::new(0x7061_7274),
+        }
+    }
+
+    /// adds a particle, returning false if there's no room for it.
+    pub fn spawn(&mut self, particle: Particle) -> bool {
+        if self.particles.len() >= self.capacity {
+            return false;
+        }
+        self.particles.push(particle);
+        true
+    }
+
+    /// spawns a burst of particles around a point.
+    pub fn emit(&mut self, emitter: &Emitter, position: Vec3) {
+        for _ in 0..emitter.count {
+            let offset = self.random_vector() * emitter.spread;
+            let direction = self.random_vector().normalize_or_zero();
+            let speed = self.rng.range(emitter.speed.0, emitter.speed.1);
+            let particle = Particle {
+                position: position + offset,
+                velocity: emitter.velocity + direction * speed,
+                age: 0.0,
+                lifetime: self.rng.range(emitter.lifetime.0, emitter.lifetime.1),
+                start_colour: emitter.start_colour,
+                end_colour: emitter.end_colour,
+                start_size: emitter.start_size,
+                end_size: emitter.end_size,
+                gravity: emitter.gravity,
+                collides: emitter.collides,
+            };
+            if !self.spawn(particle) {
+                break;
+            }
+        }
+    }
+
+    /// ages and moves every particle, removing the ones which have died.
+    pub fn update(&mut self, world: &World, delta_time: f32) {
+        let mut index = 0;
+        while index < self.particles.len() {
+            let particle = &mut self.particles[index];
+            particle.age += delta_time;
+            if particle.age >= particle.lifetime {
+                self.particles.swap_remove(index);
+                continue;
+            }
+            particle.step(world, delta_time);
+            index += 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter()
+    }
+
+    /// returns a point in a cube from -1 to 1 on each axis.
+    fn random_vector(&mut self) -> Vec3 {
+        Vec3::new(
+            self.rng.range(-1.0, 1.0),
+            self.rng.range(-1.0, 1.0),
+            self.rng.range(-1.0, 1.0),
+        )
+    }
+}
+
+/// returns whether there's a solid tile at a point, with nothing solid outside the world.
+fn is_solid_at(world: &World, position: Vec3) -> bool {
+    if position.cmplt(Vec3::ZERO).any() {
+        return false;
+    }
+    let (x, y, z) = (
+        position.x as usize,
+        position.y as usize,
+        position.z as usize,
+    );
+    world.get(x, y, z).is_some_and(tiles::is_solid)
+}
@@ -0,0 +1,171 @@
+use super::Console;
+use crate::world::{Axis, Clipboard, EditHistory, Region, World};
+use glam::IVec3;
+
+/// the console commands for editing whole regions of a world at once, with a clipboard for
+/// copying and pasting; shared by every game state that edits a world.
+pub struct RegionCommands {
+    pub clipboard: Option<Clipboard>,
+}
+
+impl Default for RegionCommands {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegionCommands {
+    pub fn new() -> Self {
+        Self { clipboard: None }
+    }
+
+    pub fn register_commands(&self, console: &mut Console) {
+        console.register_command(
+            "fill",
+            "fills a region: fill <x1> <y1> <z1> <x2> <y2> <z2> <tile> [hollow|sphere|cylinder <x|y|z>]",
+        );
+        console.register_command(
+            "replace",
+            "swaps tiles in a region: replace <x1> <y1> <z1> <x2> <y2> <z2> <from> <to>",
+        );
+        console.register_command(
+            "copy",
+            "copies a region to the clipboard: copy <x1> <y1> <z1> <x2> <y2> <z2>",
+        );
+        console.register_command(
+            "paste",
+            "pastes the clipboard with its lowest corner at a position: paste <x> <y> <z> [air]",
+        );
+        console.register_command(
+            "rotate",
+            "turns the clipboard a quarter turn around an axis: rotate <x|y|z> [turns]",
+        );
+        console.register_command(
+            "mirror",
+            "flips the clipboard along an axis: mirror <x|y|z>",
+        );
+        console.register_command(
+            "schematic",
+            "saves or loads the clipboard as a .arcs file: schematic save|load <path>",
+        );
+    }
+
+    /// handles a region command, returning false if `args` isn't one.
+    pub fn on_command(
+        &mut self,
+        args: &[&str],
+        world: &mut World,
+        history: &mut EditHistory,
+        console: &mut Console,
+    ) -> bool {
+        match args {
+            ["fill", x1, y1, z1, x2, y2, z2, tile, shape @ ..] => {
+                let region = parse_region([x1, y1, z1, x2, y2, z2]);
+                let changes = match (region, tile.parse(), shape) {
+                    (Some(region), Ok(tile), []) => Some(world.fill(region, tile)),
+                    (Some(region), Ok(tile), ["hollow"]) => Some(world.fill_hollow(region, tile)),
+                    (Some(region), Ok(tile), ["sphere"]) => Some(world.fill_sphere(region, tile)),
+                    (Some(region), Ok(tile), ["cylinder", axis]) => {
+                        Axis::from_name(axis).map(|axis| world.fill_cylinder(region, axis, tile))
+                    }
+                    _ => None,
+                };
+                match changes {
+                    Some(changes) => {
+                        console.print(&format!("filled {} tiles", changes.len()));
+                        history.record_all(changes);
+                    }
+                    None => console.print(
+                        "usage: fill <x1> <y1> <z1> <x2> <y2> <z2> <tile> [hollow|sphere|cylinder <x|y|z>]",
+                    ),
+                }
+            }
+            ["replace", x1, y1, z1, x2, y2, z2, from, to] => {
+                match (
+                    parse_region([x1, y1, z1, x2, y2, z2]),
+                    from.parse(),
+                    to.parse(),
+                ) {
+                    (Some(region), Ok(from), Ok(to)) => {
+                        let changes = world.replace(region, from, to);
+                        console.print(&format!("replaced {} tiles", changes.len()));
+                        history.record_all(changes);
+                    }
+                    _ => console.print("usage: replace <x1> <y1> <z1> <x2> <y2> <z2> <from> <to>"),
+                }
+            }
+            ["copy", x1, y1, z1, x2, y2, z2] => match parse_region([x1, y1, z1, x2, y2, z2]) {
+                Some(region) => match world.copy(region) {
+                    Some(clipboard) => {
+                        let (x, y, z) = clipboard.size();
+                        console.print(&format!("copied {} by {} by {} tiles", x, y, z));
+                        self.clipboard = Some(clipboard);
+                    }
+                    None => console.print("that region is too big to copy"),
+                },
+                None => console.print("usage: copy <x1> <y1> <z1> <x2> <y2> <z2>"),
+            },
+            ["paste", x, y, z, air @ ..] => {
+                let include_air = matches!(air, ["air"]);
+                match (parse_position([x, y, z]), &self.clipboard) {
+                    (_, None) => console.print("the clipboard is empty"),
+                    (Some(position), Some(clipboard)) => {
+                        let changes = world.paste(clipboard, position, include_air);
+                        console.print(&format!("pasted {} tiles", changes.len()));
+                        history.record_all(changes);
+                    }
+                    (None, _) => console.print("usage: paste <x> <y> <z> [air]"),
+                }
+            }
+            ["rotate", axis, turns @ ..] => {
+                let turns = match turns {
+                    [] => Some(1),
+                    [turns] => turns.parse().ok(),
+                    _ => None,
+                };
+                match (Axis::from_name(axis), turns, &mut self.clipboard) {
+                    (_, _, None) => console.print("the clipboard is empty"),
+                    (Some(axis), Some(turns), Some(clipboard)) => clipboard.rotate(axis, turns),
+                    _ => console.print("usage: rotate <x|y|z> [turns]"),
+                }
+            }
+            ["mirror", axis] => match (Axis::from_name(axis), &mut self.clipboard) {
+                (_, None) => console.print("the clipboard is empty"),
+                (Some(axis), Some(clipboard)) => clipboard.mirror(axis),
+                (None, _) => console.print("usage: mirror <x|y|z>"),
+            },
+            ["schematic", "save", path] => match &self.clipboard {
+                Some(clipboard) => match clipboard.save(path) {
+                    Ok(()) => console.print(&format!("saved {}", path)),
+                    Err(e) => console.print(&format!("couldn't save {}: {}", path, e)),
+                },
+                None => console.print("the clipboard is empty"),
+            },
+            ["schematic", "load", path] => match Clipboard::load(path) {
+                Ok(clipboard) => {
+                    self.clipboard = Some(clipboard);
+                    console.print(&format!("loaded {}", path));
+                }
+                Err(e) => console.print(&format!("couldn't load {}: {}", path, e)),
+            },
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+fn parse_position(args: [&&str; 3]) -> Option<IVec3> {
+    Some(IVec3::new(
+        args[0].parse().ok()?,
+        args[1].parse().ok()?,
+        args[2].parse().ok()?,
+    ))
+}
+
+fn parse_region(args: [&&str; 6]) -> Option<Region> {
+    Some(Region::new(
+        parse_position([args[0], args[1], args[2]])?,
+        parse_position([args[3], args[4], args[5]])?,
+    ))
+}
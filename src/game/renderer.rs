@@ -0,0 +1,515 @@
+use super::billboard::Billboard;
+use super::particles::Particles;
+use super::sky::{from_vec3, to_vec3, Sky};
+use crate::framework::Pixels;
+use crate::math::Transform;
+use crate::world::{tiles, RaycastResult, Tile, World, MAX_LIGHT};
+use glam::{IVec3, Mat4, Vec2, Vec3, Vec4};
+
+/// how many tiles a shadow ray travels towards the sun before the point is considered lit.
+const SHADOW_STEPS: usize = 64;
+/// how bright a face with no light at all is drawn, so caves are dim rather than black.
+const MIN_BRIGHTNESS: f32 = 0.1;
+/// how much sky light still reaches faces turned away from, or hidden from, the sun.
+const AMBIENT: f32 = 0.35;
+/// how dark a fully occluded corner gets.
+const AO_STRENGTH: f32 = 0.5;
+/// the colour everything is multiplied by while the camera is under water.
+const UNDERWATER_TINT: Vec3 = Vec3::new(0.45, 0.65, 0.9);
+/// how much of the murky water colour is mixed in while the camera is under water.
+const UNDERWATER_HAZE: f32 = 0.35;
+/// how little of what's behind can still show through for a ray to count as blocked in the depth
+/// buffer, so billboards hide behind water but not glass.
+const DEPTH_TRANSMITTANCE: f32 = 0.5;
+
+/// the most times a ray can bounce, as each bounce traces another ray for every pixel that hits a
+/// reflective tile.
+pub const MAX_BOUNCES: u32 = 8;
+
+/// how distant geometry fades into the sky.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fog {
+    None,
+    /// no fog before `start`, fading linearly to only sky at `end`.
+    Linear {
+        start: f32,
+        end: f32,
+    },
+    /// fog which thickens exponentially with distance, never quite reaching only sky.
+    Exponential {
+        density: f32,
+    },
+}
+
+impl Fog {
+    /// returns how much of the sky colour to blend in at a distance, from 0.0 to 1.0.
+    pub fn amount(&self, distance: f32) -> f32 {
+        match *self {
+            Fog::None => 0.0,
+            Fog::Linear { start, end } => {
+                ((distance - start) / (end - start).max(f32::EPSILON)).clamp(0.0, 1.0)
+            }
+            Fog::Exponential { density } => 1.0 - (-density * distance).exp(),
+        }
+    }
+}
+
+/// draws a world by casting a ray through every pixel.
+pub struct Renderer {
+    /// how many tiles a ray from the camera crosses before giving up.
+    pub view_distance: usize,
+    pub fog: Fog,
+    pub sky: Sky,
+    /// the direction towards the sun. doesn't need to be normalised.
+    pub sun_direction: Vec3,
+    /// shades faces by how directly they face the sun.
+    pub lambert: bool,
+    /// casts a second ray towards the sun, so faces it can't see are in shadow.
+    pub shadows: bool,
+    /// darkens the corners of faces that sit in a crease between tiles.
+    pub ambient_occlusion: bool,
+    /// tints the whole view blue while the camera is inside water.
+    pub underwater_tint: bool,
+    /// how many times a ray can bounce off reflective tiles, up to `MAX_BOUNCES`. 0 turns
+    /// reflections off.
+    pub max_bounces: u32,
+    /// how far each pixel's ray went from the camera before it was blocked, from the last `render`,
+    /// or infinity where it reached the sky.
+    depth: Vec<f32>,
+    /// the size of the pixels `depth` was rendered for.
+    depth_size: (usize, usize),
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self {
+            view_distance: 64,
+            fog: Fog::Linear {
+                start: 16.0,
+                end: 36.0,
+            },
+            sky: Sky::new(),
+            sun_direction: Vec3::new(0.4, 1.0, 0.25),
+            lambert: true,
+            shadows: true,
+            ambient_occlusion: true,
+            underwater_tint: true,
+            max_bounces: 1,
+            depth: Vec::new(),
+            depth_size: (0, 0),
+        }
+    }
+
+    /// draws the world, and remembers how far away it is at every pixel for `draw_billboards`.
+    pub fn render(&mut self, world: &World, camera: &Transform, fov: f32, pixels: &mut Pixels) {
+        let size = (pixels.width(), pixels.height());
+        let inverse = view_projection(camera, fov, size).inverse();
+
+        let mut depth = std::mem::take(&mut self.depth);
+        depth.clear();
+        depth.resize(size.0 * size.1, f32::INFINITY);
+
+        for y in 0..pixels.height() {
+            for x in 0..pixels.width() {
+                let dir = pixel_ray(inverse, size, (x, y));
+                let (colour, distance) = self.trace(world, camera.position(), dir, 0);
+                pixels.draw_pixel((x, y), from_vec3(colour));
+                depth[y * size.0 + x] = distance;
+            }
+        }
+
+        self.depth = depth;
+        self.depth_size = size;
+    }
+
+    /// returns how far the ray through a pixel went before something blocked it in the last
+    /// `render`, or infinity if it reached the sky or the pixel wasn't rendered.
+    pub fn depth(&self, pixel: (usize, usize)) -> f32 {
+        let (width, height) = self.depth_size;
+        if pixel.0 >= width || pixel.1 >= height {
+            return f32::INFINITY;
+        }
+        self.depth[pixel.1 * width + pixel.0]
+    }
+
+    /// draws billboards over the world `render` last drew from the same camera, hidden behind
+    /// whatever is in front of them and lit by the light where they stand.
+    ///
+    /// the billboards are sorted back to front first, so nearer ones cover further ones.
+    pub fn draw_billboards(
+        &self,
+        world: &World,
+        camera: &Transform,
+        fov: f32,
+        pixels: &mut Pixels,
+        billboards: &mut [Billboard],
+    ) {
+        let size = (pixels.width(), pixels.height());
+        let projection = projection(fov, size);
+        let view = camera.view();
+        let inverse = (projection * view).inverse();
+
+        // distance in front of the camera, rather than from it, so billboards side by side sort
+        // the same way they're drawn.
+        let view_depth = |billboard: &Billboard| view.transform_point3(centre(billboard)).z;
+        billboards.sort_by(|a, b| view_depth(b).total_cmp(&view_depth(a)));
+
+        for billboard in billboards.iter() {
+            let centre = view.transform_point3(centre(billboard));
+            if centre.z <= 0.001 {
+                continue;
+            }
+
+            // the corners of the billboard, lying flat across the view.
+            let half = billboard.size.extend(0.0) * 0.5;
+            let to_screen = |corner: Vec3| {
+                let clip = projection * (centre + corner).extend(1.0);
+                Vec2::new(
+                    (clip.x / clip.w + 1.0) * 0.5 * size.0 as f32,
+                    (clip.y / clip.w + 1.0) * 0.5 * size.1 as f32,
+                )
+            };
+            let top_left = to_screen(Vec3::new(-half.x, half.y, 0.0));
+            let bottom_right = to_screen(Vec3::new(half.x, -half.y, 0.0));
+
+            let (min, max) = (top_left.min(bottom_right), top_left.max(bottom_right));
+            let min_x = min.x.max(0.0) as usize;
+            let min_y = min.y.max(0.0) as usize;
+            let max_x = (max.x.ceil().max(0.0) as usize).min(size.0);
+            let max_y = (max.y.ceil().max(0.0) as usize).min(size.1);
+            if min_x >= max_x || min_y >= max_y {
+                continue;
+            }
+
+            let brightness = self.billboard_brightness(world, billboard);
+            let forward = camera.forward();
+
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let pixel = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    let uv = (pixel - top_left) / (bottom_right - top_left);
+                    let Some(colour) = billboard.sample(uv) else {
+                        continue;
+                    };
+
+                    // the billboard faces the camera, so every point on it is the same distance in
+                    // front, and further along rays towards the edges of the screen.
+                    let dir = pixel_ray(inverse, size, (x, y));
+                    let distance = centre.z / dir.dot(forward).max(f32::EPSILON);
+                    if distance >= self.depth((x, y)) {
+                        continue;
+                    }
+
+                    let sky = self.sky.colour(dir, self.sun_direction);
+                    let shaded =
+                        (to_vec3(colour) * brightness).lerp(sky, self.fog.amount(distance));
+                    pixels.draw_pixel((x, y), from_vec3(shaded));
+                }
+            }
+        }
+    }
+
+    /// returns the direction of the ray `render` casts through a pixel, e.g. for picking tiles
+    /// with the mouse.
+    pub fn screen_ray(
+        &self,
+        camera: &Transform,
+        fov: f32,
+        screen_size: (usize, usize),
+        pixel: (usize, usize),
+    ) -> Vec3 {
+        let inverse = view_projection(camera, fov, screen_size).inverse();
+        pixel_ray(inverse, screen_size, pixel)
+    }
+
+    /// draws a line between two points in the world over whatever's already drawn, lined up with
+    /// what `render` drew from the same camera.
+    pub fn draw_line(
+        &self,
+        camera: &Transform,
+        fov: f32,
+        pixels: &mut Pixels,
+        from: Vec3,
+        to: Vec3,
+        colour: (u8, u8, u8),
+    ) {
+        let view_projection = view_projection(camera, fov, (pixels.width(), pixels.height()));
+        let Some((from, to)) = clip_line(
+            view_projection * from.extend(1.0),
+            view_projection * to.extend(1.0),
+        ) else {
+            return;
+        };
+
+        let to_screen = |clip: Vec4| {
+            (
+                ((clip.x / clip.w + 1.0) * 0.5 * pixels.width() as f32) as i32,
+                ((clip.y / clip.w + 1.0) * 0.5 * pixels.height() as f32) as i32,
+            )
+        };
+        let (from, to) = (to_screen(from), to_screen(to));
+        pixels.draw_line(from, to, colour);
+    }
+
+    /// draws particles as squares over the world `render` last drew from the same camera, hidden
+    /// behind whatever is in front of them. every particle is at least a pixel wide, so distant
+    /// ones don't flicker away.
+    pub fn draw_particles(
+        &self,
+        camera: &Transform,
+        fov: f32,
+        pixels: &mut Pixels,
+        particles: &Particles,
+    ) {
+        let size = (pixels.width(), pixels.height());
+        let projection = projection(fov, size);
+        let view = camera.view();
+        // how many pixels tall something a tile tall is, one tile in front of the camera.
+        let scale = projection.y_axis.y * 0.5 * size.1 as f32;
+
+        // sorted back to front, so nearer particles cover further ones.
+        let mut visible = Vec::with_capacity(particles.len());
+        for particle in particles.iter() {
+            let depth = view.transform_point3(particle.position).z;
+            if depth > 0.001 {
+                visible.push((depth, particle));
+            }
+        }
+        visible.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        for (depth, particle) in visible {
+            let clip = projection * view.transform_point3(particle.position).extend(1.0);
+            let centre = (
+                (clip.x / clip.w + 1.0) * 0.5 * size.0 as f32,
+                (clip.y / clip.w + 1.0) * 0.5 * size.1 as f32,
+            );
+            let half = (particle.size() * scale / depth * 0.5).max(0.5);
+
+            let min_x = (centre.0 - half).round().max(0.0) as usize;
+            let min_y = (centre.1 - half).round().max(0.0) as usize;
+            let max_x = ((centre.0 + half).round().max(0.0) as usize).min(size.0);
+            let max_y = ((centre.1 + half).round().max(0.0) as usize).min(size.1);
+
+            let offset = particle.position - camera.position();
+            let distance = offset.length();
+            let sky = self.sky.colour(offset / distance, self.sun_direction);
+            let colour = to_vec3(particle.colour()).lerp(sky, self.fog.amount(distance));
+
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    if distance < self.depth((x, y)) {
+                        pixels.draw_pixel((x, y), from_vec3(colour));
+                    }
+                }
+            }
+        }
+    }
+
+    /// works out the colour seen along a ray, blending through transparent tiles front to back.
+    ///
+    /// `bounce` is how many reflections deep the ray is, starting at 0 for rays from the camera.
+    /// also returns how far along the ray it was blocked, for the depth buffer, or infinity if it
+    /// reached the sky.
+    fn trace(&self, world: &World, origin: Vec3, dir: Vec3, bounce: u32) -> (Vec3, f32) {
+        let sky = self.sky.colour(dir, self.sun_direction);
+
+        let mut colour = Vec3::ZERO;
+        // how much of whatever is further along the ray still shows through, per channel.
+        let mut transmittance = Vec3::ONE;
+        let mut depth = f32::INFINITY;
+
+        for hit in world.raycast_all(origin, dir, self.view_distance) {
+            if tiles::is_cutout(hit.tile, hit.uv) {
+                continue;
+            }
+
+            // faces between two tiles of the same kind, like inside a body of water, are invisible.
+            let front = hit.adjacent();
+            if tile_at(world, front) == Some(hit.tile) {
+                continue;
+            }
+
+            let alpha = tiles::opacity(hit.tile);
+            let mut shaded = self.shade(world, origin, dir, &hit);
+
+            let reflectivity = tiles::reflectivity(hit.tile);
+            if reflectivity > 0.0 && bounce < self.max_bounces {
+                let reflected_dir = dir - 2.0 * dir.dot(hit.normal) * hit.normal;
+                let reflected_origin = origin + dir * hit.distance + hit.normal * 0.001;
+                let (reflected, _) = self.trace(world, reflected_origin, reflected_dir, bounce + 1);
+                shaded = shaded.lerp(reflected, reflectivity);
+            }
+
+            let shaded = shaded.lerp(sky, self.fog.amount(hit.distance));
+            colour += transmittance * shaded * alpha;
+
+            // translucent tiles tint what's behind them with their own colour.
+            let tint = Vec3::ONE.lerp(to_vec3(tiles::colour(hit.tile)), alpha);
+            transmittance *= tint * (1.0 - alpha);
+            if depth.is_infinite() && transmittance.max_element() <= DEPTH_TRANSMITTANCE {
+                depth = hit.distance;
+            }
+            if transmittance.max_element() < 0.01 {
+                break;
+            }
+        }
+        colour += transmittance * sky;
+
+        let underwater = bounce == 0 && self.underwater_tint;
+        if underwater && tile_at(world, origin.floor().as_ivec3()) == Some(tiles::WATER) {
+            colour = (colour * UNDERWATER_TINT).lerp(UNDERWATER_TINT * 0.3, UNDERWATER_HAZE);
+        }
+
+        (colour, depth)
+    }
+
+    /// works out how brightly to draw a billboard from the light in the tile around its middle.
+    ///
+    /// billboards don't face any one way, so they're lit as if by the sun from straight on.
+    fn billboard_brightness(&self, world: &World, billboard: &Billboard) -> f32 {
+        let middle = centre(billboard).floor().as_ivec3();
+        let (sky, block) = world.light(middle.x, middle.y, middle.z);
+        let level = sky.max(block) as f32 / MAX_LIGHT as f32;
+        MIN_BRIGHTNESS + (1.0 - MIN_BRIGHTNESS) * level
+    }
+
+    /// works out the colour of a single face a ray hits, before fog.
+    fn shade(&self, world: &World, origin: Vec3, dir: Vec3, result: &RaycastResult) -> Vec3 {
+        // light is sampled from the empty voxel the hit face looks out into.
+        let front = result.adjacent();
+        let (sky, block) = world.light(front.x, front.y, front.z);
+
+        let sun_direction = self.sun_direction.normalize_or_zero();
+        let mut sun = 1.0;
+        if self.lambert {
+            sun *= result.normal.dot(sun_direction).max(0.0);
+        }
+        if self.shadows && sun > 0.0 {
+            let hit = origin + dir * result.distance + result.normal * 0.001;
+            let blocked = world
+                .raycast_all(hit, sun_direction, SHADOW_STEPS)
+                .any(|hit| tiles::opacity(hit.tile) >= 1.0 && !tiles::is_cutout(hit.tile, hit.uv));
+            if blocked {
+                sun = 0.0;
+            }
+        }
+
+        // only sky light is affected by the sun; lamps shine the same in every direction.
+        let sky = sky as f32 * (AMBIENT + (1.0 - AMBIENT) * sun);
+        let level = sky.max(block as f32) / MAX_LIGHT as f32;
+        let mut brightness = MIN_BRIGHTNESS + (1.0 - MIN_BRIGHTNESS) * level;
+
+        if self.ambient_occlusion {
+            brightness *= ambient_occlusion(world, front, result);
+        }
+
+        to_vec3(tiles::colour(result.tile)) * brightness
+    }
+}
+
+/// returns the matrix taking points in the world to clip space, for the camera drawing into pixels
+/// of the given size.
+fn view_projection(camera: &Transform, fov: f32, size: (usize, usize)) -> Mat4 {
+    projection(fov, size) * camera.view()
+}
+
+/// returns the matrix taking points in front of the camera to clip space.
+fn projection(fov: f32, size: (usize, usize)) -> Mat4 {
+    let aspect = size.0 as f32 / size.1 as f32;
+    Mat4::perspective_infinite_lh(fov.to_radians(), aspect, 0.001)
+}
+
+/// returns the middle of a billboard, which it's sorted and lit by.
+fn centre(billboard: &Billboard) -> Vec3 {
+    billboard.position + Vec3::Y * billboard.size.y * 0.5
+}
+
+/// returns the direction through a pixel, given the inverse of `view_projection`.
+fn pixel_ray(inverse: Mat4, size: (usize, usize), pixel: (usize, usize)) -> Vec3 {
+    let screen_coord = Vec2::new(
+        2.0 * pixel.0 as f32 / size.0 as f32 - 1.0,
+        2.0 * pixel.1 as f32 / size.1 as f32 - 1.0,
+    );
+    let clip_space = Vec4::new(screen_coord.x, screen_coord.y, 1.0, 1.0);
+    let direction = inverse * clip_space;
+    Vec3::new(direction.x, direction.y, direction.z).normalize()
+}
+
+/// cuts a line in clip space down to the part that's on screen and in front of the camera.
+fn clip_line(from: Vec4, to: Vec4) -> Option<(Vec4, Vec4)> {
+    // each plane is a function that's positive on the visible side.
+    let planes: [fn(Vec4) -> f32; 5] = [
+        |v| v.w - 0.001,
+        |v| v.w + v.x,
+        |v| v.w - v.x,
+        |v| v.w + v.y,
+        |v| v.w - v.y,
+    ];
+
+    let (mut start, mut end) = (0.0_f32, 1.0_f32);
+    for plane in planes {
+        let (a, b) = (plane(from), plane(to));
+        if a < 0.0 && b < 0.0 {
+            return None;
+        }
+        if a < 0.0 {
+            start = start.max(a / (a - b));
+        } else if b < 0.0 {
+            end = end.min(a / (a - b));
+        }
+    }
+
+    (start <= end).then(|| (from.lerp(to, start), from.lerp(to, end)))
+}
+
+/// returns the tile at a position, or nothing outside the world.
+fn tile_at(world: &World, position: IVec3) -> Option<Tile> {
+    if position.cmplt(IVec3::ZERO).any() {
+        return None;
+    }
+    world.get(
+        position.x as usize,
+        position.y as usize,
+        position.z as usize,
+    )
+}
+
+/// returns how much light reaches the hit point, from 1.0 when open down to `1.0 - AO_STRENGTH`.
+///
+/// each corner of the face is darkened by the solid tiles touching it in front of the face, and
+/// the hit point blends between the four corners by its uv.
+fn ambient_occlusion(world: &World, front: IVec3, result: &RaycastResult) -> f32 {
+    // the axes the uv runs along, matching how `World::raycast` works them out.
+    let (u_axis, v_axis) = if result.normal.x != 0.0 {
+        (IVec3::Z, IVec3::Y)
+    } else if result.normal.y != 0.0 {
+        (IVec3::X, IVec3::Z)
+    } else {
+        (IVec3::X, IVec3::Y)
+    };
+
+    let solid = |position: IVec3| tile_at(world, position).is_some_and(tiles::is_opaque);
+
+    let corner = |du: i32, dv: i32| {
+        let side_u = solid(front + u_axis * du);
+        let side_v = solid(front + v_axis * dv);
+        let diagonal = solid(front + u_axis * du + v_axis * dv);
+        let occlusion = if side_u && side_v {
+            3
+        } else {
+            side_u as u8 + side_v as u8 + diagonal as u8
+        };
+        1.0 - AO_STRENGTH * occlusion as f32 / 3.0
+    };
+
+    let (u, v) = (result.uv.x, result.uv.y);
+    let bottom = corner(-1, -1) * (1.0 - u) + corner(1, -1) * u;
+    let top = corner(-1, 1) * (1.0 - u) + corner(1, 1) * u;
+    bottom * (1.0 - v) + top * v
+}
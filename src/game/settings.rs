@@ -0,0 +1,214 @@
+use crate::framework::{Key, UpscaleMode, WindowMode};
+use std::{fs, io, path::Path};
+
+/// where settings are read from and written to, relative to the working directory.
+pub const SETTINGS_PATH: &str = "settings.cfg";
+
+/// the window sizes offered by the settings menu.
+pub const RESOLUTIONS: [(usize, usize); 4] = [(512, 512), (768, 768), (1024, 768), (1280, 720)];
+
+/// the largest the render size can be along either axis, so a typo can't ask for gigabytes of
+/// pixels.
+pub const MAX_RENDER_SIZE: usize = 4096;
+
+/// which keys trigger which player actions.
+#[derive(Clone, PartialEq)]
+pub struct KeyBindings {
+    pub forward: Key,
+    pub backward: Key,
+    pub left: Key,
+    pub right: Key,
+    pub up: Key,
+    pub down: Key,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            forward: Key::W,
+            backward: Key::S,
+            left: Key::A,
+            right: Key::D,
+            up: Key::Space,
+            down: Key::LeftShift,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// returns every binding alongside the name of its action, in the order they are shown in menus.
+    pub fn actions_mut(&mut self) -> [(&'static str, &mut Key); 6] {
+        [
+            ("forward", &mut self.forward),
+            ("backward", &mut self.backward),
+            ("left", &mut self.left),
+            ("right", &mut self.right),
+            ("up", &mut self.up),
+            ("down", &mut self.down),
+        ]
+    }
+}
+
+/// user-configurable settings, persisted to `SETTINGS_PATH` as `key = value` lines.
+#[derive(Clone, PartialEq)]
+pub struct Settings {
+    pub window_size: (usize, usize),
+    pub window_mode: WindowMode,
+    /// the vertical field of view, in degrees.
+    pub fov: f32,
+    /// how many degrees the camera turns per pixel of mouse movement.
+    pub mouse_sensitivity: f32,
+    pub key_bindings: KeyBindings,
+    /// the size the game is drawn at, before being upscaled to the window.
+    pub render_size: (usize, usize),
+    pub upscale: UpscaleMode,
+    /// lowers the render resolution when frames take longer than `target_frame_time`.
+    pub dynamic_resolution: bool,
+    /// the frame time dynamic resolution aims for, in milliseconds.
+    pub target_frame_time: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_size: (768, 768),
+            window_mode: WindowMode::Windowed,
+            fov: 90.0,
+            mouse_sensitivity: 0.5,
+            key_bindings: KeyBindings::default(),
+            render_size: (256, 256),
+            upscale: UpscaleMode::Nearest,
+            dynamic_resolution: false,
+            target_frame_time: 1000.0 / 60.0,
+        }
+    }
+}
+
+impl Settings {
+    /// loads settings from `SETTINGS_PATH`, falling back to defaults if the file doesn't exist.
+    pub fn load() -> Self {
+        Self::load_from(SETTINGS_PATH)
+    }
+
+    /// loads settings from a file, falling back to defaults for the file or any lines that can't be read.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Self {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(SETTINGS_PATH)
+    }
+
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.serialize())
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut settings = Self::default();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "window_width" => {
+                    if let Ok(width) = value.parse::<usize>() {
+                        settings.window_size.0 = width.max(1);
+                    }
+                }
+                "window_height" => {
+                    if let Ok(height) = value.parse::<usize>() {
+                        settings.window_size.1 = height.max(1);
+                    }
+                }
+                "window_mode" => {
+                    if let Some(mode) = WindowMode::from_name(value) {
+                        settings.window_mode = mode;
+                    }
+                }
+                "fov" => {
+                    if let Some(fov) = parse_finite(value) {
+                        settings.fov = fov.clamp(30.0, 150.0);
+                    }
+                }
+                "mouse_sensitivity" => {
+                    if let Some(sensitivity) = parse_finite(value) {
+                        settings.mouse_sensitivity = sensitivity.clamp(0.05, 2.0);
+                    }
+                }
+                "render_width" => {
+                    if let Ok(width) = value.parse::<usize>() {
+                        settings.render_size.0 = width.clamp(1, MAX_RENDER_SIZE);
+                    }
+                }
+                "render_height" => {
+                    if let Ok(height) = value.parse::<usize>() {
+                        settings.render_size.1 = height.clamp(1, MAX_RENDER_SIZE);
+                    }
+                }
+                "upscale" => {
+                    if let Some(mode) = UpscaleMode::from_name(value) {
+                        settings.upscale = mode;
+                    }
+                }
+                "dynamic_resolution" => settings.dynamic_resolution = value == "true",
+                "target_frame_time" => {
+                    if let Ok(time) = value.parse::<f32>() {
+                        settings.target_frame_time = time.max(1.0);
+                    }
+                }
+                _ => {
+                    let action = key.strip_prefix("bind_");
+                    for (name, binding) in settings.key_bindings.actions_mut() {
+                        if Some(name) == action {
+                            if let Some(bound_key) = Key::from_name(value) {
+                                *binding = bound_key;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        settings
+    }
+
+    fn serialize(&self) -> String {
+        let mut contents = String::from("# arcana settings\n");
+        contents += &format!("window_width = {}\n", self.window_size.0);
+        contents += &format!("window_height = {}\n", self.window_size.1);
+        contents += &format!("window_mode = {}\n", self.window_mode.name());
+        contents += &format!("fov = {}\n", self.fov);
+        contents += &format!("mouse_sensitivity = {}\n", self.mouse_sensitivity);
+        contents += &format!("render_width = {}\n", self.render_size.0);
+        contents += &format!("render_height = {}\n", self.render_size.1);
+        contents += &format!("upscale = {}\n", self.upscale.name());
+        contents += &format!("dynamic_resolution = {}\n", self.dynamic_resolution);
+        contents += &format!("target_frame_time = {}\n", self.target_frame_time);
+
+        let mut key_bindings = self.key_bindings.clone();
+        for (name, key) in key_bindings.actions_mut() {
+            contents += &format!("bind_{} = {}\n", name, key.name());
+        }
+
+        contents
+    }
+}
+
+/// parses a number, leaving out infinities and nan which would get through clamping.
+fn parse_finite(value: &str) -> Option<f32> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
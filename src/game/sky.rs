@@ -0,0 +1,125 @@
+use crate::framework::Pixels;
+use glam::Vec3;
+use std::io;
+
+/// the background drawn wherever a ray doesn't hit the world.
+pub struct Sky {
+    /// the colour straight up.
+    pub zenith: (u8, u8, u8),
+    /// the colour at the horizon, which the zenith and ground colours fade into.
+    pub horizon: (u8, u8, u8),
+    /// the colour straight down.
+    pub ground: (u8, u8, u8),
+    pub sun_colour: (u8, u8, u8),
+    /// the angular radius of the sun disc, in degrees.
+    pub sun_size: f32,
+    /// the faces of a cubemap, in the order +x, -x, +y, -y, +z, -z, drawn instead of the gradient.
+    pub cubemap: Option<[Pixels; 6]>,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sky {
+    pub fn new() -> Self {
+        Self {
+            zenith: (70, 120, 200),
+            horizon: (180, 210, 235),
+            ground: (90, 95, 105),
+            sun_colour: (255, 244, 214),
+            sun_size: 3.0,
+            cubemap: None,
+        }
+    }
+
+    /// loads a cubemap from six ppm images, named `<prefix>_px.ppm`, `<prefix>_nx.ppm` and so on.
+    pub fn load_cubemap(&mut self, prefix: &str) -> io::Result<()> {
+        let load = |face: &str| Pixels::load_ppm(format!("{}_{}.ppm", prefix, face));
+        self.cubemap = Some([
+            load("px")?,
+            load("nx")?,
+            load("py")?,
+            load("ny")?,
+            load("pz")?,
+            load("nz")?,
+        ]);
+        Ok(())
+    }
+
+    /// returns the colour of the sky in a direction, with the sun drawn towards `sun_direction`.
+    pub fn colour(&self, dir: Vec3, sun_direction: Vec3) -> Vec3 {
+        let background = match &self.cubemap {
+            Some(faces) => sample_cubemap(faces, dir),
+            None => {
+                let horizon = to_vec3(self.horizon);
+                if dir.y >= 0.0 {
+                    horizon.lerp(to_vec3(self.zenith), dir.y.sqrt())
+                } else {
+                    horizon.lerp(to_vec3(self.ground), (-dir.y).sqrt())
+                }
+            }
+        };
+
+        // a hard disc, with a faint glow around it.
+        let angle = dir
+            .dot(sun_direction.normalize_or_zero())
+            .clamp(-1.0, 1.0)
+            .acos()
+            .to_degrees();
+        let sun = if angle < self.sun_size {
+            1.0
+        } else {
+            0.5 * (1.0 - (angle - self.sun_size) / (self.sun_size * 4.0)).max(0.0)
+        };
+
+        background.lerp(to_vec3(self.sun_colour), sun)
+    }
+}
+
+fn sample_cubemap(faces: &[Pixels; 6], dir: Vec3) -> Vec3 {
+    let abs = dir.abs();
+
+    // pick the face the direction points most towards, and where on it the direction lands.
+    let (face, s, t, major) = if abs.x >= abs.y && abs.x >= abs.z {
+        if dir.x > 0.0 {
+            (0, -dir.z, -dir.y, abs.x)
+        } else {
+            (1, dir.z, -dir.y, abs.x)
+        }
+    } else if abs.y >= abs.z {
+        if dir.y > 0.0 {
+            (2, dir.x, dir.z, abs.y)
+        } else {
+            (3, dir.x, -dir.z, abs.y)
+        }
+    } else if dir.z > 0.0 {
+        (4, dir.x, -dir.y, abs.z)
+    } else {
+        (5, -dir.x, -dir.y, abs.z)
+    };
+
+    let face = &faces[face];
+    if face.width() == 0 || face.height() == 0 {
+        return Vec3::ZERO;
+    }
+
+    let u = (s / major + 1.0) * 0.5;
+    let v = (t / major + 1.0) * 0.5;
+    let x = ((u * face.width() as f32) as usize).min(face.width() - 1);
+    let y = ((v * face.height() as f32) as usize).min(face.height() - 1);
+    to_vec3(face.get_pixel((x, y)))
+}
+
+/// converts a colour to floats between 0.0 and 1.0.
+pub fn to_vec3(colour: (u8, u8, u8)) -> Vec3 {
+    Vec3::new(colour.0 as f32, colour.1 as f32, colour.2 as f32) / 255.0
+}
+
+/// converts a colour from floats between 0.0 and 1.0.
+pub fn from_vec3(colour: Vec3) -> (u8, u8, u8) {
+    let colour = (colour.clamp(Vec3::ZERO, Vec3::ONE) * 255.0).round();
+    (colour.x as u8, colour.y as u8, colour.z as u8)
+}
@@ -1,12 +1,75 @@
+pub mod ecs;
+pub mod error;
 pub mod framework;
 pub mod game;
 pub mod math;
+pub mod physics;
+pub mod reader;
+pub mod ui;
 pub mod world;
+pub mod worldgen;
 
-use crate::game::{Game, GameStateInit};
+use crate::framework::{
+    Platform, PlatformHeadless, PlatformMinifb, PlatformRecorder, PlatformReplay, Replay,
+};
+use crate::game::{Game, GameStateInit, Settings};
 
 fn main() {
-    let mut game = Game::new();
+    let args: Vec<String> = std::env::args().collect();
+    let has_flag = |flag: &str| args.iter().any(|arg| arg == flag);
+    // returns the value following a flag, like the path in `--record out.y4m`.
+    let arg_value = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|index| args.get(index + 1))
+    };
+
+    let replay = arg_value("--replay").map(|path| {
+        Replay::load(path).unwrap_or_else(|e| panic!("couldn't load replay {}: {}", path, e))
+    });
+    let window_size = Settings::load().window_size;
+
+    let mut platform: Box<dyn Platform> = if has_flag("--terminal") {
+        terminal_platform()
+    } else if has_flag("--headless") {
+        let headless = PlatformHeadless::new(window_size.0, window_size.1);
+        // without input, a headless game needs to know when to stop, unless a replay says so.
+        let frames = arg_value("--headless").and_then(|frames| frames.parse().ok());
+        match frames.or(replay.is_none().then_some(600)) {
+            Some(frames) => Box::new(headless.with_frame_limit(frames)),
+            None => Box::new(headless),
+        }
+    } else {
+        Box::new(PlatformMinifb::new(window_size.0, window_size.1))
+    };
+    if let Some(replay) = replay {
+        platform = Box::new(PlatformReplay::new(replay, platform));
+    }
+    if let Some(path) = arg_value("--record-input") {
+        platform = Box::new(
+            PlatformRecorder::new(platform, path)
+                .unwrap_or_else(|e| panic!("couldn't record input to {}: {}", path, e)),
+        );
+    }
+
+    let mut game = Game::with_platform(platform);
+    if let Some(path) = arg_value("--record") {
+        game.start_recording(path);
+    }
     game.push_state(Box::new(GameStateInit {}));
     game.run().unwrap();
 }
+
+/// runs the game in the terminal, for when there's no display, like over ssh.
+#[cfg(unix)]
+fn terminal_platform() -> Box<dyn Platform> {
+    match crate::framework::PlatformTerminal::new() {
+        Ok(platform) => Box::new(platform),
+        Err(e) => panic!("couldn't set up the terminal: {}", e),
+    }
+}
+
+#[cfg(not(unix))]
+fn terminal_platform() -> Box<dyn Platform> {
+    panic!("--terminal is only supported on unix");
+}
@@ -1,3 +1,5 @@
+mod rng;
 mod transform;
 
+pub use rng::Rng;
 pub use transform::Transform;
@@ -1,5 +1,6 @@
 use glam::{Mat4, Vec3};
 
+#[derive(Clone)]
 pub struct Transform {
     position: Vec3,
     rotation: Vec3,
@@ -0,0 +1,35 @@
+use glam::Vec3;
+
+/// an axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// creates a box standing on a point, like a body's feet.
+    pub fn from_feet(feet: Vec3, width: f32, height: f32) -> Self {
+        let half_width = width * 0.5;
+        Self {
+            min: Vec3::new(feet.x - half_width, feet.y, feet.z - half_width),
+            max: Vec3::new(feet.x + half_width, feet.y + height, feet.z + half_width),
+        }
+    }
+
+    pub fn translated(&self, offset: Vec3) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// returns true if the boxes overlap; boxes which only touch don't.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmplt(other.max).all() && other.min.cmplt(self.max).all()
+    }
+}
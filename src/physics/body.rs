@@ -0,0 +1,292 @@
+use super::Aabb;
+use crate::world::{tiles, World};
+use glam::{Vec2, Vec3};
+
+/// how far inside a tile's edge still counts as outside it, so a body resting exactly against a
+/// tile isn't caught on it by rounding.
+const EPSILON: f32 = 1e-4;
+
+/// something that moves through the world and collides with solid tiles, like the player.
+///
+/// the body is a box standing on `position`, moved by `step` in fixed steps; see `FixedTimestep`.
+pub struct Body {
+    /// the centre of the bottom of the box.
+    pub position: Vec3,
+    /// where the body was before the last step, for drawing it between steps.
+    pub previous_position: Vec3,
+    pub velocity: Vec3,
+    pub width: f32,
+    pub height: f32,
+    /// how quickly the body speeds up while falling, in tiles per second per second.
+    pub gravity: f32,
+    pub max_fall_speed: f32,
+    /// the upwards speed of a jump.
+    pub jump_speed: f32,
+    /// how fast the body walks, in tiles per second.
+    pub walk_speed: f32,
+    /// how quickly the body speeds up to, and slows down from, walking while on the ground.
+    pub friction: f32,
+    /// the same as `friction`, while in the air.
+    pub air_control: f32,
+    /// the tallest ledge walked up onto without jumping.
+    pub step_height: f32,
+    /// whether the body is standing on something, as of the last step.
+    pub on_ground: bool,
+}
+
+impl Body {
+    /// creates a body the size of the player, standing at a position.
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            previous_position: position,
+            velocity: Vec3::ZERO,
+            width: 0.6,
+            height: 1.8,
+            gravity: 24.0,
+            max_fall_speed: 50.0,
+            jump_speed: 8.0,
+            walk_speed: 4.3,
+            friction: 12.0,
+            air_control: 2.0,
+            step_height: 1.0,
+            on_ground: false,
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_feet(self.position, self.width, self.height)
+    }
+
+    /// moves the body without colliding along the way, and stops it.
+    pub fn teleport(&mut self, position: Vec3) {
+        self.position = position;
+        self.previous_position = position;
+        self.velocity = Vec3::ZERO;
+    }
+
+    /// returns where to draw the body, `alpha` of the way from its previous position to its current one.
+    pub fn interpolated_position(&self, alpha: f32) -> Vec3 {
+        self.previous_position.lerp(self.position, alpha)
+    }
+
+    /// simulates one fixed step.
+    ///
+    /// `walk` is the direction to walk in on the x and z axes, at most 1.0 long for full speed.
+    pub fn step(&mut self, world: &World, walk: Vec2, jump: bool, delta_time: f32) {
+        self.previous_position = self.position;
+
+        let control = if self.on_ground {
+            self.friction
+        } else {
+            self.air_control
+        };
+        let target = walk * self.walk_speed;
+        let blend = (control * delta_time).min(1.0);
+        self.velocity.x += (target.x - self.velocity.x) * blend;
+        self.velocity.z += (target.y - self.velocity.z) * blend;
+
+        if jump && self.on_ground {
+            self.velocity.y = self.jump_speed;
+        }
+        self.velocity.y = (self.velocity.y - self.gravity * delta_time).max(-self.max_fall_speed);
+
+        self.move_by(world, self.velocity * delta_time);
+    }
+
+    /// moves as far as possible towards an offset, one axis at a time, sliding along what's hit.
+    fn move_by(&mut self, world: &World, offset: Vec3) {
+        // vertical first, so whether the body is on the ground is known before stepping up.
+        let vertical = sweep(world, &self.aabb(), 1, offset.y);
+        self.position.y += vertical;
+        let hit_vertically = vertical != offset.y;
+        if hit_vertically {
+            self.velocity.y = 0.0;
+        }
+        self.on_ground = hit_vertically && offset.y < 0.0;
+
+        let horizontal = Vec3::new(offset.x, 0.0, offset.z);
+        let aabb = self.aabb();
+        let (mut moved, mut blocked) = slide(world, &aabb, horizontal);
+
+        if blocked.iter().any(|axis| *axis) && self.on_ground && self.step_height > 0.0 {
+            // lifts the body, moves it, then drops it back down onto whatever it's stepped onto.
+            let up = sweep(world, &aabb, 1, self.step_height);
+            let raised = aabb.translated(Vec3::Y * up);
+            let (stepped, stepped_blocked) = slide(world, &raised, horizontal);
+            let down = sweep(world, &raised.translated(stepped), 1, -up);
+
+            if stepped.length_squared() > moved.length_squared() + EPSILON {
+                moved = stepped + Vec3::Y * (up + down);
+                blocked = stepped_blocked;
+            }
+        }
+
+        self.position += moved;
+        if blocked[0] {
+            self.velocity.x = 0.0;
+        }
+        if blocked[1] {
+            self.velocity.z = 0.0;
+        }
+    }
+}
+
+/// moves a box along the x then the z axis, returning how far it got and whether each axis was blocked.
+fn slide(world: &World, aabb: &Aabb, offset: Vec3) -> (Vec3, [bool; 2]) {
+    let x = sweep(world, aabb, 0, offset.x);
+    let z = sweep(world, &aabb.translated(Vec3::X * x), 2, offset.z);
+    (Vec3::new(x, 0.0, z), [x != offset.x, z != offset.z])
+}
+
+/// returns how far a box can move along an axis before touching a solid tile, up to `distance`.
+fn sweep(world: &World, aabb: &Aabb, axis: usize, distance: f32) -> f32 {
+    if distance == 0.0 {
+        return 0.0;
+    }
+
+    // the tiles the box covers on the other two axes.
+    let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+    let covered = |axis: usize| {
+        let start = (aabb.min[axis] + EPSILON).floor() as i32;
+        let end = (aabb.max[axis] - EPSILON).ceil() as i32;
+        start..end
+    };
+    let is_blocked = |layer: i32| {
+        covered(a).any(|u| {
+            covered(b).any(|v| {
+                let mut cell = [0; 3];
+                cell[axis] = layer;
+                cell[a] = u;
+                cell[b] = v;
+                is_solid_at(world, cell)
+            })
+        })
+    };
+
+    // checks each layer of tiles in front of the box, nearest first.
+    if distance > 0.0 {
+        let face = aabb.max[axis];
+        let mut layer = (face - EPSILON).ceil() as i32;
+        while (layer as f32) < face + distance {
+            if is_blocked(layer) {
+                return (layer as f32 - face).max(0.0);
+            }
+            layer += 1;
+        }
+    } else {
+        let face = aabb.min[axis];
+        let mut layer = (face + EPSILON).floor() as i32 - 1;
+        while (layer + 1) as f32 > face + distance {
+            if is_blocked(layer) {
+                return ((layer + 1) as f32 - face).min(0.0);
+            }
+            layer -= 1;
+        }
+    }
+
+    distance
+}
+
+/// returns true if a tile blocks movement; below the world is solid, so nothing falls out of it.
+fn is_solid_at(world: &World, [x, y, z]: [i32; 3]) -> bool {
+    if y < 0 {
+        return true;
+    }
+    if x < 0 || z < 0 {
+        return false;
+    }
+
+    world
+        .get(x as usize, y as usize, z as usize)
+        .is_some_and(tiles::is_solid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::PHYSICS_TIME_STEP;
+
+    /// an empty world which is raised by `height` tiles from x = 5 on.
+    fn world_with_ledge(height: usize) -> World {
+        let mut world = World::with_size(16, 16, 16);
+        for x in 5..16 {
+            for y in 0..height {
+                for z in 0..16 {
+                    world.set(x, y, z, tiles::STONE);
+                }
+            }
+        }
+        world
+    }
+
+    fn run(body: &mut Body, world: &World, walk: Vec2, steps: usize) {
+        for _ in 0..steps {
+            body.step(world, walk, false, PHYSICS_TIME_STEP);
+        }
+    }
+
+    #[test]
+    fn falls_until_it_lands() {
+        let world = World::with_size(16, 16, 16);
+        let mut body = Body::new(Vec3::new(8.5, 3.0, 8.5));
+        run(&mut body, &world, Vec2::ZERO, 1);
+        assert!(!body.on_ground);
+
+        run(&mut body, &world, Vec2::ZERO, 120);
+        assert!(body.on_ground);
+        assert_eq!(body.position.y, 0.0);
+        assert_eq!(body.velocity.y, 0.0);
+    }
+
+    #[test]
+    fn fast_bodies_stop_at_walls_rather_than_passing_into_them() {
+        let world = world_with_ledge(4);
+        let mut body = Body::new(Vec3::new(2.5, 0.0, 8.5));
+        // far enough in one step to end up inside the ledge without a sweep.
+        body.velocity.x = 600.0;
+        run(&mut body, &world, Vec2::ZERO, 1);
+
+        assert!((body.aabb().max.x - 5.0).abs() < 1e-3);
+        assert_eq!(body.velocity.x, 0.0);
+    }
+
+    #[test]
+    fn steps_up_onto_a_single_block() {
+        let world = world_with_ledge(1);
+        let mut body = Body::new(Vec3::new(3.5, 0.0, 8.5));
+        run(&mut body, &world, Vec2::X, 60);
+
+        assert!(body.position.x > 5.0);
+        assert_eq!(body.position.y, 1.0);
+        assert!(body.on_ground);
+    }
+
+    #[test]
+    fn doesnt_step_up_onto_two_blocks() {
+        let world = world_with_ledge(2);
+        let mut body = Body::new(Vec3::new(3.5, 0.0, 8.5));
+        run(&mut body, &world, Vec2::X, 60);
+
+        assert!((body.aabb().max.x - 5.0).abs() < 1e-3);
+        assert_eq!(body.position.y, 0.0);
+    }
+
+    #[test]
+    fn friction_slows_bodies_more_on_the_ground_than_in_the_air() {
+        let world = World::with_size(16, 16, 16);
+        let mut grounded = Body::new(Vec3::new(8.5, 0.0, 8.5));
+        run(&mut grounded, &world, Vec2::ZERO, 1);
+        let mut flying = Body::new(Vec3::new(8.5, 10.0, 8.5));
+        run(&mut flying, &world, Vec2::ZERO, 1);
+
+        grounded.velocity.x = 4.0;
+        flying.velocity.x = 4.0;
+        run(&mut grounded, &world, Vec2::ZERO, 10);
+        run(&mut flying, &world, Vec2::ZERO, 10);
+
+        assert!(grounded.velocity.x < 1.0);
+        assert!(flying.velocity.x > grounded.velocity.x);
+        assert!(flying.velocity.x < 4.0);
+    }
+}
@@ -0,0 +1,7 @@
+mod aabb;
+mod body;
+mod timestep;
+
+pub use aabb::Aabb;
+pub use body::Body;
+pub use timestep::{FixedTimestep, PHYSICS_TIME_STEP};
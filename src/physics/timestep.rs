@@ -0,0 +1,69 @@
+/// how long each physics step simulates, in seconds.
+pub const PHYSICS_TIME_STEP: f32 = 1.0 / 60.0;
+
+/// the most steps taken in one frame, so a long stall doesn't leave the game forever catching up.
+const MAX_STEPS: u32 = 8;
+
+/// runs a simulation in fixed steps whatever the frame rate, so it behaves the same at any speed.
+pub struct FixedTimestep {
+    step: f32,
+    /// frame time not yet simulated.
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32) -> Self {
+        Self {
+            step,
+            accumulator: 0.0,
+        }
+    }
+
+    /// adds a frame's time, returning how many steps to simulate.
+    pub fn advance(&mut self, delta_time: f32) -> u32 {
+        // time past what can be caught up on would be dropped anyway, and letting it build up
+        // would make it too big for a step to make a difference to.
+        let limit = self.step * (MAX_STEPS + 1) as f32;
+        self.accumulator = (self.accumulator + delta_time.max(0.0)).min(limit);
+
+        let remainder = self.accumulator % self.step;
+        let steps = ((self.accumulator - remainder) / self.step).round() as u32;
+        self.accumulator = remainder;
+        // drops the time that can't be caught up on; the simulation slows down instead.
+        steps.min(MAX_STEPS)
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// returns how far the frame is between the last step and the next, from 0.0 to 1.0, for
+    /// drawing things smoothly between steps.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_for_each_whole_step_of_time() {
+        let mut timestep = FixedTimestep::new(0.25);
+        assert_eq!(timestep.advance(0.6), 2);
+        assert!((timestep.alpha() - 0.4).abs() < 1e-4);
+        assert_eq!(timestep.advance(0.15), 1);
+        assert_eq!(timestep.advance(0.0), 0);
+    }
+
+    #[test]
+    fn long_stalls_are_capped() {
+        let mut timestep = FixedTimestep::new(PHYSICS_TIME_STEP);
+        assert_eq!(timestep.advance(1.0e6), MAX_STEPS);
+        assert!(timestep.alpha() < 1.0);
+        assert_eq!(timestep.advance(f32::INFINITY), MAX_STEPS);
+        assert_eq!(timestep.advance(f32::NAN), 0);
+        assert_eq!(timestep.advance(PHYSICS_TIME_STEP), 1);
+    }
+}
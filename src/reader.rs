@@ -0,0 +1,87 @@
+//! reading the little endian binary formats the game saves, like worlds, schematics and replays.
+
+use crate::error::ErrorType;
+
+/// an error for a file that isn't laid out the way its format says.
+pub fn corrupt(reason: &str) -> ErrorType {
+    ErrorType::CorruptFile(reason.to_string())
+}
+
+/// reads little endian values from a byte slice, failing if it runs out.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn take(&mut self, count: usize) -> Result<&'a [u8], ErrorType> {
+        if count > self.bytes.len() {
+            return Err(corrupt("unexpected end of file"));
+        }
+
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, ErrorType> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, ErrorType> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, ErrorType> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, ErrorType> {
+        Ok(self.u32()? as i32)
+    }
+
+    pub fn f32(&mut self) -> Result<f32, ErrorType> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    /// a `u16` length followed by that many bytes of utf-8.
+    pub fn string(&mut self) -> Result<String, ErrorType> {
+        let length = self.u16()? as usize;
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|_| corrupt("string is not utf-8"))
+    }
+}
+
+/// checks shared by the tests of each format read with a `Reader`.
+#[cfg(test)]
+pub mod testing {
+    use crate::error::ErrorType;
+
+    pub fn is_corrupt<T>(result: Result<T, ErrorType>) -> bool {
+        matches!(result, Err(ErrorType::CorruptFile(_)))
+    }
+
+    /// asserts that every shorter prefix of `bytes` is corrupt, except the lengths in `complete`
+    /// where a file may end, like between a replay's frames.
+    pub fn assert_truncations_are_corrupt<T>(
+        bytes: &[u8],
+        complete: &[usize],
+        parse: impl Fn(&[u8]) -> Result<T, ErrorType>,
+    ) {
+        for length in (0..bytes.len()).filter(|length| !complete.contains(length)) {
+            assert!(is_corrupt(parse(&bytes[..length])), "{}", length);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+/// an axis-aligned rectangle in `Pixels` coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: usize,
+    pub h: usize,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, w: usize, h: usize) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// returns a rect of the given size centred inside of a `width` x `height` area.
+    pub fn centred(width: usize, height: usize, w: usize, h: usize) -> Self {
+        Self::new(
+            (width as i32 - w as i32) / 2,
+            (height as i32 - h as i32) / 2,
+            w,
+            h,
+        )
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.w as i32 && y < self.y + self.h as i32
+    }
+
+    /// returns this rect shrunk by `amount` pixels on every side.
+    pub fn shrink(&self, amount: usize) -> Self {
+        Self::new(
+            self.x + amount as i32,
+            self.y + amount as i32,
+            self.w.saturating_sub(amount * 2),
+            self.h.saturating_sub(amount * 2),
+        )
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.w, self.h)
+    }
+}
+
+/// which way a layout places its widgets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// each widget goes below the previous one and fills the layout's width.
+    Vertical,
+    /// each widget goes to the right of the previous one and is only as wide as it needs to be.
+    Horizontal,
+}
+
+/// places widgets one after another inside of a rect.
+pub(super) struct Layout {
+    pub bounds: Rect,
+    pub direction: Direction,
+    pub spacing: usize,
+    /// how far along the layout's direction the next widget will be placed.
+    cursor: usize,
+    /// the largest extent of any widget across the layout's direction.
+    extent: usize,
+}
+
+impl Layout {
+    pub fn new(bounds: Rect, direction: Direction, spacing: usize) -> Self {
+        Self {
+            bounds,
+            direction,
+            spacing,
+            cursor: 0,
+            extent: 0,
+        }
+    }
+
+    /// reserves space for a widget that would like to be `width` x `height`, returning where it should go.
+    pub fn allocate(&mut self, width: usize, height: usize) -> Rect {
+        let rect = match self.direction {
+            Direction::Vertical => Rect::new(
+                self.bounds.x,
+                self.bounds.y + self.cursor as i32,
+                self.bounds.w,
+                height,
+            ),
+            Direction::Horizontal => Rect::new(
+                self.bounds.x + self.cursor as i32,
+                self.bounds.y,
+                width,
+                height,
+            ),
+        };
+
+        let (along, across) = match self.direction {
+            Direction::Vertical => (rect.h, rect.w),
+            Direction::Horizontal => (rect.w, rect.h),
+        };
+        self.cursor += along + self.spacing;
+        self.extent = self.extent.max(across);
+
+        rect
+    }
+}
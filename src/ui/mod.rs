@@ -0,0 +1,5 @@
+mod layout;
+mod ui;
+
+pub use layout::{Direction, Rect};
+pub use ui::{Theme, Ui};
@@ -0,0 +1,552 @@
+use super::layout::{Direction, Layout, Rect};
+use crate::framework::{Input, Key, Pixels};
+
+/// how tall a single line widget is; the font is 8 pixels tall, plus padding.
+const LINE_HEIGHT: usize = 12;
+/// padding between the edge of a widget and its contents.
+const PADDING: usize = 2;
+/// space between widgets in a layout.
+const SPACING: usize = 2;
+
+type Colour = (u8, u8, u8);
+
+/// the colours used to draw widgets.
+#[derive(Clone)]
+pub struct Theme {
+    pub text: Colour,
+    pub text_disabled: Colour,
+    pub panel: Colour,
+    pub panel_border: Colour,
+    pub title: Colour,
+    pub widget: Colour,
+    pub widget_hot: Colour,
+    pub widget_active: Colour,
+    pub accent: Colour,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            text: (255, 255, 255),
+            text_disabled: (128, 128, 128),
+            panel: (24, 24, 32),
+            panel_border: (96, 96, 128),
+            title: (48, 48, 80),
+            widget: (48, 48, 64),
+            widget_hot: (72, 72, 96),
+            widget_active: (96, 96, 136),
+            accent: (224, 176, 64),
+        }
+    }
+}
+
+enum DrawCommand {
+    Fill(Rect, Colour),
+    Outline(Rect, Colour),
+    Text((i32, i32), String, Colour),
+}
+
+/// an immediate-mode ui.
+///
+/// widgets are declared every frame from `GameState::on_update`, where they read the input and
+/// return whether they were interacted with. drawing is deferred until `draw` is called from
+/// `GameState::on_draw`, since input and pixels are not available at the same time.
+///
+/// widgets are identified by their label, so two widgets in the same panel need different labels.
+/// anything after a `##` in a label is used for identification but not displayed, which allows
+/// e.g. two buttons both showing "x".
+pub struct Ui {
+    theme: Theme,
+    screen_size: (usize, usize),
+
+    mouse: (i32, i32),
+    mouse_down: bool,
+    mouse_pressed: bool,
+    mouse_released: bool,
+    mouse_wheel: f32,
+    keys_pressed: Vec<Key>,
+    text: String,
+
+    /// the widget under the mouse.
+    hot: Option<u64>,
+    /// the widget currently being clicked or dragged.
+    active: Option<u64>,
+    /// the widget receiving keyboard input.
+    focused: Option<u64>,
+    /// whether `focused` was declared this frame; focus is dropped from widgets that disappear.
+    focused_seen: bool,
+    /// per-widget scroll offsets, for lists.
+    scroll: Vec<(u64, usize)>,
+
+    id_scope: u64,
+    layouts: Vec<Layout>,
+    commands: Vec<DrawCommand>,
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Self::with_theme(Theme::default())
+    }
+
+    pub fn with_theme(theme: Theme) -> Self {
+        Self {
+            theme,
+            screen_size: (256, 256),
+            mouse: (0, 0),
+            mouse_down: false,
+            mouse_pressed: false,
+            mouse_released: false,
+            mouse_wheel: 0.0,
+            keys_pressed: Vec::new(),
+            text: String::new(),
+            hot: None,
+            active: None,
+            focused: None,
+            focused_seen: false,
+            scroll: Vec::new(),
+            id_scope: 0,
+            layouts: Vec::new(),
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// returns the size of the pixels the ui was last drawn into.
+    pub fn screen_size(&self) -> (usize, usize) {
+        self.screen_size
+    }
+
+    /// returns true if a text field has keyboard focus, so the caller should ignore key presses.
+    pub fn wants_keyboard(&self) -> bool {
+        self.focused.is_some()
+    }
+
+    /// returns true if the mouse is over any panel declared this frame.
+    pub fn wants_mouse(&self) -> bool {
+        self.hot.is_some() || self.active.is_some()
+    }
+
+    /// starts a new frame of ui, discarding everything declared in the previous one.
+    pub fn begin(&mut self, input: &Input) {
+        let mouse_down = input.mouse_left;
+        self.mouse_pressed = mouse_down && !self.mouse_down;
+        self.mouse_released = !mouse_down && self.mouse_down;
+        self.mouse_down = mouse_down;
+        self.mouse = (input.mouse_x as i32, input.mouse_y as i32);
+        self.mouse_wheel = input.mouse_wheel;
+        self.keys_pressed.clone_from(&input.keys_pressed);
+        self.text.clone_from(&input.text);
+
+        self.hot = None;
+        self.focused_seen = false;
+        self.id_scope = 0;
+        self.layouts.clear();
+        self.commands.clear();
+
+        // clicking on nothing in particular drops keyboard focus.
+        if self.mouse_pressed {
+            self.focused = None;
+        }
+    }
+
+    /// finishes the frame; must be called after every widget has been declared.
+    pub fn end(&mut self) {
+        if !self.focused_seen {
+            self.focused = None;
+        }
+        if !self.mouse_down {
+            self.active = None;
+        }
+    }
+
+    /// draws everything declared since the last `begin`.
+    pub fn draw(&mut self, pixels: &mut Pixels) {
+        self.screen_size = (pixels.width(), pixels.height());
+
+        for command in &self.commands {
+            match command {
+                DrawCommand::Fill(rect, colour) => {
+                    pixels.fill_rect(rect.position(), rect.size(), *colour)
+                }
+                DrawCommand::Outline(rect, colour) => {
+                    pixels.draw_rect(rect.position(), rect.size(), *colour)
+                }
+                DrawCommand::Text(position, text, colour) => {
+                    pixels.draw_text_coloured(*position, text, *colour, None)
+                }
+            }
+        }
+    }
+
+    /// begins a titled panel; widgets declared until `end_panel` are laid out vertically inside of it.
+    pub fn begin_panel(&mut self, title: &str, rect: Rect) {
+        self.id_scope = hash(self.id_scope, title);
+
+        self.commands
+            .push(DrawCommand::Fill(rect, self.theme.panel));
+        self.commands
+            .push(DrawCommand::Outline(rect, self.theme.panel_border));
+
+        let mut content = rect.shrink(PADDING + 1);
+        if !title.is_empty() {
+            let title_rect = Rect::new(
+                rect.x + 1,
+                rect.y + 1,
+                rect.w.saturating_sub(2),
+                LINE_HEIGHT,
+            );
+            self.commands
+                .push(DrawCommand::Fill(title_rect, self.theme.title));
+            self.push_text(title_rect, display_text(title), self.theme.text, true);
+
+            content.y += LINE_HEIGHT as i32;
+            content.h = content.h.saturating_sub(LINE_HEIGHT);
+        }
+
+        if rect.contains(self.mouse.0, self.mouse.1) && self.hot.is_none() {
+            // the panel itself is hot so clicks on its background are not seen by the game.
+            self.hot = Some(self.id_scope);
+        }
+
+        self.layouts
+            .push(Layout::new(content, Direction::Vertical, SPACING));
+    }
+
+    pub fn end_panel(&mut self) {
+        self.layouts.pop();
+        self.id_scope = 0;
+    }
+
+    /// begins a row; widgets declared until `end_row` are placed side by side.
+    pub fn begin_row(&mut self) {
+        let rect = self.allocate(0, LINE_HEIGHT);
+        self.layouts
+            .push(Layout::new(rect, Direction::Horizontal, SPACING));
+    }
+
+    pub fn end_row(&mut self) {
+        self.layouts.pop();
+    }
+
+    /// leaves an empty gap of `amount` pixels.
+    pub fn space(&mut self, amount: usize) {
+        self.allocate(amount, amount);
+    }
+
+    /// draws a horizontal line across the layout.
+    pub fn separator(&mut self) {
+        let rect = self.allocate(1, 3);
+        self.commands.push(DrawCommand::Fill(
+            Rect::new(rect.x, rect.y + 1, rect.w, 1),
+            self.theme.panel_border,
+        ));
+    }
+
+    pub fn label(&mut self, text: &str) {
+        self.label_coloured(text, self.theme.text);
+    }
+
+    pub fn label_coloured(&mut self, text: &str, colour: Colour) {
+        let rect = self.allocate(Pixels::text_width(text) + PADDING * 2, LINE_HEIGHT);
+        self.push_text(rect, text, colour, false);
+    }
+
+    /// returns true on the frame the button is clicked.
+    pub fn button(&mut self, label: &str) -> bool {
+        let id = hash(self.id_scope, label);
+        let text = display_text(label);
+        let rect = self.allocate(Pixels::text_width(text) + PADDING * 4, LINE_HEIGHT);
+
+        let clicked = self.interact(id, rect) && self.mouse_released;
+
+        let colour = self.widget_colour(id);
+        self.commands.push(DrawCommand::Fill(rect, colour));
+        self.push_text(rect, text, self.theme.text, true);
+
+        clicked
+    }
+
+    /// returns true on the frame the value changes.
+    pub fn checkbox(&mut self, label: &str, value: &mut bool) -> bool {
+        let id = hash(self.id_scope, label);
+        let text = display_text(label);
+        let rect = self.allocate(
+            LINE_HEIGHT + PADDING + Pixels::text_width(text),
+            LINE_HEIGHT,
+        );
+
+        let changed = self.interact(id, rect) && self.mouse_released;
+        if changed {
+            *value = !*value;
+        }
+
+        let box_rect = Rect::new(rect.x, rect.y, LINE_HEIGHT, LINE_HEIGHT);
+        self.commands
+            .push(DrawCommand::Fill(box_rect, self.widget_colour(id)));
+        if *value {
+            self.commands
+                .push(DrawCommand::Fill(box_rect.shrink(3), self.theme.accent));
+        }
+
+        let text_rect = Rect::new(
+            rect.x + (LINE_HEIGHT + PADDING) as i32,
+            rect.y,
+            rect.w.saturating_sub(LINE_HEIGHT + PADDING),
+            rect.h,
+        );
+        self.push_text(text_rect, text, self.theme.text, false);
+
+        changed
+    }
+
+    /// a horizontal slider between `min` and `max`; returns true when the value changes.
+    pub fn slider(&mut self, label: &str, value: &mut f32, min: f32, max: f32) -> bool {
+        let id = hash(self.id_scope, label);
+        let text = format!("{}: {:.2}", display_text(label), value);
+        let rect = self.allocate(Pixels::text_width(&text) + PADDING * 4, LINE_HEIGHT);
+
+        self.interact(id, rect);
+
+        let mut changed = false;
+        if self.active == Some(id) && rect.w > 0 {
+            let t = ((self.mouse.0 - rect.x) as f32 / rect.w as f32).clamp(0.0, 1.0);
+            let new_value = min + (max - min) * t;
+            if new_value != *value {
+                *value = new_value;
+                changed = true;
+            }
+        }
+
+        let t = if max > min {
+            ((*value - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self.commands
+            .push(DrawCommand::Fill(rect, self.widget_colour(id)));
+        self.commands.push(DrawCommand::Fill(
+            Rect::new(rect.x, rect.y, (rect.w as f32 * t) as usize, rect.h),
+            self.theme.widget_active,
+        ));
+        self.push_text(rect, &text, self.theme.text, true);
+
+        changed
+    }
+
+    /// a single line text field; returns true when its contents change.
+    ///
+    /// the field takes keyboard focus when clicked and releases it on enter or escape.
+    pub fn text_field(&mut self, label: &str, value: &mut String) -> bool {
+        let id = hash(self.id_scope, label);
+        let rect = self.allocate(Pixels::text_width(value) + PADDING * 4 + 8, LINE_HEIGHT);
+
+        if self.interact(id, rect) && self.mouse_pressed {
+            self.focused = Some(id);
+        }
+
+        let focused = self.focused == Some(id);
+        let mut changed = false;
+        if focused {
+            self.focused_seen = true;
+
+            for c in self.text.chars().filter(|c| !c.is_control()) {
+                value.push(c);
+                changed = true;
+            }
+            for key in &self.keys_pressed {
+                match key {
+                    Key::Backspace => changed |= value.pop().is_some(),
+                    Key::Enter | Key::Escape => self.focused = None,
+                    _ => {}
+                }
+            }
+        }
+
+        let colour = if focused {
+            self.theme.widget_active
+        } else {
+            self.widget_colour(id)
+        };
+        self.commands.push(DrawCommand::Fill(rect, colour));
+
+        // show the end of the text if it doesn't fit.
+        let max_chars = rect.w.saturating_sub(PADDING * 2) / 8;
+        let mut shown: String = if value.is_empty() && !focused {
+            display_text(label).to_string()
+        } else {
+            value.clone()
+        };
+        if focused {
+            shown.push('_');
+        }
+        let skip = shown.chars().count().saturating_sub(max_chars);
+        let shown: String = shown.chars().skip(skip).collect();
+
+        let text_colour = if value.is_empty() && !focused {
+            self.theme.text_disabled
+        } else {
+            self.theme.text
+        };
+        self.push_text(rect, &shown, text_colour, false);
+
+        changed
+    }
+
+    /// a scrollable list showing `rows` items at once; returns true when the selection changes.
+    pub fn list<S: AsRef<str>>(
+        &mut self,
+        label: &str,
+        items: &[S],
+        selected: &mut usize,
+        rows: usize,
+    ) -> bool {
+        let id = hash(self.id_scope, label);
+        let rect = self.allocate(0, rows * LINE_HEIGHT + 2);
+        let hovered = self.interact(id, rect);
+
+        let mut scroll = self.scroll_of(id);
+        if hovered && self.mouse_wheel != 0.0 {
+            scroll = if self.mouse_wheel > 0.0 {
+                scroll.saturating_sub(1)
+            } else {
+                scroll + 1
+            };
+        }
+        scroll = scroll.min(items.len().saturating_sub(rows));
+        self.set_scroll_of(id, scroll);
+
+        self.commands
+            .push(DrawCommand::Fill(rect, self.theme.widget));
+        self.commands
+            .push(DrawCommand::Outline(rect, self.theme.panel_border));
+
+        let mut changed = false;
+        for (row, (index, item)) in items.iter().enumerate().skip(scroll).take(rows).enumerate() {
+            let row_rect = Rect::new(
+                rect.x + 1,
+                rect.y + 1 + (row * LINE_HEIGHT) as i32,
+                rect.w.saturating_sub(2),
+                LINE_HEIGHT,
+            );
+            let row_hovered = row_rect.contains(self.mouse.0, self.mouse.1);
+
+            if row_hovered && self.active == Some(id) && self.mouse_released && *selected != index {
+                *selected = index;
+                changed = true;
+            }
+
+            if index == *selected {
+                self.commands
+                    .push(DrawCommand::Fill(row_rect, self.theme.widget_active));
+            } else if row_hovered {
+                self.commands
+                    .push(DrawCommand::Fill(row_rect, self.theme.widget_hot));
+            }
+            self.push_text(row_rect, item.as_ref(), self.theme.text, false);
+        }
+
+        // a thin scroll bar, if there is anything to scroll.
+        if items.len() > rows && rect.h > 2 {
+            let track = rect.h - 2;
+            let bar_h = (track * rows / items.len()).max(2);
+            let bar_y = (track - bar_h) * scroll / (items.len() - rows);
+            self.commands.push(DrawCommand::Fill(
+                Rect::new(
+                    rect.x + rect.w as i32 - 3,
+                    rect.y + 1 + bar_y as i32,
+                    2,
+                    bar_h,
+                ),
+                self.theme.accent,
+            ));
+        }
+
+        changed
+    }
+
+    /// reserves space in the current layout, or the whole screen if there is no layout.
+    fn allocate(&mut self, width: usize, height: usize) -> Rect {
+        match self.layouts.last_mut() {
+            Some(layout) => layout.allocate(width, height),
+            None => Rect::new(0, 0, width, height),
+        }
+    }
+
+    /// updates hot and active state for a widget, returning true if the mouse is over it.
+    fn interact(&mut self, id: u64, rect: Rect) -> bool {
+        let hovered = rect.contains(self.mouse.0, self.mouse.1);
+        if hovered {
+            self.hot = Some(id);
+            if self.mouse_pressed {
+                self.active = Some(id);
+            }
+        }
+
+        hovered && (self.active.is_none() || self.active == Some(id))
+    }
+
+    fn widget_colour(&self, id: u64) -> Colour {
+        if self.active == Some(id) {
+            self.theme.widget_active
+        } else if self.hot == Some(id) {
+            self.theme.widget_hot
+        } else {
+            self.theme.widget
+        }
+    }
+
+    /// queues text vertically centred in `rect`, and optionally horizontally centred too.
+    fn push_text(&mut self, rect: Rect, text: &str, colour: Colour, centred: bool) {
+        let y = rect.y + (rect.h as i32 - 8) / 2;
+        let x = if centred {
+            rect.x + (rect.w as i32 - Pixels::text_width(text) as i32) / 2
+        } else {
+            rect.x + PADDING as i32
+        };
+
+        self.commands
+            .push(DrawCommand::Text((x, y), text.to_string(), colour));
+    }
+
+    fn scroll_of(&self, id: u64) -> usize {
+        self.scroll
+            .iter()
+            .find(|(scroll_id, _)| *scroll_id == id)
+            .map_or(0, |(_, scroll)| *scroll)
+    }
+
+    fn set_scroll_of(&mut self, id: u64, scroll: usize) {
+        match self
+            .scroll
+            .iter_mut()
+            .find(|(scroll_id, _)| *scroll_id == id)
+        {
+            Some(entry) => entry.1 = scroll,
+            None => self.scroll.push((id, scroll)),
+        }
+    }
+}
+
+/// returns the part of a label before any `##`.
+fn display_text(label: &str) -> &str {
+    label.split("##").next().unwrap_or(label)
+}
+
+/// fnv-1a hash of a label, mixed with the id of the enclosing scope.
+fn hash(scope: u64, label: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325 ^ scope;
+    for byte in label.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
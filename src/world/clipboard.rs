@@ -0,0 +1,239 @@
+use super::region::{checked_add, to_position, Axis, Region};
+use super::{history::TileChange, tiles, Tile, World};
+use glam::IVec3;
+
+/// tiles copied out of a world, to be transformed and pasted back in; saved as a schematic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clipboard {
+    size: (usize, usize, usize),
+    /// with x varying fastest, then y, then z, the same as `World`.
+    tiles: Vec<Tile>,
+}
+
+impl Clipboard {
+    /// creates a clipboard full of air.
+    pub fn new(size_x: usize, size_y: usize, size_z: usize) -> Self {
+        Self {
+            size: (size_x, size_y, size_z),
+            tiles: vec![tiles::AIR; size_x * size_y * size_z],
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize, usize) {
+        self.size
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<Tile> {
+        self.offset_of(x, y, z).map(|index| self.tiles[index])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, tile: Tile) {
+        if let Some(index) = self.offset_of(x, y, z) {
+            self.tiles[index] = tile;
+        }
+    }
+
+    /// returns every tile, with x varying fastest, then y, then z.
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub(super) fn tiles_mut(&mut self) -> &mut [Tile] {
+        &mut self.tiles
+    }
+
+    /// turns the contents a quarter turn around an axis for each of `turns`; negative turns go the
+    /// other way, and four turns are back where it started.
+    pub fn rotate(&mut self, axis: Axis, turns: i32) {
+        for _ in 0..turns.rem_euclid(4) {
+            self.rotate_once(axis);
+        }
+    }
+
+    /// flips the contents along an axis.
+    pub fn mirror(&mut self, axis: Axis) {
+        let size = self.dimensions();
+        self.remap(size, |mut position| {
+            position[axis.index()] = size[axis.index()] - 1 - position[axis.index()];
+            position
+        });
+    }
+
+    fn rotate_once(&mut self, axis: Axis) {
+        // the two axes being turned, so that a goes to where b was.
+        let (a, b) = ((axis.index() + 1) % 3, (axis.index() + 2) % 3);
+        let size = self.dimensions();
+
+        let mut rotated_size = size;
+        rotated_size[a] = size[b];
+        rotated_size[b] = size[a];
+        self.remap(rotated_size, |position| {
+            let mut rotated = position;
+            rotated[a] = size[b] - 1 - position[b];
+            rotated[b] = position[a];
+            rotated
+        });
+    }
+
+    /// moves every tile to a new position, in a clipboard of a new size.
+    fn remap(&mut self, new_size: [usize; 3], map: impl Fn([usize; 3]) -> [usize; 3]) {
+        let mut remapped = Clipboard::new(new_size[0], new_size[1], new_size[2]);
+        for z in 0..self.size.2 {
+            for y in 0..self.size.1 {
+                for x in 0..self.size.0 {
+                    let [new_x, new_y, new_z] = map([x, y, z]);
+                    remapped.set(new_x, new_y, new_z, self.tiles[self.index(x, y, z)]);
+                }
+            }
+        }
+        *self = remapped;
+    }
+
+    fn dimensions(&self) -> [usize; 3] {
+        [self.size.0, self.size.1, self.size.2]
+    }
+
+    fn offset_of(&self, x: usize, y: usize, z: usize) -> Option<usize> {
+        (x < self.size.0 && y < self.size.1 && z < self.size.2).then(|| self.index(x, y, z))
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (self.size.0 * self.size.1 * z) + (self.size.0 * y) + x
+    }
+}
+
+impl World {
+    /// copies the tiles in a region; anything outside the world is copied as air.
+    ///
+    /// returns nothing if the region has more tiles than a world can, as it'd be too big to paste
+    /// anywhere or save as a schematic.
+    pub fn copy(&self, region: Region) -> Option<Clipboard> {
+        let size = region.size()?;
+        let (size_x, size_y, size_z) = (size.x as usize, size.y as usize, size.z as usize);
+        if !World::is_valid_size(size_x, size_y, size_z) {
+            return None;
+        }
+
+        let mut clipboard = Clipboard::new(size_x, size_y, size_z);
+        for position in region.positions() {
+            let tile = to_position(position)
+                .and_then(|(x, y, z)| self.get(x, y, z))
+                .unwrap_or(tiles::AIR);
+            let offset = position - region.min;
+            clipboard.set(
+                offset.x as usize,
+                offset.y as usize,
+                offset.z as usize,
+                tile,
+            );
+        }
+        Some(clipboard)
+    }
+
+    /// pastes a clipboard with its lowest corner at a position, returning what changed.
+    ///
+    /// air in the clipboard leaves the world alone, unless `include_air` is set.
+    pub fn paste(
+        &mut self,
+        clipboard: &Clipboard,
+        position: IVec3,
+        include_air: bool,
+    ) -> Vec<TileChange> {
+        let (size_x, size_y, size_z) = clipboard.size();
+        let region = Region::new(
+            IVec3::ZERO,
+            IVec3::new(size_x as i32, size_y as i32, size_z as i32) - IVec3::ONE,
+        );
+
+        let tiles: Vec<_> = region
+            .positions()
+            .zip(clipboard.tiles().iter().copied())
+            .filter(|(_, tile)| include_air || *tile != tiles::AIR)
+            .filter_map(|(offset, tile)| Some((to_position(checked_add(position, offset)?)?, tile)))
+            .collect();
+        self.set_many(tiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a clipboard with a different tile in every position and a different length on every axis,
+    /// so any tile ending up in the wrong place shows.
+    fn sample_clipboard() -> Clipboard {
+        let mut clipboard = Clipboard::new(2, 3, 4);
+        for (index, tile) in clipboard.tiles_mut().iter_mut().enumerate() {
+            *tile = index as Tile + 1;
+        }
+        clipboard
+    }
+
+    #[test]
+    fn four_turns_are_the_identity() {
+        for axis in Axis::ALL {
+            let mut clipboard = sample_clipboard();
+            clipboard.rotate(axis, 1);
+            assert_ne!(clipboard, sample_clipboard());
+            clipboard.rotate(axis, 3);
+            assert_eq!(clipboard, sample_clipboard());
+        }
+    }
+
+    #[test]
+    fn negative_turns_go_the_other_way() {
+        for axis in Axis::ALL {
+            let mut clipboard = sample_clipboard();
+            clipboard.rotate(axis, -1);
+            clipboard.rotate(axis, 1);
+            assert_eq!(clipboard, sample_clipboard());
+        }
+    }
+
+    #[test]
+    fn mirroring_twice_is_the_identity() {
+        for axis in Axis::ALL {
+            let mut clipboard = sample_clipboard();
+            clipboard.mirror(axis);
+            assert_ne!(clipboard, sample_clipboard());
+            clipboard.mirror(axis);
+            assert_eq!(clipboard, sample_clipboard());
+        }
+    }
+
+    #[test]
+    fn copies_and_pastes() {
+        let mut world = World::with_size(4, 4, 4);
+        world.set(1, 1, 1, tiles::STONE);
+        let clipboard = world
+            .copy(Region::new(IVec3::ONE, IVec3::splat(2)))
+            .unwrap();
+        assert_eq!(clipboard.get(0, 0, 0), Some(tiles::STONE));
+
+        world.paste(&clipboard, IVec3::splat(2), false);
+        assert_eq!(world.get(2, 2, 2), Some(tiles::STONE));
+    }
+
+    #[test]
+    fn pastes_past_the_largest_position_are_skipped() {
+        let mut world = World::with_size(4, 4, 4);
+        let clipboard = sample_clipboard();
+        assert!(world
+            .paste(&clipboard, IVec3::new(i32::MAX, 0, 0), true)
+            .is_empty());
+        assert!(world
+            .paste(&clipboard, IVec3::splat(i32::MIN), true)
+            .is_empty());
+    }
+
+    #[test]
+    fn regions_too_big_to_copy_are_refused() {
+        let world = World::with_size(4, 4, 4);
+        assert!(world
+            .copy(Region::new(IVec3::ZERO, IVec3::splat(1 << 20)))
+            .is_none());
+        assert!(world
+            .copy(Region::new(IVec3::splat(i32::MIN), IVec3::splat(i32::MAX)))
+            .is_none());
+    }
+}
@@ -0,0 +1,337 @@
+use super::{Tile, World};
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+
+/// how long after an edit the next one still joins the same undo step, in seconds.
+const COALESCE_WINDOW: f32 = 0.3;
+
+/// how many bytes of changes are kept before the oldest undo steps are forgotten.
+const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// one tile changed by an edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileChange {
+    pub position: (usize, usize, usize),
+    pub old: Tile,
+    pub new: Tile,
+}
+
+/// a group of changes undone and redone together.
+#[derive(Default)]
+struct Transaction {
+    changes: Vec<TileChange>,
+}
+
+impl Transaction {
+    fn memory(&self) -> usize {
+        self.changes.len() * mem::size_of::<TileChange>()
+    }
+}
+
+/// records edits to a world so they can be undone and redone.
+///
+/// edits made through `set` are grouped into transactions: everything between `begin` and `end`
+/// is one undo step, and edits outside of one join the previous step if they come within a moment
+/// of each other, so dragging out a line of tiles undoes in one go.
+pub struct EditHistory {
+    undo: VecDeque<Transaction>,
+    redo: Vec<Transaction>,
+    /// the transaction edits are currently going into, if any.
+    open: Option<Transaction>,
+    /// where each tile in `open` is in its changes, so a tile edited twice is only recorded once.
+    open_positions: HashMap<(usize, usize, usize), usize>,
+    /// how many `begin`s haven't been matched by an `end` yet.
+    depth: u32,
+    /// seconds since the last edit, for coalescing.
+    since_last_edit: f32,
+    memory_limit: usize,
+    memory_used: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EditHistory {
+    pub fn new() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            open: None,
+            open_positions: HashMap::new(),
+            depth: 0,
+            since_last_edit: 0.0,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            memory_used: 0,
+        }
+    }
+
+    /// sets how many bytes of changes to keep, forgetting the oldest undo steps beyond it.
+    pub fn set_memory_limit(&mut self, bytes: usize) {
+        self.memory_limit = bytes;
+        self.trim();
+    }
+
+    /// advances the clock used to coalesce edits; call once a frame.
+    pub fn advance(&mut self, delta_time: f32) {
+        self.since_last_edit += delta_time;
+        if self.depth == 0 && self.since_last_edit > COALESCE_WINDOW {
+            self.close();
+        }
+    }
+
+    /// starts a transaction, so every edit until the matching `end` is undone in one step.
+    ///
+    /// transactions can nest; only the outermost one makes an undo step.
+    pub fn begin(&mut self) {
+        if self.depth == 0 {
+            self.close();
+        }
+        self.depth += 1;
+    }
+
+    pub fn end(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            self.close();
+        }
+    }
+
+    /// sets a tile, recording the change.
+    pub fn set(&mut self, world: &mut World, x: usize, y: usize, z: usize, tile: Tile) {
+        let Some(old) = world.get(x, y, z) else {
+            return;
+        };
+        if old == tile {
+            return;
+        }
+
+        world.set(x, y, z, tile);
+        self.record(TileChange {
+            position: (x, y, z),
+            old,
+            new: tile,
+        });
+    }
+
+    /// records a change already made to the world, for edits made some other way.
+    pub fn record(&mut self, change: TileChange) {
+        self.redo.clear();
+        self.since_last_edit = 0.0;
+
+        let open = self.open.get_or_insert_with(Transaction::default);
+        match self.open_positions.get(&change.position) {
+            // keeps the tile's original value, so undoing goes all the way back.
+            Some(index) => open.changes[*index].new = change.new,
+            None => {
+                self.open_positions
+                    .insert(change.position, open.changes.len());
+                open.changes.push(change);
+            }
+        }
+    }
+
+    /// records changes already made to the world as one undo step, e.g. from `World::fill`.
+    pub fn record_all(&mut self, changes: Vec<TileChange>) {
+        self.begin();
+        for change in changes {
+            self.record(change);
+        }
+        self.end();
+    }
+
+    /// undoes the most recent step, returning false if there was nothing to undo.
+    pub fn undo(&mut self, world: &mut World) -> bool {
+        self.close();
+        let Some(transaction) = self.undo.pop_back() else {
+            return false;
+        };
+
+        self.memory_used -= transaction.memory();
+        world.set_many(
+            transaction
+                .changes
+                .iter()
+                .rev()
+                .map(|change| (change.position, change.old)),
+        );
+        self.redo.push(transaction);
+        true
+    }
+
+    /// redoes the most recently undone step, returning false if there was nothing to redo.
+    pub fn redo(&mut self, world: &mut World) -> bool {
+        self.close();
+        let Some(transaction) = self.redo.pop() else {
+            return false;
+        };
+
+        world.set_many(
+            transaction
+                .changes
+                .iter()
+                .map(|change| (change.position, change.new)),
+        );
+        self.push_undo(transaction);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.open.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// forgets everything, e.g. when a different world is loaded.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open = None;
+        self.open_positions.clear();
+        self.memory_used = 0;
+    }
+
+    /// finishes the open transaction, making it an undo step.
+    fn close(&mut self) {
+        self.open_positions.clear();
+        if let Some(transaction) = self.open.take() {
+            // changes that ended up back where they started aren't worth undoing.
+            let changes: Vec<TileChange> = transaction
+                .changes
+                .into_iter()
+                .filter(|change| change.old != change.new)
+                .collect();
+            if !changes.is_empty() {
+                self.push_undo(Transaction { changes });
+            }
+        }
+    }
+
+    fn push_undo(&mut self, transaction: Transaction) {
+        self.memory_used += transaction.memory();
+        self.undo.push_back(transaction);
+        self.trim();
+    }
+
+    /// forgets the oldest undo steps until the history fits in its memory limit.
+    fn trim(&mut self) {
+        while self.memory_used > self.memory_limit {
+            let Some(oldest) = self.undo.pop_front() else {
+                break;
+            };
+            self.memory_used -= oldest.memory();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::tiles;
+
+    fn tile(world: &World, x: usize) -> Tile {
+        world.get(x, 0, 0).unwrap()
+    }
+
+    /// makes an edit far enough apart from the last that it won't be coalesced with it.
+    fn separate_edit(history: &mut EditHistory, world: &mut World, x: usize, new: Tile) {
+        history.advance(COALESCE_WINDOW * 2.0);
+        history.set(world, x, 0, 0, new);
+        history.advance(COALESCE_WINDOW * 2.0);
+    }
+
+    #[test]
+    fn undoes_and_redoes() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        separate_edit(&mut history, &mut world, 0, tiles::STONE);
+        separate_edit(&mut history, &mut world, 0, tiles::DIRT);
+
+        assert!(history.undo(&mut world));
+        assert_eq!(tile(&world, 0), tiles::STONE);
+        assert!(history.undo(&mut world));
+        assert_eq!(tile(&world, 0), tiles::AIR);
+        assert!(!history.undo(&mut world));
+
+        assert!(history.redo(&mut world));
+        assert!(history.redo(&mut world));
+        assert_eq!(tile(&world, 0), tiles::DIRT);
+        assert!(!history.redo(&mut world));
+    }
+
+    #[test]
+    fn new_edits_clear_redo() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        separate_edit(&mut history, &mut world, 0, tiles::STONE);
+        history.undo(&mut world);
+        assert!(history.can_redo());
+
+        separate_edit(&mut history, &mut world, 1, tiles::STONE);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn coalesces_quick_edits() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        for x in 0..4 {
+            history.set(&mut world, x, 0, 0, tiles::STONE);
+            history.advance(COALESCE_WINDOW * 0.5);
+        }
+
+        assert!(history.undo(&mut world));
+        assert!((0..4).all(|x| tile(&world, x) == tiles::AIR));
+        assert!(!history.undo(&mut world));
+    }
+
+    #[test]
+    fn edits_undone_within_a_step_are_dropped() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        history.set(&mut world, 0, 0, 0, tiles::STONE);
+        history.set(&mut world, 0, 0, 0, tiles::AIR);
+        history.advance(COALESCE_WINDOW * 2.0);
+
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn nested_transactions_make_one_step() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        history.begin();
+        history.set(&mut world, 0, 0, 0, tiles::STONE);
+        history.begin();
+        history.set(&mut world, 1, 0, 0, tiles::STONE);
+        history.end();
+        // still open, so waiting doesn't split it.
+        history.advance(COALESCE_WINDOW * 2.0);
+        history.set(&mut world, 2, 0, 0, tiles::STONE);
+        history.end();
+
+        assert!(history.undo(&mut world));
+        assert!((0..3).all(|x| tile(&world, x) == tiles::AIR));
+        assert!(!history.undo(&mut world));
+    }
+
+    #[test]
+    fn forgets_the_oldest_steps_past_the_memory_limit() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        history.set_memory_limit(mem::size_of::<TileChange>() * 2);
+        for x in 0..3 {
+            separate_edit(&mut history, &mut world, x, tiles::STONE);
+        }
+
+        assert!(history.undo(&mut world));
+        assert!(history.undo(&mut world));
+        assert!(!history.undo(&mut world));
+        assert_eq!(tile(&world, 0), tiles::STONE);
+        assert_eq!(tile(&world, 1), tiles::AIR);
+    }
+}
@@ -0,0 +1,279 @@
+use super::{tiles, Tile};
+use std::collections::VecDeque;
+
+/// the brightest a light level can be.
+pub const MAX_LIGHT: u8 = 15;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Channel {
+    /// light from the sky, which travels straight down through air without fading.
+    Sky,
+    /// light from emissive tiles.
+    Block,
+}
+
+/// per-voxel light levels, stored alongside a world's tiles.
+///
+/// each voxel has a sky light and a block light level from 0 to 15, packed into the high and low
+/// nibbles of a byte. light spreads to neighbouring non-opaque voxels, losing one level per step.
+#[derive(Clone)]
+pub(super) struct LightMap {
+    size: (usize, usize, usize),
+    levels: Vec<u8>,
+}
+
+impl LightMap {
+    pub fn new(size: (usize, usize, usize)) -> Self {
+        Self {
+            size,
+            levels: vec![0; size.0 * size.1 * size.2],
+        }
+    }
+
+    pub fn sky(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.levels[self.index(x, y, z)] >> 4
+    }
+
+    pub fn block(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.levels[self.index(x, y, z)] & 0x0f
+    }
+
+    /// recomputes every light level from scratch.
+    pub fn relight(&mut self, tiles: &[Tile]) {
+        self.levels.fill(0);
+
+        let mut sky_queue = VecDeque::new();
+        let mut block_queue = VecDeque::new();
+
+        // sky light pours straight down each column until it hits something.
+        for z in 0..self.size.2 {
+            for x in 0..self.size.0 {
+                for y in (0..self.size.1).rev() {
+                    let index = self.index(x, y, z);
+                    if tiles[index] != tiles::AIR {
+                        break;
+                    }
+                    self.set_level(Channel::Sky, index, MAX_LIGHT);
+                    sky_queue.push_back(index);
+                }
+            }
+        }
+
+        for (index, tile) in tiles.iter().enumerate() {
+            let emission = tiles::emission(*tile);
+            if emission > 0 {
+                self.set_level(Channel::Block, index, emission);
+                block_queue.push_back(index);
+            }
+        }
+
+        self.propagate(Channel::Sky, tiles, sky_queue);
+        self.propagate(Channel::Block, tiles, block_queue);
+    }
+
+    /// updates light levels after the tile at a position has changed.
+    pub fn on_tile_changed(&mut self, tiles: &[Tile], x: usize, y: usize, z: usize) {
+        let index = self.index(x, y, z);
+        let tile = tiles[index];
+
+        for channel in [Channel::Sky, Channel::Block] {
+            // take away all light that came from, or through, the changed voxel...
+            let mut queue = self.remove(channel, tiles, index);
+
+            // ...then let the voxel's own light and its neighbours' light flow back in.
+            let source = match channel {
+                Channel::Block => tiles::emission(tile),
+                Channel::Sky if tile == tiles::AIR && self.is_under_sky(index) => MAX_LIGHT,
+                Channel::Sky => 0,
+            };
+            if source > 0 {
+                self.set_level(channel, index, source);
+                queue.push_back(index);
+            }
+            for neighbour in self.neighbours(index).into_iter().flatten() {
+                if self.level(channel, neighbour) > 0 {
+                    queue.push_back(neighbour);
+                }
+            }
+
+            self.propagate(channel, tiles, queue);
+        }
+    }
+
+    /// darkens everything lit through `start`, returning the voxels bordering the dark area which still have light.
+    fn remove(&mut self, channel: Channel, tiles: &[Tile], start: usize) -> VecDeque<usize> {
+        let mut relight = VecDeque::new();
+        let mut queue = VecDeque::new();
+
+        let level = self.level(channel, start);
+        self.set_level(channel, start, 0);
+        queue.push_back((start, level));
+
+        while let Some((index, level)) = queue.pop_front() {
+            for (direction, neighbour) in self.neighbours(index).into_iter().enumerate() {
+                let Some(neighbour) = neighbour else {
+                    continue;
+                };
+
+                let neighbour_level = self.level(channel, neighbour);
+                let lit_from_here = neighbour_level != 0
+                    && (neighbour_level < level
+                        || (channel == Channel::Sky
+                            && direction == DOWN
+                            && level == MAX_LIGHT
+                            && neighbour_level == MAX_LIGHT
+                            && tiles[neighbour] == tiles::AIR));
+
+                if lit_from_here {
+                    self.set_level(channel, neighbour, 0);
+                    queue.push_back((neighbour, neighbour_level));
+                } else if neighbour_level >= level && neighbour_level > 0 {
+                    relight.push_back(neighbour);
+                }
+            }
+        }
+
+        relight
+    }
+
+    /// spreads light outwards from every voxel in the queue.
+    fn propagate(&mut self, channel: Channel, tiles: &[Tile], mut queue: VecDeque<usize>) {
+        while let Some(index) = queue.pop_front() {
+            let level = self.level(channel, index);
+            if level <= 1 {
+                continue;
+            }
+
+            for (direction, neighbour) in self.neighbours(index).into_iter().enumerate() {
+                let Some(neighbour) = neighbour else {
+                    continue;
+                };
+                if tiles::is_opaque(tiles[neighbour]) {
+                    continue;
+                }
+
+                let spread = if channel == Channel::Sky
+                    && direction == DOWN
+                    && level == MAX_LIGHT
+                    && tiles[neighbour] == tiles::AIR
+                {
+                    MAX_LIGHT
+                } else {
+                    level - 1
+                };
+
+                if spread > self.level(channel, neighbour) {
+                    self.set_level(channel, neighbour, spread);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+
+    /// returns true if the voxel is at the top of the world or has full sky light directly above it.
+    fn is_under_sky(&self, index: usize) -> bool {
+        match self.neighbours(index)[UP] {
+            Some(above) => self.level(Channel::Sky, above) == MAX_LIGHT,
+            None => true,
+        }
+    }
+
+    fn level(&self, channel: Channel, index: usize) -> u8 {
+        match channel {
+            Channel::Sky => self.levels[index] >> 4,
+            Channel::Block => self.levels[index] & 0x0f,
+        }
+    }
+
+    fn set_level(&mut self, channel: Channel, index: usize, level: u8) {
+        let packed = &mut self.levels[index];
+        *packed = match channel {
+            Channel::Sky => (*packed & 0x0f) | (level << 4),
+            Channel::Block => (*packed & 0xf0) | level,
+        };
+    }
+
+    /// returns the indices of the six neighbouring voxels, in the order -x, +x, -y, +y, -z, +z.
+    fn neighbours(&self, index: usize) -> [Option<usize>; 6] {
+        let (size_x, size_y, size_z) = self.size;
+        let x = index % size_x;
+        let y = (index / size_x) % size_y;
+        let z = index / (size_x * size_y);
+        let layer = size_x * size_y;
+
+        [
+            (x > 0).then(|| index - 1),
+            (x + 1 < size_x).then(|| index + 1),
+            (y > 0).then(|| index - size_x),
+            (y + 1 < size_y).then(|| index + size_x),
+            (z > 0).then(|| index - layer),
+            (z + 1 < size_z).then(|| index + layer),
+        ]
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (self.size.0 * self.size.1 * z) + (self.size.0 * y) + x
+    }
+}
+
+/// the index of the neighbour below, in the array returned by `LightMap::neighbours`.
+const DOWN: usize = 2;
+/// the index of the neighbour above, in the array returned by `LightMap::neighbours`.
+const UP: usize = 3;
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Rng;
+    use crate::world::{tiles, Tile, World};
+
+    /// returns the first position where a world's light differs from relighting it from scratch.
+    fn first_difference(world: &World) -> Option<(i32, i32, i32)> {
+        let mut relit = world.clone();
+        relit.relight();
+
+        let (x, y, z) = (world.size_x(), world.size_y(), world.size_z());
+        (0..z as i32)
+            .flat_map(|z| (0..y as i32).flat_map(move |y| (0..x as i32).map(move |x| (x, y, z))))
+            .find(|(x, y, z)| world.light(*x, *y, *z) != relit.light(*x, *y, *z))
+    }
+
+    #[test]
+    fn edits_light_the_same_as_relighting() {
+        const EDIT_TILES: [Tile; 6] = [
+            tiles::AIR,
+            tiles::STONE,
+            tiles::LAMP,
+            tiles::GLASS,
+            tiles::LEAVES,
+            tiles::WATER,
+        ];
+
+        let mut rng = Rng::new(0x6c69_6768);
+        let mut world = World::with_size(12, 12, 12);
+        for x in 0..12 {
+            for z in 0..12 {
+                world.set(x, 0, z, tiles::STONE);
+            }
+        }
+
+        for edit in 0..400 {
+            let (x, y, z) = (
+                rng.below(12) as usize,
+                rng.below(12) as usize,
+                rng.below(12) as usize,
+            );
+            let tile = EDIT_TILES[rng.below(EDIT_TILES.len() as u32) as usize];
+            world.set(x, y, z, tile);
+            assert_eq!(
+                first_difference(&world),
+                None,
+                "after edit {} setting ({}, {}, {}) to {}",
+                edit,
+                x,
+                y,
+                z,
+                tile
+            );
+        }
+    }
+}
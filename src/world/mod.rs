@@ -1,3 +1,16 @@
+mod clipboard;
+mod history;
+mod lighting;
+mod region;
+mod schematic;
+pub mod tiles;
+mod vox;
 mod world;
+mod worldfile;
 
-pub use world::{RaycastResult, Tile, World};
+pub use clipboard::Clipboard;
+pub use history::{EditHistory, TileChange};
+pub use lighting::MAX_LIGHT;
+pub use region::{Axis, Region};
+pub use vox::{identity_tile_map, VoxInstance, VoxModel, VoxScene};
+pub use world::{Raycast, RaycastResult, Tile, World, MAX_VOLUME};
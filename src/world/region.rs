@@ -0,0 +1,196 @@
+use super::{history::TileChange, Tile, World};
+use glam::{IVec3, Vec3};
+
+/// one of the three axes of the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    /// returns the axis' index into a vector, 0 for x up to 2 for z.
+    pub fn index(&self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+
+    /// returns the axis' name, as used in the console.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Axis::X => "x",
+            Axis::Y => "y",
+            Axis::Z => "z",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Axis> {
+        Self::ALL.into_iter().find(|axis| axis.name() == name)
+    }
+}
+
+/// an axis-aligned box of tiles, including both of its corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub min: IVec3,
+    pub max: IVec3,
+}
+
+impl Region {
+    /// creates the region between two corners, given in any order.
+    pub fn new(a: IVec3, b: IVec3) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// returns how many tiles the region spans along each axis, or nothing if a span doesn't fit
+    /// in an `i32`.
+    pub fn size(&self) -> Option<IVec3> {
+        let span = |min: i32, max: i32| max.checked_sub(min)?.checked_add(1);
+        Some(IVec3::new(
+            span(self.min.x, self.max.x)?,
+            span(self.min.y, self.max.y)?,
+            span(self.min.z, self.max.z)?,
+        ))
+    }
+
+    pub fn contains(&self, position: IVec3) -> bool {
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
+
+    /// returns the part of the region which is also in another, if they overlap.
+    pub fn intersection(&self, other: Region) -> Option<Region> {
+        let min = self.min.max(other.min);
+        let max = self.max.min(other.max);
+        min.cmple(max).all().then_some(Region { min, max })
+    }
+
+    /// returns every position in the region, with x varying fastest, then y, then z.
+    pub fn positions(&self) -> impl Iterator<Item = IVec3> {
+        let (min, max) = (self.min, self.max);
+        (min.z..=max.z).flat_map(move |z| {
+            (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec3::new(x, y, z)))
+        })
+    }
+
+    /// returns where a position is within the region, scaled from -1.0 to 1.0 on each axis, for
+    /// shapes that fit inside the region like spheres.
+    fn normalised(&self, position: IVec3) -> Vec3 {
+        let (min, max) = (self.min.as_vec3(), self.max.as_vec3() + Vec3::ONE);
+        let centre = (min + max) * 0.5;
+        let radius = (max - min) * 0.5;
+        (position.as_vec3() + Vec3::splat(0.5) - centre) / radius
+    }
+}
+
+/// edits over a whole region at once. each returns the changes made, for `EditHistory::record_all`.
+impl World {
+    /// sets every tile in a region.
+    pub fn fill(&mut self, region: Region, tile: Tile) -> Vec<TileChange> {
+        self.fill_where(region, tile, |_| true)
+    }
+
+    /// swaps one kind of tile for another within a region.
+    pub fn replace(&mut self, region: Region, from: Tile, to: Tile) -> Vec<TileChange> {
+        let Some(inside) = region.intersection(self.bounds()) else {
+            return Vec::new();
+        };
+        let tiles: Vec<_> = inside
+            .positions()
+            .filter_map(to_position)
+            .filter(|(x, y, z)| self.get(*x, *y, *z) == Some(from))
+            .map(|position| (position, to))
+            .collect();
+        self.set_many(tiles)
+    }
+
+    /// sets the tiles on the outside of a region, leaving the inside alone.
+    pub fn fill_hollow(&mut self, region: Region, tile: Tile) -> Vec<TileChange> {
+        self.fill_where(region, tile, |position| {
+            position.cmpeq(region.min).any() || position.cmpeq(region.max).any()
+        })
+    }
+
+    /// fills the largest sphere, or ellipsoid, which fits in a region.
+    pub fn fill_sphere(&mut self, region: Region, tile: Tile) -> Vec<TileChange> {
+        self.fill_where(region, tile, |position| {
+            region.normalised(position).length_squared() <= 1.0
+        })
+    }
+
+    /// fills the largest cylinder which fits in a region, running along an axis.
+    pub fn fill_cylinder(&mut self, region: Region, axis: Axis, tile: Tile) -> Vec<TileChange> {
+        self.fill_where(region, tile, |position| {
+            let mut offset = region.normalised(position);
+            offset[axis.index()] = 0.0;
+            offset.length_squared() <= 1.0
+        })
+    }
+
+    fn fill_where(
+        &mut self,
+        region: Region,
+        tile: Tile,
+        predicate: impl Fn(IVec3) -> bool,
+    ) -> Vec<TileChange> {
+        // only the part inside the world can change, but shapes are still sized to all of it.
+        let Some(inside) = region.intersection(self.bounds()) else {
+            return Vec::new();
+        };
+        let tiles: Vec<_> = inside
+            .positions()
+            .filter(|position| predicate(*position))
+            .filter_map(to_position)
+            .map(|position| (position, tile))
+            .collect();
+        self.set_many(tiles)
+    }
+
+    /// returns the region covering the whole world.
+    pub fn bounds(&self) -> Region {
+        Region::new(
+            IVec3::ZERO,
+            IVec3::new(
+                self.size_x() as i32,
+                self.size_y() as i32,
+                self.size_z() as i32,
+            ) - IVec3::ONE,
+        )
+    }
+}
+
+/// converts a position to the coordinates `World` takes, if it isn't below zero.
+pub(super) fn to_position(position: IVec3) -> Option<(usize, usize, usize)> {
+    (position.min_element() >= 0).then_some((
+        position.x as usize,
+        position.y as usize,
+        position.z as usize,
+    ))
+}
+
+/// adds two positions, or returns nothing if any axis overflows, e.g. when pasting near the edge
+/// of what an `i32` can hold.
+pub(super) fn checked_add(a: IVec3, b: IVec3) -> Option<IVec3> {
+    Some(IVec3::new(
+        a.x.checked_add(b.x)?,
+        a.y.checked_add(b.y)?,
+        a.z.checked_add(b.z)?,
+    ))
+}
+
+/// subtracts one position from another, or returns nothing if any axis overflows.
+pub(super) fn checked_sub(a: IVec3, b: IVec3) -> Option<IVec3> {
+    Some(IVec3::new(
+        a.x.checked_sub(b.x)?,
+        a.y.checked_sub(b.y)?,
+        a.z.checked_sub(b.z)?,
+    ))
+}
@@ -0,0 +1,174 @@
+//! the `.arcs` schematic format, for saving clipboards.
+//!
+//! schematics are laid out like `.arc` world files: a header of the magic bytes `ARCS` and a `u16`
+//! major and minor version, followed by sections until the end of the file, each being a four
+//! byte tag, a `u32` length and then that many bytes of payload:
+//!
+//! * `SIZE` : the clipboard's dimensions, as three `u32`s.
+//! * `PALT` : the same as in a world file.
+//! * `TILE` : the same as in a world file.
+//!
+//! readers skip sections they don't recognise, so new sections can be added in a minor version.
+
+use super::worldfile::{read_tiles, write_section, write_tiles};
+use super::{Clipboard, World};
+use crate::error::ErrorType;
+use crate::reader::{corrupt, Reader};
+use std::{fs, path::Path};
+
+const MAGIC: &[u8; 4] = b"ARCS";
+const VERSION_MAJOR: u16 = 1;
+const VERSION_MINOR: u16 = 0;
+
+impl Clipboard {
+    /// saves the clipboard to a file in the `.arcs` format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ErrorType> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// loads a clipboard from a file in the `.arcs` format.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Clipboard, ErrorType> {
+        Clipboard::from_bytes(&fs::read(path)?)
+    }
+
+    /// encodes the clipboard in the `.arcs` format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION_MAJOR.to_le_bytes());
+        bytes.extend_from_slice(&VERSION_MINOR.to_le_bytes());
+
+        let (size_x, size_y, size_z) = self.size();
+        let mut size = Vec::new();
+        for dimension in [size_x, size_y, size_z] {
+            size.extend_from_slice(&(dimension as u32).to_le_bytes());
+        }
+        write_section(&mut bytes, b"SIZE", &size);
+        write_tiles(&mut bytes, self.tiles());
+
+        bytes
+    }
+
+    /// decodes a clipboard in the `.arcs` format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Clipboard, ErrorType> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(4)? != MAGIC {
+            return Err(corrupt("not a schematic file"));
+        }
+        let major = reader.u16()?;
+        let minor = reader.u16()?;
+        if major > VERSION_MAJOR {
+            return Err(ErrorType::UnsupportedVersion(major, minor));
+        }
+
+        let mut clipboard: Option<Clipboard> = None;
+        let mut palette = None;
+        let mut tiles_read = false;
+
+        while !reader.is_empty() {
+            let tag = reader.take(4)?;
+            let length = reader.u32()? as usize;
+            let mut section = Reader::new(reader.take(length)?);
+
+            match tag {
+                b"SIZE" => {
+                    if clipboard.is_some() {
+                        return Err(corrupt("duplicate size section"));
+                    }
+                    let size_x = section.u32()? as usize;
+                    let size_y = section.u32()? as usize;
+                    let size_z = section.u32()? as usize;
+                    // no bigger than a world, to avoid huge allocations from corrupt files.
+                    if !World::is_valid_size(size_x, size_y, size_z) {
+                        return Err(corrupt("schematic dimensions are out of range"));
+                    }
+                    clipboard = Some(Clipboard::new(size_x, size_y, size_z));
+                }
+                b"PALT" => {
+                    let count = section.u16()? as usize;
+                    palette = Some(section.take(count)?.to_vec());
+                }
+                b"TILE" => {
+                    let clipboard = clipboard
+                        .as_mut()
+                        .ok_or_else(|| corrupt("tiles before size"))?;
+                    let palette = palette
+                        .as_ref()
+                        .ok_or_else(|| corrupt("tiles before palette"))?;
+                    read_tiles(&mut section, palette, clipboard.tiles_mut())?;
+                    tiles_read = true;
+                }
+                _ => {} // unknown sections are from newer minor versions, and safe to skip.
+            }
+        }
+
+        let clipboard = clipboard.ok_or_else(|| corrupt("missing size section"))?;
+        if !tiles_read {
+            return Err(corrupt("missing tile section"));
+        }
+
+        Ok(clipboard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::testing::{assert_truncations_are_corrupt, is_corrupt};
+    use crate::world::tiles;
+
+    fn sample_clipboard() -> Clipboard {
+        let mut clipboard = Clipboard::new(3, 2, 4);
+        clipboard.set(0, 0, 0, tiles::STONE);
+        clipboard.set(2, 1, 3, tiles::GRASS);
+        clipboard
+    }
+
+    #[test]
+    fn round_trips() {
+        let clipboard = sample_clipboard();
+        assert_eq!(
+            Clipboard::from_bytes(&clipboard.to_bytes()).unwrap(),
+            clipboard
+        );
+    }
+
+    #[test]
+    fn truncated_files_are_corrupt() {
+        let bytes = sample_clipboard().to_bytes();
+        assert_truncations_are_corrupt(&bytes, &[], Clipboard::from_bytes);
+    }
+
+    #[test]
+    fn corrupt_files_are_rejected() {
+        let mut bytes = sample_clipboard().to_bytes();
+        bytes[0] = b'X';
+        assert!(is_corrupt(Clipboard::from_bytes(&bytes)));
+
+        let mut bytes = sample_clipboard().to_bytes();
+        let size = bytes.windows(4).position(|tag| tag == b"SIZE").unwrap();
+        bytes[size + 8..size + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(is_corrupt(Clipboard::from_bytes(&bytes)));
+
+        let mut bytes = sample_clipboard().to_bytes();
+        let size = bytes.windows(4).position(|tag| tag == b"SIZE").unwrap();
+        let section = bytes[size..size + 20].to_vec();
+        bytes.extend_from_slice(&section);
+        assert!(is_corrupt(Clipboard::from_bytes(&bytes)));
+    }
+
+    #[test]
+    fn newer_major_versions_are_unsupported() {
+        let mut bytes = sample_clipboard().to_bytes();
+        bytes[4..6].copy_from_slice(&(VERSION_MAJOR + 1).to_le_bytes());
+        assert_eq!(
+            Clipboard::from_bytes(&bytes).err(),
+            Some(ErrorType::UnsupportedVersion(
+                VERSION_MAJOR + 1,
+                VERSION_MINOR
+            ))
+        );
+    }
+}
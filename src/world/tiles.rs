@@ -0,0 +1,139 @@
+//! ids of the tiles the engine knows about.
+//!
+//! tiles are plain bytes so worlds stay small; zero is always empty space.
+
+use super::Tile;
+use glam::Vec2;
+
+pub const AIR: Tile = 0;
+pub const STONE: Tile = 1;
+pub const DIRT: Tile = 2;
+pub const GRASS: Tile = 3;
+pub const SAND: Tile = 4;
+pub const SNOW: Tile = 5;
+pub const WOOD: Tile = 6;
+pub const LEAVES: Tile = 7;
+pub const WATER: Tile = 8;
+pub const COAL_ORE: Tile = 9;
+pub const IRON_ORE: Tile = 10;
+pub const GOLD_ORE: Tile = 11;
+pub const GRAVEL: Tile = 12;
+pub const LAMP: Tile = 13;
+pub const GLASS: Tile = 14;
+pub const POLISHED_STONE: Tile = 15;
+
+/// every tile, in id order.
+pub const ALL: [Tile; 16] = [
+    AIR,
+    STONE,
+    DIRT,
+    GRASS,
+    SAND,
+    SNOW,
+    WOOD,
+    LEAVES,
+    WATER,
+    COAL_ORE,
+    IRON_ORE,
+    GOLD_ORE,
+    GRAVEL,
+    LAMP,
+    GLASS,
+    POLISHED_STONE,
+];
+
+/// returns the tile's name, as shown in the editor.
+pub fn name(tile: Tile) -> &'static str {
+    match tile {
+        AIR => "air",
+        STONE => "stone",
+        DIRT => "dirt",
+        GRASS => "grass",
+        SAND => "sand",
+        SNOW => "snow",
+        WOOD => "wood",
+        LEAVES => "leaves",
+        WATER => "water",
+        COAL_ORE => "coal ore",
+        IRON_ORE => "iron ore",
+        GOLD_ORE => "gold ore",
+        GRAVEL => "gravel",
+        LAMP => "lamp",
+        GLASS => "glass",
+        POLISHED_STONE => "polished stone",
+        _ => "unknown",
+    }
+}
+
+/// returns true if the tile blocks light.
+pub fn is_opaque(tile: Tile) -> bool {
+    !matches!(tile, AIR | LEAVES | WATER | GLASS)
+}
+
+/// returns true if bodies collide with the tile rather than passing through it.
+pub fn is_solid(tile: Tile) -> bool {
+    !matches!(tile, AIR | WATER)
+}
+
+/// returns how much of what's behind the tile it hides, from 0.0 for invisible to 1.0 for solid.
+pub fn opacity(tile: Tile) -> f32 {
+    match tile {
+        AIR => 0.0,
+        GLASS => 0.2,
+        WATER => 0.5,
+        _ => 1.0,
+    }
+}
+
+/// returns how much of the tile's colour is replaced by what it reflects, from 0.0 to 1.0.
+pub fn reflectivity(tile: Tile) -> f32 {
+    match tile {
+        WATER => 0.3,
+        GLASS => 0.15,
+        POLISHED_STONE => 0.4,
+        _ => 0.0,
+    }
+}
+
+/// returns true if the tile has a hole at the given point on its face, for alpha-cutout tiles.
+pub fn is_cutout(tile: Tile, uv: Vec2) -> bool {
+    match tile {
+        LEAVES => {
+            // a scattered pattern of holes, on an 8x8 grid of texels.
+            let x = (uv.x * 8.0) as u32 & 7;
+            let y = (uv.y * 8.0) as u32 & 7;
+            (x * 5 + y * 3 + (x * y) % 4).is_multiple_of(5)
+        }
+        _ => false,
+    }
+}
+
+/// returns how brightly the tile glows, from 0 to 15.
+pub fn emission(tile: Tile) -> u8 {
+    match tile {
+        LAMP => 15,
+        _ => 0,
+    }
+}
+
+/// returns the base colour of the tile, before any lighting.
+pub fn colour(tile: Tile) -> (u8, u8, u8) {
+    match tile {
+        STONE => (128, 128, 128),
+        DIRT => (134, 96, 67),
+        GRASS => (96, 160, 64),
+        SAND => (218, 206, 150),
+        SNOW => (240, 244, 250),
+        WOOD => (104, 82, 50),
+        LEAVES => (56, 120, 40),
+        WATER => (48, 96, 200),
+        COAL_ORE => (64, 64, 64),
+        IRON_ORE => (176, 140, 120),
+        GOLD_ORE => (230, 200, 60),
+        GRAVEL => (136, 126, 126),
+        LAMP => (255, 230, 160),
+        GLASS => (200, 230, 240),
+        POLISHED_STONE => (170, 170, 176),
+        _ => (255, 0, 255),
+    }
+}
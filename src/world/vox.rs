@@ -0,0 +1,577 @@
+//! import and export of magicavoxel `.vox` files.
+//!
+//! magicavoxel is z-up while the world is y-up, so a voxel at `(x, y, z)` in a `.vox` file is the
+//! tile at `(x, z, y)` in the world. palette indices map to tiles through a table, which by default
+//! is the identity since both palette indices and tiles are a byte with zero meaning empty.
+
+use super::region::{checked_add, checked_sub};
+use super::{Tile, World};
+use crate::error::ErrorType;
+use crate::reader::{corrupt, Reader};
+use glam::IVec3;
+use std::{collections::HashMap, fs, path::Path};
+
+const MAGIC: &[u8; 4] = b"VOX ";
+const VERSION: u32 = 150;
+/// the largest model magicavoxel can open, along each axis.
+const MAX_MODEL_SIZE: usize = 256;
+/// the largest scene that will be imported, to avoid huge allocations from far-apart models.
+const MAX_SCENE_VOLUME: i64 = 1 << 28;
+
+/// a single model from a `.vox` file.
+#[derive(Clone)]
+pub struct VoxModel {
+    /// the model's size, in magicavoxel's z-up coordinates.
+    pub size: (usize, usize, usize),
+    /// every filled voxel, as x, y, z and a palette index.
+    pub voxels: Vec<[u8; 4]>,
+}
+
+/// a model placed in the scene, with translations from the scene graph already applied.
+#[derive(Clone)]
+pub struct VoxInstance {
+    pub model: usize,
+    /// where the model's minimum corner is, in magicavoxel's z-up coordinates.
+    pub position: IVec3,
+}
+
+/// the contents of a `.vox` file.
+pub struct VoxScene {
+    pub models: Vec<VoxModel>,
+    pub instances: Vec<VoxInstance>,
+    /// rgba colours for palette indices 1 to 255; index 0 is always empty. files without a palette
+    /// get a grey ramp.
+    pub palette: [[u8; 4]; 256],
+}
+
+/// returns a tile map where each palette index becomes the tile with the same id.
+pub fn identity_tile_map() -> [Tile; 256] {
+    let mut map = [0; 256];
+    for (index, tile) in map.iter_mut().enumerate() {
+        *tile = index as Tile;
+    }
+    map
+}
+
+enum Node {
+    Transform { child: i32, translation: IVec3 },
+    Group { children: Vec<i32> },
+    Shape { models: Vec<usize> },
+}
+
+impl VoxScene {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<VoxScene, ErrorType> {
+        VoxScene::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<VoxScene, ErrorType> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(4)? != MAGIC {
+            return Err(corrupt("not a .vox file"));
+        }
+        let _version = reader.u32()?;
+
+        let (id, _, children) = read_chunk(&mut reader)?;
+        if id != b"MAIN" {
+            return Err(corrupt("missing MAIN chunk"));
+        }
+
+        let mut models = Vec::new();
+        let mut size = None;
+        let mut palette = default_palette();
+        let mut nodes = HashMap::new();
+
+        let mut reader = Reader::new(children);
+        while !reader.is_empty() {
+            let (id, content, _) = read_chunk(&mut reader)?;
+            let mut content = Reader::new(content);
+
+            match id {
+                b"SIZE" => {
+                    let x = content.u32()? as usize;
+                    let y = content.u32()? as usize;
+                    let z = content.u32()? as usize;
+                    size = Some((x, y, z));
+                }
+                b"XYZI" => {
+                    let size = size.take().ok_or_else(|| corrupt("XYZI without SIZE"))?;
+                    let count = content.u32()? as usize;
+                    let mut voxels = Vec::with_capacity(count.min(content.remaining() / 4));
+                    for _ in 0..count {
+                        let voxel = content.take(4)?;
+                        voxels.push([voxel[0], voxel[1], voxel[2], voxel[3]]);
+                    }
+                    models.push(VoxModel { size, voxels });
+                }
+                b"RGBA" => {
+                    // the chunk stores colours for indices 1 to 255, then one unused entry.
+                    for entry in palette.iter_mut().skip(1) {
+                        let colour = content.take(4)?;
+                        *entry = [colour[0], colour[1], colour[2], colour[3]];
+                    }
+                }
+                b"nTRN" => {
+                    let id = content.i32()?;
+                    read_dict(&mut content)?;
+                    let child = content.i32()?;
+                    let _reserved = content.i32()?;
+                    let _layer = content.i32()?;
+                    let frames = content.i32()?;
+
+                    let mut translation = IVec3::ZERO;
+                    for frame in 0..frames {
+                        let attributes = read_dict(&mut content)?;
+                        if frame == 0 {
+                            if let Some(t) = attributes.get("_t") {
+                                translation = parse_translation(t)?;
+                            }
+                        }
+                    }
+                    nodes.insert(id, Node::Transform { child, translation });
+                }
+                b"nGRP" => {
+                    let id = content.i32()?;
+                    read_dict(&mut content)?;
+                    let count = content.i32()?;
+                    let mut children = Vec::new();
+                    for _ in 0..count {
+                        children.push(content.i32()?);
+                    }
+                    nodes.insert(id, Node::Group { children });
+                }
+                b"nSHP" => {
+                    let id = content.i32()?;
+                    read_dict(&mut content)?;
+                    let count = content.i32()?;
+                    let mut shape_models = Vec::new();
+                    for _ in 0..count {
+                        shape_models.push(content.i32()?.max(0) as usize);
+                        read_dict(&mut content)?;
+                    }
+                    nodes.insert(
+                        id,
+                        Node::Shape {
+                            models: shape_models,
+                        },
+                    );
+                }
+                _ => {} // PACK, materials, layers, cameras etc. aren't needed.
+            }
+        }
+
+        let mut instances = Vec::new();
+        if nodes.contains_key(&0) {
+            collect_instances(&nodes, &models, 0, IVec3::ZERO, 0, &mut instances)?;
+        } else {
+            // files from before the scene graph just place every model at the origin.
+            for model in 0..models.len() {
+                instances.push(VoxInstance {
+                    model,
+                    position: IVec3::ZERO,
+                });
+            }
+        }
+
+        Ok(VoxScene {
+            models,
+            instances,
+            palette,
+        })
+    }
+
+    /// builds a world just big enough to hold every instance, mapping palette indices through `tile_map`.
+    pub fn to_world(&self, tile_map: &[Tile; 256]) -> Result<World, ErrorType> {
+        if self.instances.is_empty() {
+            return Ok(World::with_size(1, 1, 1));
+        }
+
+        let mut min = IVec3::splat(i32::MAX);
+        let mut max = IVec3::splat(i32::MIN);
+        for instance in &self.instances {
+            let (x, y, z) = self.models[instance.model].size;
+            let end = checked_add(instance.position, IVec3::new(x as i32, y as i32, z as i32))
+                .ok_or_else(|| corrupt("translation out of range"))?;
+            min = min.min(instance.position);
+            max = max.max(end);
+        }
+
+        let size = checked_sub(max, min)
+            .ok_or_else(|| corrupt("translation out of range"))?
+            .max(IVec3::ONE);
+        if size.x as i64 * size.y as i64 * size.z as i64 > MAX_SCENE_VOLUME {
+            return Err(ErrorType::String(
+                "the .vox scene is too big to fit in a world".to_string(),
+            ));
+        }
+
+        let mut world = World::with_size(size.x as usize, size.z as usize, size.y as usize);
+        for instance in &self.instances {
+            let offset = instance.position - min;
+            for [x, y, z, index] in &self.models[instance.model].voxels {
+                let x = (offset.x + *x as i32) as usize;
+                let y = (offset.y + *y as i32) as usize;
+                let z = (offset.z + *z as i32) as usize;
+                if world.in_bounds(x, z, y) {
+                    world.set_unlit(x, z, y, tile_map[*index as usize]);
+                }
+            }
+        }
+        world.relight();
+
+        Ok(world)
+    }
+}
+
+impl World {
+    /// imports every model in a `.vox` file, with each palette index becoming the tile of the same id.
+    pub fn load_vox<P: AsRef<Path>>(path: P) -> Result<World, ErrorType> {
+        VoxScene::load(path)?.to_world(&identity_tile_map())
+    }
+
+    /// exports the whole world as a single-model `.vox` file.
+    pub fn save_vox<P: AsRef<Path>>(
+        &self,
+        path: P,
+        palette: Option<&[[u8; 4]; 256]>,
+    ) -> Result<(), ErrorType> {
+        let max = (self.size_x(), self.size_y(), self.size_z());
+        fs::write(path, self.region_to_vox((0, 0, 0), max, palette)?)?;
+        Ok(())
+    }
+
+    /// encodes the tiles from `min` up to but not including `max` as a single-model `.vox` file.
+    ///
+    /// tiles become palette indices of the same value. without a palette no `RGBA` chunk is written,
+    /// so magicavoxel shows the file with its own default palette.
+    pub fn region_to_vox(
+        &self,
+        min: (usize, usize, usize),
+        max: (usize, usize, usize),
+        palette: Option<&[[u8; 4]; 256]>,
+    ) -> Result<Vec<u8>, ErrorType> {
+        let max = (
+            max.0.min(self.size_x()),
+            max.1.min(self.size_y()),
+            max.2.min(self.size_z()),
+        );
+        let size = (
+            max.0.saturating_sub(min.0),
+            max.1.saturating_sub(min.1),
+            max.2.saturating_sub(min.2),
+        );
+        if size.0 == 0 || size.1 == 0 || size.2 == 0 {
+            return Err(ErrorType::String(
+                "the region to export is empty".to_string(),
+            ));
+        }
+        if size.0 > MAX_MODEL_SIZE || size.1 > MAX_MODEL_SIZE || size.2 > MAX_MODEL_SIZE {
+            return Err(ErrorType::String(format!(
+                ".vox models can't be bigger than {} along any axis",
+                MAX_MODEL_SIZE
+            )));
+        }
+
+        let mut voxels = Vec::new();
+        for z in min.2..max.2 {
+            for y in min.1..max.1 {
+                for x in min.0..max.0 {
+                    let tile = self.get(x, y, z).unwrap_or(0);
+                    if tile != 0 {
+                        // swap from the world's y-up to magicavoxel's z-up.
+                        voxels.extend_from_slice(&[
+                            (x - min.0) as u8,
+                            (z - min.2) as u8,
+                            (y - min.1) as u8,
+                            tile,
+                        ]);
+                    }
+                }
+            }
+        }
+
+        let mut children = Vec::new();
+
+        let mut size_chunk = Vec::new();
+        for dimension in [size.0, size.2, size.1] {
+            size_chunk.extend_from_slice(&(dimension as u32).to_le_bytes());
+        }
+        write_chunk(&mut children, b"SIZE", &size_chunk, &[]);
+
+        let mut xyzi = ((voxels.len() / 4) as u32).to_le_bytes().to_vec();
+        xyzi.extend_from_slice(&voxels);
+        write_chunk(&mut children, b"XYZI", &xyzi, &[]);
+
+        if let Some(palette) = palette {
+            let mut rgba = Vec::with_capacity(256 * 4);
+            for colour in palette[1..].iter().chain(std::iter::once(&[0; 4])) {
+                rgba.extend_from_slice(colour);
+            }
+            write_chunk(&mut children, b"RGBA", &rgba, &[]);
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        write_chunk(&mut bytes, b"MAIN", &[], &children);
+
+        Ok(bytes)
+    }
+}
+
+/// walks the scene graph from `id`, accumulating translations down to shape nodes.
+fn collect_instances(
+    nodes: &HashMap<i32, Node>,
+    models: &[VoxModel],
+    id: i32,
+    translation: IVec3,
+    depth: usize,
+    instances: &mut Vec<VoxInstance>,
+) -> Result<(), ErrorType> {
+    if depth > 64 {
+        return Err(corrupt("scene graph is too deep, or has a cycle"));
+    }
+
+    match nodes.get(&id) {
+        Some(Node::Transform {
+            child,
+            translation: local,
+        }) => {
+            let translation = checked_add(translation, *local)
+                .ok_or_else(|| corrupt("translation out of range"))?;
+            collect_instances(nodes, models, *child, translation, depth + 1, instances)?
+        }
+        Some(Node::Group { children }) => {
+            for child in children {
+                collect_instances(nodes, models, *child, translation, depth + 1, instances)?;
+            }
+        }
+        Some(Node::Shape {
+            models: shape_models,
+        }) => {
+            for model in shape_models {
+                let (x, y, z) = models
+                    .get(*model)
+                    .ok_or_else(|| corrupt("shape refers to a model that doesn't exist"))?
+                    .size;
+
+                // translations are to the centre of the model, rounding down.
+                let half = IVec3::new(x as i32 / 2, y as i32 / 2, z as i32 / 2);
+                instances.push(VoxInstance {
+                    model: *model,
+                    position: checked_sub(translation, half)
+                        .ok_or_else(|| corrupt("translation out of range"))?,
+                });
+            }
+        }
+        None => return Err(corrupt("scene graph refers to a node that doesn't exist")),
+    }
+
+    Ok(())
+}
+
+/// a chunk's id, content and children.
+type Chunk<'a> = (&'a [u8], &'a [u8], &'a [u8]);
+
+fn read_chunk<'a>(reader: &mut Reader<'a>) -> Result<Chunk<'a>, ErrorType> {
+    let id = reader.take(4)?;
+    let content_size = reader.u32()? as usize;
+    let children_size = reader.u32()? as usize;
+    let content = reader.take(content_size)?;
+    let children = reader.take(children_size)?;
+    Ok((id, content, children))
+}
+
+fn write_chunk(bytes: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    bytes.extend_from_slice(id);
+    bytes.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(content);
+    bytes.extend_from_slice(children);
+}
+
+fn read_dict(reader: &mut Reader) -> Result<HashMap<String, String>, ErrorType> {
+    let count = reader.i32()?;
+    let mut dict = HashMap::new();
+    for _ in 0..count {
+        let key = read_vox_string(reader)?;
+        let value = read_vox_string(reader)?;
+        dict.insert(key, value);
+    }
+    Ok(dict)
+}
+
+fn read_vox_string(reader: &mut Reader) -> Result<String, ErrorType> {
+    let length = reader.u32()? as usize;
+    String::from_utf8(reader.take(length)?.to_vec()).map_err(|_| corrupt("string is not utf-8"))
+}
+
+fn parse_translation(text: &str) -> Result<IVec3, ErrorType> {
+    let parts: Vec<i32> = text
+        .split_whitespace()
+        .map(|part| {
+            part.parse()
+                .map_err(|_| corrupt("translation is not three integers"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    match parts.as_slice() {
+        [x, y, z] => Ok(IVec3::new(*x, *y, *z)),
+        _ => Err(corrupt("translation is not three integers")),
+    }
+}
+
+/// a grey ramp, used for files without an RGBA chunk so that every index still has a colour.
+///
+/// this isn't magicavoxel's default palette, so such files import with different colours than
+/// magicavoxel shows, though the tiles are the same.
+fn default_palette() -> [[u8; 4]; 256] {
+    let mut palette = [[0; 4]; 256];
+    for (index, colour) in palette.iter_mut().enumerate().skip(1) {
+        let value = index as u8;
+        *colour = [value, value, value, 255];
+    }
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::testing::{assert_truncations_are_corrupt, is_corrupt};
+
+    fn write_dict(bytes: &mut Vec<u8>, pairs: &[(&str, &str)]) {
+        bytes.extend_from_slice(&(pairs.len() as i32).to_le_bytes());
+        for string in pairs.iter().flat_map(|(key, value)| [key, value]) {
+            bytes.extend_from_slice(&(string.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(string.as_bytes());
+        }
+    }
+
+    fn model(children: &mut Vec<u8>, size: [u32; 3], voxels: &[[u8; 4]]) {
+        let size: Vec<u8> = size.iter().flat_map(|d| d.to_le_bytes()).collect();
+        write_chunk(children, b"SIZE", &size, &[]);
+        let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+        xyzi.extend(voxels.iter().flatten());
+        write_chunk(children, b"XYZI", &xyzi, &[]);
+    }
+
+    fn transform(children: &mut Vec<u8>, id: i32, child: i32, translation: &str) {
+        let mut content = id.to_le_bytes().to_vec();
+        write_dict(&mut content, &[]);
+        for value in [child, -1, 0, 1] {
+            content.extend_from_slice(&value.to_le_bytes());
+        }
+        write_dict(&mut content, &[("_t", translation)]);
+        write_chunk(children, b"nTRN", &content, &[]);
+    }
+
+    fn group(children: &mut Vec<u8>, id: i32, nodes: &[i32]) {
+        let mut content = id.to_le_bytes().to_vec();
+        write_dict(&mut content, &[]);
+        content.extend_from_slice(&(nodes.len() as i32).to_le_bytes());
+        for node in nodes {
+            content.extend_from_slice(&node.to_le_bytes());
+        }
+        write_chunk(children, b"nGRP", &content, &[]);
+    }
+
+    fn shape(children: &mut Vec<u8>, id: i32, model: i32) {
+        let mut content = id.to_le_bytes().to_vec();
+        write_dict(&mut content, &[]);
+        content.extend_from_slice(&1i32.to_le_bytes());
+        content.extend_from_slice(&model.to_le_bytes());
+        write_dict(&mut content, &[]);
+        write_chunk(children, b"nSHP", &content, &[]);
+    }
+
+    fn file(children: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        write_chunk(&mut bytes, b"MAIN", &[], children);
+        bytes
+    }
+
+    /// two models, each moved by its own transform under a group.
+    fn scene(second_translation: &str) -> Vec<u8> {
+        let mut children = Vec::new();
+        model(&mut children, [2, 2, 2], &[[0, 0, 0, 1], [1, 1, 1, 2]]);
+        model(
+            &mut children,
+            [1, 1, 3],
+            &[[0, 0, 0, 3], [0, 0, 1, 3], [0, 0, 2, 3]],
+        );
+        transform(&mut children, 0, 1, "0 0 0");
+        group(&mut children, 1, &[2, 4]);
+        transform(&mut children, 2, 3, "0 0 0");
+        shape(&mut children, 3, 0);
+        transform(&mut children, 4, 5, second_translation);
+        shape(&mut children, 5, 1);
+        file(&children)
+    }
+
+    fn import(bytes: &[u8]) -> Result<World, ErrorType> {
+        VoxScene::from_bytes(bytes)?.to_world(&identity_tile_map())
+    }
+
+    #[test]
+    fn imports_models_where_their_transforms_put_them() {
+        let world = import(&scene("5 -3 1")).unwrap();
+
+        // the scene spans -1 to 6 along x, -3 to 1 along y and -1 to 3 along z, with z up.
+        assert_eq!((world.size_x(), world.size_y(), world.size_z()), (7, 4, 4));
+        assert_eq!(world.get(0, 0, 2), Some(1));
+        assert_eq!(world.get(1, 1, 3), Some(2));
+        for y in 1..4 {
+            assert_eq!(world.get(6, y, 0), Some(3));
+        }
+        assert_eq!(world.tiles().iter().filter(|tile| **tile != 0).count(), 5);
+    }
+
+    #[test]
+    fn round_trips() {
+        let world = import(&scene("5 -3 1")).unwrap();
+        let size = (world.size_x(), world.size_y(), world.size_z());
+        let bytes = world.region_to_vox((0, 0, 0), size, None).unwrap();
+        let loaded = import(&bytes).unwrap();
+
+        assert_eq!((loaded.size_x(), loaded.size_y(), loaded.size_z()), size);
+        assert_eq!(loaded.tiles(), world.tiles());
+    }
+
+    #[test]
+    fn truncated_files_are_corrupt() {
+        let bytes = scene("5 -3 1");
+        assert_truncations_are_corrupt(&bytes, &[], import);
+    }
+
+    #[test]
+    fn corrupt_files_are_rejected() {
+        let mut bytes = scene("5 -3 1");
+        bytes[0] = b'X';
+        assert!(is_corrupt(import(&bytes)));
+
+        assert!(is_corrupt(import(&scene("5 -3"))));
+
+        let mut children = Vec::new();
+        model(&mut children, [1, 1, 1], &[[0, 0, 0, 1]]);
+        transform(&mut children, 0, 1, "0 0 0");
+        shape(&mut children, 1, 7);
+        assert!(is_corrupt(import(&file(&children))));
+
+        let mut children = Vec::new();
+        transform(&mut children, 0, 0, "0 0 0");
+        assert!(is_corrupt(import(&file(&children))));
+    }
+
+    #[test]
+    fn translations_out_of_range_are_corrupt() {
+        assert!(is_corrupt(import(&scene("2147483647 0 0"))));
+        assert!(is_corrupt(import(&scene("-2147483648 0 0"))));
+
+        // each translation fits, but not both together.
+        let mut children = Vec::new();
+        model(&mut children, [1, 1, 1], &[[0, 0, 0, 1]]);
+        transform(&mut children, 0, 1, "2000000000 0 0");
+        transform(&mut children, 1, 2, "2000000000 0 0");
+        shape(&mut children, 2, 0);
+        assert!(is_corrupt(import(&file(&children))));
+    }
+}
@@ -1,4 +1,7 @@
+use super::history::TileChange;
+use super::lighting::{LightMap, MAX_LIGHT};
 use glam::{IVec3, Vec2, Vec3};
+use std::collections::BTreeMap;
 
 pub type Tile = u8;
 
@@ -6,28 +9,77 @@ const SIZE_X: usize = 16;
 const SIZE_Y: usize = 16;
 const SIZE_Z: usize = 16;
 
+/// edits changing more tiles than this skip updating the light around each tile and relight the
+/// whole world once instead, which is quicker.
+const RELIGHT_THRESHOLD: usize = 4096;
+
+/// the most tiles a world can have, so sizes typed in or read from a file can't ask for more memory
+/// than is sensible.
+pub const MAX_VOLUME: usize = 1 << 28;
+
 pub struct RaycastResult {
     pub tile: Tile,
     pub uv: Vec2,
     pub normal: Vec3,
+    /// the tile that was hit.
+    pub position: IVec3,
+    /// how far along the ray the hit face is.
+    pub distance: f32,
+}
+
+impl RaycastResult {
+    /// returns the tile in front of the face that was hit, where a tile placed against it goes.
+    pub fn adjacent(&self) -> IVec3 {
+        self.position + self.normal.as_ivec3()
+    }
 }
 
+#[derive(Clone)]
 pub struct World {
     size: (usize, usize, usize),
     tiles: Vec<Tile>,
+    light: LightMap,
+    metadata: BTreeMap<String, String>,
 }
 
 impl World {
     pub fn new() -> Self {
-        let size = (SIZE_X, SIZE_Y, SIZE_Z);
+        Self::with_size(SIZE_X, SIZE_Y, SIZE_Z)
+    }
+
+    /// returns whether a world can be made with the given dimensions, which it can't if any of them
+    /// is zero or there would be more than `MAX_VOLUME` tiles.
+    pub fn is_valid_size(size_x: usize, size_y: usize, size_z: usize) -> bool {
+        size_x
+            .checked_mul(size_y)
+            .and_then(|area| area.checked_mul(size_z))
+            .is_some_and(|volume| volume > 0 && volume <= MAX_VOLUME)
+    }
+
+    /// creates an empty world of the given dimensions.
+    pub fn with_size(size_x: usize, size_y: usize, size_z: usize) -> Self {
+        let size = (size_x, size_y, size_z);
         let mut tiles = Vec::new();
-        tiles.resize(SIZE_X * SIZE_Y * SIZE_Z, 0);
+        tiles.resize(size_x * size_y * size_z, 0);
 
-        Self { size, tiles }
+        let mut world = Self {
+            size,
+            tiles,
+            light: LightMap::new(size),
+            metadata: BTreeMap::new(),
+        };
+        world.relight();
+        world
     }
 
+    /// returns the first tile a ray hits, if any.
     pub fn raycast(&self, pos: Vec3, dir: Vec3, distance: usize) -> Option<RaycastResult> {
-        let mut t_pos = IVec3::new(
+        self.raycast_all(pos, dir, distance).next()
+    }
+
+    /// returns every tile a ray passes through, nearest first, for seeing through transparent tiles.
+    pub fn raycast_all(&self, pos: Vec3, dir: Vec3, distance: usize) -> Raycast<'_> {
+        let t_pos = IVec3::new(
             pos.x.floor() as i32,
             pos.y.floor() as i32,
             pos.z.floor() as i32,
@@ -58,39 +110,17 @@ impl World {
             t_max.z = (t_pos.z as f32 + 1.0 - pos.z) * t_delta.z;
         }
 
-        let mut steps_taken = 0;
-        loop {
-            steps_taken += 1;
-
-            if t_max.x < t_max.y || t_max.z < t_max.y {
-                if t_max.x < t_max.z {
-                    t_max.x += t_delta.x;
-                    t_pos.x += t_step.x;
-                } else {
-                    t_max.z += t_delta.z;
-                    t_pos.z += t_step.z;
-                }
-            } else {
-                t_max.y += t_delta.y;
-                t_pos.y += t_step.y;
-            }
-
-            if let Some(tile) = self.get(t_pos.x as usize, t_pos.y as usize, t_pos.z as usize) {
-                if tile != 0 {
-                    return Some(RaycastResult {
-                        tile,
-                        uv: Vec2::ONE,
-                        normal: Vec3::ONE,
-                    });
-                }
-            }
-
-            if steps_taken > distance {
-                break;
-            }
+        Raycast {
+            world: self,
+            pos,
+            dir,
+            distance,
+            t_pos,
+            t_step,
+            t_delta,
+            t_max,
+            steps_taken: 0,
         }
-
-        None
     }
 
     pub fn in_bounds(&self, x: usize, y: usize, z: usize) -> bool {
@@ -98,11 +128,71 @@ impl World {
         x < self.size.0 && y < self.size.1 && z < self.size.2
     }
 
+    /// sets a tile and updates the light around it.
     pub fn set(&mut self, x: usize, y: usize, z: usize, tile: Tile) {
+        let index = self.offset_of(x, y, z);
+        if self.tiles[index] != tile {
+            self.tiles[index] = tile;
+            self.light.on_tile_changed(&self.tiles, x, y, z);
+        }
+    }
+
+    /// sets a tile without updating the light, for bulk edits which call `relight` once afterwards.
+    pub fn set_unlit(&mut self, x: usize, y: usize, z: usize, tile: Tile) {
         let index = self.offset_of(x, y, z);
         self.tiles[index] = tile;
     }
 
+    /// sets many tiles, returning what changed. tiles outside the world are ignored.
+    pub fn set_many(
+        &mut self,
+        tiles: impl IntoIterator<Item = ((usize, usize, usize), Tile)>,
+    ) -> Vec<TileChange> {
+        let changes: Vec<TileChange> = tiles
+            .into_iter()
+            .filter_map(|((x, y, z), tile)| {
+                let old = self.get(x, y, z)?;
+                (old != tile).then_some(TileChange {
+                    position: (x, y, z),
+                    old,
+                    new: tile,
+                })
+            })
+            .collect();
+
+        if changes.len() > RELIGHT_THRESHOLD {
+            for change in &changes {
+                let (x, y, z) = change.position;
+                self.set_unlit(x, y, z, change.new);
+            }
+            self.relight();
+        } else {
+            for change in &changes {
+                let (x, y, z) = change.position;
+                self.set(x, y, z, change.new);
+            }
+        }
+
+        changes
+    }
+
+    /// recomputes the light of the whole world.
+    pub fn relight(&mut self) {
+        self.light.relight(&self.tiles);
+    }
+
+    /// returns the sky light and block light levels at a position, from 0 to 15.
+    ///
+    /// everything outside the world is open to the sky.
+    pub fn light(&self, x: i32, y: i32, z: i32) -> (u8, u8) {
+        if x < 0 || y < 0 || z < 0 || !self.in_bounds(x as usize, y as usize, z as usize) {
+            return (MAX_LIGHT, 0);
+        }
+
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+        (self.light.sky(x, y, z), self.light.block(x, y, z))
+    }
+
     pub fn get(&self, x: usize, y: usize, z: usize) -> Option<Tile> {
         if !self.in_bounds(x, y, z) {
             return None;
@@ -124,7 +214,107 @@ impl World {
         self.size.2
     }
 
+    /// returns free-form key / value data stored alongside the world, e.g. its name or author.
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    pub fn set_metadata(&mut self, key: &str, value: &str) {
+        self.metadata.insert(key.to_string(), value.to_string());
+    }
+
+    /// returns every tile, with x varying fastest, then y, then z.
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub(super) fn tiles_mut(&mut self) -> &mut [Tile] {
+        &mut self.tiles
+    }
+
     fn offset_of(&self, x: usize, y: usize, z: usize) -> usize {
         (self.size.0 * self.size.1 * z) + (self.size.0 * y) + x
     }
 }
+
+/// walks a ray through the world one tile at a time, yielding every non-empty tile it enters.
+pub struct Raycast<'a> {
+    world: &'a World,
+    pos: Vec3,
+    dir: Vec3,
+    distance: usize,
+    t_pos: IVec3,
+    t_step: IVec3,
+    t_delta: Vec3,
+    t_max: Vec3,
+    steps_taken: usize,
+}
+
+impl Iterator for Raycast<'_> {
+    type Item = RaycastResult;
+
+    fn next(&mut self) -> Option<RaycastResult> {
+        while self.steps_taken <= self.distance {
+            self.steps_taken += 1;
+
+            let (t_pos, t_step, t_delta, t_max) =
+                (&mut self.t_pos, self.t_step, self.t_delta, &mut self.t_max);
+
+            // remember which axis was crossed, and how far along the ray, to find the hit face.
+            let (axis, t) = if t_max.x < t_max.y || t_max.z < t_max.y {
+                if t_max.x < t_max.z {
+                    let t = t_max.x;
+                    t_max.x += t_delta.x;
+                    t_pos.x += t_step.x;
+                    (0, t)
+                } else {
+                    let t = t_max.z;
+                    t_max.z += t_delta.z;
+                    t_pos.z += t_step.z;
+                    (2, t)
+                }
+            } else {
+                let t = t_max.y;
+                t_max.y += t_delta.y;
+                t_pos.y += t_step.y;
+                (1, t)
+            };
+
+            let position = *t_pos;
+            let tile = self.world.get(
+                position.x as usize,
+                position.y as usize,
+                position.z as usize,
+            );
+            if let Some(tile) = tile {
+                if tile != 0 {
+                    let hit = self.pos + self.dir * t;
+                    let (normal, uv) = match axis {
+                        0 => (
+                            Vec3::new(-t_step.x as f32, 0.0, 0.0),
+                            Vec2::new(hit.z.fract(), hit.y.fract()),
+                        ),
+                        1 => (
+                            Vec3::new(0.0, -t_step.y as f32, 0.0),
+                            Vec2::new(hit.x.fract(), hit.z.fract()),
+                        ),
+                        _ => (
+                            Vec3::new(0.0, 0.0, -t_step.z as f32),
+                            Vec2::new(hit.x.fract(), hit.y.fract()),
+                        ),
+                    };
+
+                    return Some(RaycastResult {
+                        tile,
+                        uv,
+                        normal,
+                        position,
+                        distance: t,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
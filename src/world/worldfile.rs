@@ -0,0 +1,300 @@
+//! the `.arc` world file format.
+//!
+//! all numbers are little endian. a file starts with a header:
+//!
+//! | bytes | contents                                   |
+//! |-------|--------------------------------------------|
+//! | 4     | the magic bytes `ARCW`                     |
+//! | 2     | major version                              |
+//! | 2     | minor version                              |
+//!
+//! followed by sections until the end of the file, each being a four byte tag, a `u32` length and
+//! then that many bytes of payload:
+//!
+//! * `SIZE` : the world's dimensions, as three `u32`s.
+//! * `PALT` : a `u16` count followed by that many tiles. tile data refers to tiles by their index
+//!   in this palette.
+//! * `TILE` : run-length encoded palette indices, as pairs of a `u16` run length and a `u8` index,
+//!   with x varying fastest, then y, then z.
+//! * `META` : a `u32` count followed by that many key / value pairs, each string being a `u16`
+//!   length followed by utf-8 bytes.
+//!
+//! readers skip sections they don't recognise, so new sections can be added in a minor version.
+//! a new major version means older readers can't load the file at all.
+
+use super::{Tile, World};
+use crate::error::ErrorType;
+use crate::reader::{corrupt, Reader};
+use std::{fs, path::Path};
+
+const MAGIC: &[u8; 4] = b"ARCW";
+const VERSION_MAJOR: u16 = 1;
+const VERSION_MINOR: u16 = 0;
+
+impl World {
+    /// saves the world to a file in the `.arc` format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ErrorType> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// loads a world from a file in the `.arc` format.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<World, ErrorType> {
+        World::from_bytes(&fs::read(path)?)
+    }
+
+    /// encodes the world in the `.arc` format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION_MAJOR.to_le_bytes());
+        bytes.extend_from_slice(&VERSION_MINOR.to_le_bytes());
+
+        let mut size = Vec::new();
+        for dimension in [self.size_x(), self.size_y(), self.size_z()] {
+            size.extend_from_slice(&(dimension as u32).to_le_bytes());
+        }
+        write_section(&mut bytes, b"SIZE", &size);
+
+        write_tiles(&mut bytes, self.tiles());
+
+        if !self.metadata().is_empty() {
+            let mut metadata = (self.metadata().len() as u32).to_le_bytes().to_vec();
+            for (key, value) in self.metadata() {
+                write_string(&mut metadata, key);
+                write_string(&mut metadata, value);
+            }
+            write_section(&mut bytes, b"META", &metadata);
+        }
+
+        bytes
+    }
+
+    /// decodes a world in the `.arc` format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<World, ErrorType> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(4)? != MAGIC {
+            return Err(corrupt("not a world file"));
+        }
+        let major = reader.u16()?;
+        let minor = reader.u16()?;
+        if major > VERSION_MAJOR {
+            return Err(ErrorType::UnsupportedVersion(major, minor));
+        }
+
+        let mut world: Option<World> = None;
+        let mut palette: Option<Vec<Tile>> = None;
+        let mut tiles_read = false;
+        let mut metadata = Vec::new();
+
+        while !reader.is_empty() {
+            let tag = reader.take(4)?;
+            let length = reader.u32()? as usize;
+            let mut section = Reader::new(reader.take(length)?);
+
+            match tag {
+                b"SIZE" => {
+                    if world.is_some() {
+                        return Err(corrupt("duplicate size section"));
+                    }
+                    let size_x = section.u32()? as usize;
+                    let size_y = section.u32()? as usize;
+                    let size_z = section.u32()? as usize;
+                    if !World::is_valid_size(size_x, size_y, size_z) {
+                        return Err(corrupt("world dimensions are out of range"));
+                    }
+                    world = Some(World::with_size(size_x, size_y, size_z));
+                }
+                b"PALT" => {
+                    let count = section.u16()? as usize;
+                    palette = Some(section.take(count)?.to_vec());
+                }
+                b"TILE" => {
+                    let world = world.as_mut().ok_or_else(|| corrupt("tiles before size"))?;
+                    let palette = palette
+                        .as_ref()
+                        .ok_or_else(|| corrupt("tiles before palette"))?;
+                    read_tiles(&mut section, palette, world.tiles_mut())?;
+                    tiles_read = true;
+                }
+                b"META" => {
+                    let count = section.u32()?;
+                    for _ in 0..count {
+                        let key = section.string()?;
+                        let value = section.string()?;
+                        metadata.push((key, value));
+                    }
+                }
+                _ => {} // unknown sections are from newer minor versions, and safe to skip.
+            }
+        }
+
+        let mut world = world.ok_or_else(|| corrupt("missing size section"))?;
+        if !tiles_read {
+            return Err(corrupt("missing tile section"));
+        }
+        world.relight();
+        for (key, value) in metadata {
+            world.set_metadata(&key, &value);
+        }
+
+        Ok(world)
+    }
+}
+
+/// reads a `TILE` section into `tiles`, which must be exactly as long as the section describes.
+pub(super) fn read_tiles(
+    reader: &mut Reader,
+    palette: &[Tile],
+    tiles: &mut [Tile],
+) -> Result<(), ErrorType> {
+    let mut offset = 0;
+    while !reader.is_empty() {
+        let run = reader.u16()? as usize;
+        let index = reader.u8()? as usize;
+
+        let tile = *palette
+            .get(index)
+            .ok_or_else(|| corrupt("tile refers to a palette entry that doesn't exist"))?;
+        let end = offset + run;
+        if end > tiles.len() {
+            return Err(corrupt("more tiles than fit in the world"));
+        }
+
+        tiles[offset..end].fill(tile);
+        offset = end;
+    }
+
+    if offset != tiles.len() {
+        return Err(corrupt("fewer tiles than fit in the world"));
+    }
+
+    Ok(())
+}
+
+/// writes the `PALT` and `TILE` sections for some tiles.
+pub(super) fn write_tiles(bytes: &mut Vec<u8>, tiles: &[Tile]) {
+    let mut palette: Vec<Tile> = Vec::new();
+    for tile in tiles {
+        if !palette.contains(tile) {
+            palette.push(*tile);
+        }
+    }
+    palette.sort_unstable();
+
+    let mut palette_section = (palette.len() as u16).to_le_bytes().to_vec();
+    palette_section.extend_from_slice(&palette);
+    write_section(bytes, b"PALT", &palette_section);
+
+    let mut runs_section = Vec::new();
+    let mut runs = tiles.iter().peekable();
+    while let Some(tile) = runs.next() {
+        let mut run: u16 = 1;
+        while run < u16::MAX && runs.peek() == Some(&tile) {
+            runs.next();
+            run += 1;
+        }
+
+        let index = palette.binary_search(tile).unwrap_or(0) as u8;
+        runs_section.extend_from_slice(&run.to_le_bytes());
+        runs_section.push(index);
+    }
+    write_section(bytes, b"TILE", &runs_section);
+}
+
+pub(super) fn write_section(bytes: &mut Vec<u8>, tag: &[u8; 4], payload: &[u8]) {
+    bytes.extend_from_slice(tag);
+    bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(payload);
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    let mut length = string.len().min(u16::MAX as usize);
+    while !string.is_char_boundary(length) {
+        length -= 1;
+    }
+    let string = &string.as_bytes()[..length];
+    bytes.extend_from_slice(&(string.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(string);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::testing::{assert_truncations_are_corrupt, is_corrupt};
+    use crate::world::tiles;
+
+    fn sample_world() -> World {
+        let mut world = World::with_size(5, 4, 3);
+        world.set(0, 0, 0, tiles::STONE);
+        world.set(4, 3, 2, tiles::GRASS);
+        world.set(2, 1, 1, tiles::WATER);
+        world
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut world = sample_world();
+        world.set_metadata("name", "test");
+        let loaded = World::from_bytes(&world.to_bytes()).unwrap();
+
+        assert_eq!(
+            (loaded.size_x(), loaded.size_y(), loaded.size_z()),
+            (5, 4, 3)
+        );
+        assert_eq!(loaded.tiles(), world.tiles());
+        assert_eq!(loaded.metadata(), world.metadata());
+    }
+
+    #[test]
+    fn truncated_files_are_corrupt() {
+        let bytes = sample_world().to_bytes();
+        assert_truncations_are_corrupt(&bytes, &[], World::from_bytes);
+    }
+
+    #[test]
+    fn corrupt_files_are_rejected() {
+        let mut bytes = sample_world().to_bytes();
+        bytes[0] = b'X';
+        assert!(is_corrupt(World::from_bytes(&bytes)));
+
+        // points the first run at a palette entry past the end of the palette.
+        let mut bytes = sample_world().to_bytes();
+        let tile = bytes.windows(4).position(|tag| tag == b"TILE").unwrap();
+        bytes[tile + 8 + 2] = 200;
+        assert!(is_corrupt(World::from_bytes(&bytes)));
+
+        let mut bytes = sample_world().to_bytes();
+        let size = bytes.windows(4).position(|tag| tag == b"SIZE").unwrap();
+        bytes[size + 8..size + 12].copy_from_slice(&0u32.to_le_bytes());
+        assert!(is_corrupt(World::from_bytes(&bytes)));
+
+        // a second size after the tiles would otherwise replace the world with an empty one.
+        let mut bytes = sample_world().to_bytes();
+        let size = bytes.windows(4).position(|tag| tag == b"SIZE").unwrap();
+        let section = bytes[size..size + 20].to_vec();
+        bytes.extend_from_slice(&section);
+        assert!(is_corrupt(World::from_bytes(&bytes)));
+    }
+
+    #[test]
+    fn newer_major_versions_are_unsupported() {
+        let mut bytes = sample_world().to_bytes();
+        bytes[4..6].copy_from_slice(&(VERSION_MAJOR + 1).to_le_bytes());
+        assert_eq!(
+            World::from_bytes(&bytes).err(),
+            Some(ErrorType::UnsupportedVersion(
+                VERSION_MAJOR + 1,
+                VERSION_MINOR
+            ))
+        );
+    }
+
+    #[test]
+    fn unknown_sections_are_skipped() {
+        let mut bytes = sample_world().to_bytes();
+        write_section(&mut bytes, b"NEWS", &[1, 2, 3]);
+        assert!(World::from_bytes(&bytes).is_ok());
+    }
+}
@@ -0,0 +1,470 @@
+use super::noise::{hash, Noise, NoiseKind};
+use crate::math::Rng;
+use crate::world::{tiles, Tile, World};
+
+/// the kind of terrain at a column, chosen from temperature, moisture and mountain noise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biome {
+    Plains,
+    Forest,
+    Desert,
+    Mountains,
+    Tundra,
+}
+
+impl Biome {
+    /// the tile on top of each column.
+    pub fn surface(&self) -> Tile {
+        match self {
+            Biome::Plains | Biome::Forest => tiles::GRASS,
+            Biome::Desert => tiles::SAND,
+            Biome::Mountains => tiles::STONE,
+            Biome::Tundra => tiles::SNOW,
+        }
+    }
+
+    /// the tiles between the surface and the stone beneath it.
+    pub fn filler(&self) -> Tile {
+        match self {
+            Biome::Plains | Biome::Forest | Biome::Tundra => tiles::DIRT,
+            Biome::Desert => tiles::SAND,
+            Biome::Mountains => tiles::GRAVEL,
+        }
+    }
+
+    /// the chance of a tree growing on any given column.
+    pub fn tree_chance(&self) -> f32 {
+        match self {
+            Biome::Plains => 0.005,
+            Biome::Forest => 0.04,
+            Biome::Tundra => 0.002,
+            Biome::Desert | Biome::Mountains => 0.0,
+        }
+    }
+}
+
+/// veins of a tile scattered through stone.
+#[derive(Debug, Clone)]
+pub struct OreConfig {
+    pub tile: Tile,
+    /// how many veins start in each chunk.
+    pub veins_per_chunk: u32,
+    /// how many tiles each vein tries to place.
+    pub vein_size: u32,
+    /// veins only start below this height.
+    pub max_height: usize,
+}
+
+/// everything that decides what a generated world looks like; the same config always generates the same world.
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    pub seed: u64,
+    pub noise: NoiseKind,
+    /// columns lower than this are flooded with water.
+    pub sea_level: usize,
+    /// the average height of the surface.
+    pub base_height: f32,
+    /// how far the surface strays above and below `base_height` outside of mountains.
+    pub height_variation: f32,
+    /// the frequency of the heightmap noise, in cycles per tile.
+    pub terrain_frequency: f32,
+    pub octaves: u32,
+    /// the frequency of the noise choosing biomes; lower makes bigger biomes.
+    pub biome_frequency: f32,
+    pub caves: bool,
+    pub cave_frequency: f32,
+    /// 3d noise above this value is carved out into caves, so higher makes fewer caves.
+    pub cave_threshold: f32,
+    pub ores: Vec<OreConfig>,
+    pub trees: bool,
+    /// the width and depth of a chunk, which is the unit features are scattered in.
+    pub chunk_size: usize,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            noise: NoiseKind::Simplex,
+            sea_level: 12,
+            base_height: 14.0,
+            height_variation: 4.0,
+            terrain_frequency: 1.0 / 48.0,
+            octaves: 4,
+            biome_frequency: 1.0 / 128.0,
+            caves: true,
+            cave_frequency: 1.0 / 16.0,
+            cave_threshold: 0.55,
+            ores: vec![
+                OreConfig {
+                    tile: tiles::COAL_ORE,
+                    veins_per_chunk: 4,
+                    vein_size: 8,
+                    max_height: 24,
+                },
+                OreConfig {
+                    tile: tiles::IRON_ORE,
+                    veins_per_chunk: 2,
+                    vein_size: 6,
+                    max_height: 14,
+                },
+                OreConfig {
+                    tile: tiles::GOLD_ORE,
+                    veins_per_chunk: 1,
+                    vein_size: 4,
+                    max_height: 8,
+                },
+            ],
+            trees: true,
+            chunk_size: 16,
+        }
+    }
+}
+
+/// an axis-aligned region of columns that generation is allowed to write to.
+#[derive(Clone, Copy)]
+struct Region {
+    min_x: usize,
+    min_z: usize,
+    max_x: usize,
+    max_z: usize,
+}
+
+impl Region {
+    fn contains(&self, x: i32, z: i32) -> bool {
+        x >= self.min_x as i32
+            && z >= self.min_z as i32
+            && x < self.max_x as i32
+            && z < self.max_z as i32
+    }
+}
+
+/// fills worlds with terrain from a `GeneratorConfig`.
+///
+/// generation is deterministic and works a chunk of columns at a time: features such as trees
+/// which cross chunk borders are generated from every neighbouring chunk and clipped, so a world
+/// generated chunk by chunk in any order matches one generated all at once.
+pub struct Generator {
+    config: GeneratorConfig,
+    height_noise: Noise,
+    mountain_noise: Noise,
+    temperature_noise: Noise,
+    moisture_noise: Noise,
+    cave_noise: Noise,
+}
+
+impl Generator {
+    pub fn new(config: GeneratorConfig) -> Self {
+        let seed = config.seed;
+        let kind = config.noise;
+
+        Self {
+            height_noise: Noise::new(hash(seed, 1, 0, 0), kind),
+            mountain_noise: Noise::new(hash(seed, 2, 0, 0), kind),
+            temperature_noise: Noise::new(hash(seed, 3, 0, 0), kind),
+            moisture_noise: Noise::new(hash(seed, 4, 0, 0), kind),
+            cave_noise: Noise::new(hash(seed, 5, 0, 0), kind),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &GeneratorConfig {
+        &self.config
+    }
+
+    /// returns how mountainous a column is, between 0.0 and 1.0.
+    fn mountainness(&self, x: i32, z: i32) -> f32 {
+        let frequency = self.config.biome_frequency;
+        let value = self
+            .mountain_noise
+            .fractal2(x as f32 * frequency, z as f32 * frequency, 2);
+        ((value - 0.1) * 2.5).clamp(0.0, 1.0)
+    }
+
+    pub fn biome_at(&self, x: i32, z: i32) -> Biome {
+        let frequency = self.config.biome_frequency;
+        let temperature =
+            self.temperature_noise
+                .fractal2(x as f32 * frequency, z as f32 * frequency, 2);
+        let moisture = self
+            .moisture_noise
+            .fractal2(x as f32 * frequency, z as f32 * frequency, 2);
+
+        if self.mountainness(x, z) > 0.6 {
+            Biome::Mountains
+        } else if temperature < -0.35 {
+            Biome::Tundra
+        } else if temperature > 0.3 && moisture < 0.0 {
+            Biome::Desert
+        } else if moisture > 0.15 {
+            Biome::Forest
+        } else {
+            Biome::Plains
+        }
+    }
+
+    /// returns the height of the topmost solid tile in a column, before caves are carved.
+    pub fn height_at(&self, x: i32, z: i32) -> i32 {
+        let frequency = self.config.terrain_frequency;
+        let value = self.height_noise.fractal2(
+            x as f32 * frequency,
+            z as f32 * frequency,
+            self.config.octaves,
+        );
+
+        // mountains stretch the same noise up to four times as tall.
+        let scale = 1.0 + 3.0 * self.mountainness(x, z);
+        let height = self.config.base_height + value * self.config.height_variation * scale;
+        height.round().max(1.0) as i32
+    }
+
+    /// returns the tile a column's surface would have.
+    fn surface_at(&self, x: i32, z: i32, height: i32) -> Tile {
+        let biome = self.biome_at(x, z);
+        if height <= self.config.sea_level as i32 && biome != Biome::Tundra {
+            tiles::SAND // beaches and sea beds.
+        } else {
+            biome.surface()
+        }
+    }
+
+    /// generates every chunk of the world.
+    pub fn generate(&self, world: &mut World) {
+        let chunk_size = self.config.chunk_size.max(1);
+        let chunks_x = world.size_x().div_ceil(chunk_size);
+        let chunks_z = world.size_z().div_ceil(chunk_size);
+
+        for chunk_z in 0..chunks_z {
+            for chunk_x in 0..chunks_x {
+                self.fill_chunk(world, chunk_x, chunk_z);
+            }
+        }
+        world.relight();
+    }
+
+    /// generates the columns of a single chunk, overwriting whatever was there.
+    pub fn generate_chunk(&self, world: &mut World, chunk_x: usize, chunk_z: usize) {
+        self.fill_chunk(world, chunk_x, chunk_z);
+        world.relight();
+    }
+
+    /// writes a chunk's tiles without updating the world's light.
+    fn fill_chunk(&self, world: &mut World, chunk_x: usize, chunk_z: usize) {
+        let chunk_size = self.config.chunk_size.max(1);
+        let region = Region {
+            min_x: chunk_x * chunk_size,
+            min_z: chunk_z * chunk_size,
+            max_x: ((chunk_x + 1) * chunk_size).min(world.size_x()),
+            max_z: ((chunk_z + 1) * chunk_size).min(world.size_z()),
+        };
+
+        for z in region.min_z..region.max_z {
+            for x in region.min_x..region.max_x {
+                self.generate_column(world, x, z);
+            }
+        }
+
+        // features from neighbouring chunks can spill into this one.
+        for neighbour_z in chunk_z as i32 - 1..=chunk_z as i32 + 1 {
+            for neighbour_x in chunk_x as i32 - 1..=chunk_x as i32 + 1 {
+                for (index, ore) in self.config.ores.iter().enumerate() {
+                    self.scatter_ore(world, region, ore, index, neighbour_x, neighbour_z);
+                }
+                if self.config.trees {
+                    self.scatter_trees(world, region, neighbour_x, neighbour_z);
+                }
+            }
+        }
+    }
+
+    fn generate_column(&self, world: &mut World, x: usize, z: usize) {
+        let (wx, wz) = (x as i32, z as i32);
+        let height = self.height_at(wx, wz);
+        let biome = self.biome_at(wx, wz);
+        let surface = self.surface_at(wx, wz, height);
+        let sea_level = self.config.sea_level as i32;
+        let frequency = self.config.cave_frequency;
+
+        for y in 0..world.size_y() {
+            let wy = y as i32;
+            let mut tile = if wy > height {
+                if wy <= sea_level {
+                    tiles::WATER
+                } else {
+                    tiles::AIR
+                }
+            } else if wy == height {
+                surface
+            } else if wy > height - 4 {
+                if surface == tiles::SAND {
+                    tiles::SAND
+                } else {
+                    biome.filler()
+                }
+            } else {
+                tiles::STONE
+            };
+
+            // caves are kept off the bottom layer and away from the surface under water, so they don't flood.
+            let carveable = wy > 0 && wy <= height && (height > sea_level || wy < height - 3);
+            if self.config.caves && carveable {
+                let cave = self.cave_noise.fractal3(
+                    wx as f32 * frequency,
+                    wy as f32 * frequency * 1.5,
+                    wz as f32 * frequency,
+                    2,
+                );
+                if cave > self.config.cave_threshold {
+                    tile = tiles::AIR;
+                }
+            }
+
+            world.set_unlit(x, y, z, tile);
+        }
+    }
+
+    fn scatter_ore(
+        &self,
+        world: &mut World,
+        region: Region,
+        ore: &OreConfig,
+        ore_index: usize,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) {
+        let chunk_size = self.config.chunk_size.max(1) as i32;
+        let mut rng = Rng::new(hash(
+            self.config.seed ^ 0x6f72_6573,
+            chunk_x,
+            ore_index as i32,
+            chunk_z,
+        ));
+
+        for _ in 0..ore.veins_per_chunk {
+            let mut x = chunk_x * chunk_size + rng.below(chunk_size as u32) as i32;
+            let mut z = chunk_z * chunk_size + rng.below(chunk_size as u32) as i32;
+            let mut y = rng.below(ore.max_height.max(1) as u32) as i32;
+
+            for _ in 0..ore.vein_size {
+                if region.contains(x, z) && y >= 0 && (y as usize) < world.size_y() {
+                    let (ux, uy, uz) = (x as usize, y as usize, z as usize);
+                    if world.get(ux, uy, uz) == Some(tiles::STONE) {
+                        world.set_unlit(ux, uy, uz, ore.tile);
+                    }
+                }
+
+                match rng.below(6) {
+                    0 => x += 1,
+                    1 => x -= 1,
+                    2 => y += 1,
+                    3 => y -= 1,
+                    4 => z += 1,
+                    _ => z -= 1,
+                }
+            }
+        }
+    }
+
+    fn scatter_trees(&self, world: &mut World, region: Region, chunk_x: i32, chunk_z: i32) {
+        let chunk_size = self.config.chunk_size.max(1) as i32;
+        let mut rng = Rng::new(hash(self.config.seed ^ 0x7472_6565, chunk_x, 0, chunk_z));
+
+        for local_z in 0..chunk_size {
+            for local_x in 0..chunk_size {
+                // always draw both numbers so every column consumes the same amount of randomness.
+                let roll = rng.unit();
+                let trunk_height = 4 + rng.below(3) as i32;
+
+                let (x, z) = (
+                    chunk_x * chunk_size + local_x,
+                    chunk_z * chunk_size + local_z,
+                );
+                if roll >= self.biome_at(x, z).tree_chance() {
+                    continue;
+                }
+
+                let height = self.height_at(x, z);
+                if height <= self.config.sea_level as i32
+                    || self.surface_at(x, z, height) != tiles::GRASS
+                {
+                    continue;
+                }
+
+                self.place_tree(world, region, x, height + 1, z, trunk_height);
+            }
+        }
+    }
+
+    fn place_tree(
+        &self,
+        world: &mut World,
+        region: Region,
+        x: i32,
+        y: i32,
+        z: i32,
+        trunk_height: i32,
+    ) {
+        let top = y + trunk_height;
+
+        // a blob of leaves around the top of the trunk, trimmed at the corners.
+        for dy in -2..=1 {
+            let radius: i32 = if dy == 1 { 1 } else { 2 };
+            for dz in -radius..=radius {
+                for dx in -radius..=radius {
+                    if dx.abs() == radius && dz.abs() == radius && radius > 1 {
+                        continue;
+                    }
+                    self.place_feature(world, region, x + dx, top + dy, z + dz, tiles::LEAVES);
+                }
+            }
+        }
+
+        for trunk_y in y..top {
+            self.place_feature(world, region, x, trunk_y, z, tiles::WOOD);
+        }
+    }
+
+    /// places a feature tile if it is inside of the region and would replace air or leaves.
+    fn place_feature(&self, world: &mut World, region: Region, x: i32, y: i32, z: i32, tile: Tile) {
+        if !region.contains(x, z) || y < 0 {
+            return;
+        }
+
+        let (ux, uy, uz) = (x as usize, y as usize, z as usize);
+        if matches!(
+            world.get(ux, uy, uz),
+            Some(tiles::AIR) | Some(tiles::LEAVES)
+        ) {
+            world.set_unlit(ux, uy, uz, tile);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_by_chunk_matches_all_at_once() {
+        let generator = Generator::new(GeneratorConfig {
+            seed: 7,
+            chunk_size: 8,
+            ..GeneratorConfig::default()
+        });
+
+        // not a whole number of chunks across, so the last ones are cut short.
+        let mut whole = World::with_size(36, 32, 36);
+        generator.generate(&mut whole);
+        assert!(whole.tiles().contains(&tiles::LEAVES));
+
+        // backwards, so features spilling in from later chunks are written first.
+        let mut chunked = World::with_size(36, 32, 36);
+        for chunk_z in (0..5).rev() {
+            for chunk_x in (0..5).rev() {
+                generator.generate_chunk(&mut chunked, chunk_x, chunk_z);
+            }
+        }
+
+        assert!(whole.tiles() == chunked.tiles());
+    }
+}
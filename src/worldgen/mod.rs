@@ -0,0 +1,5 @@
+mod generator;
+mod noise;
+
+pub use generator::{Biome, Generator, GeneratorConfig, OreConfig};
+pub use noise::{Noise, NoiseKind};
@@ -0,0 +1,284 @@
+/// which kind of gradient or lattice noise to sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind {
+    /// random values at lattice points, smoothly interpolated. blocky, but cheap.
+    Value,
+    /// random gradients at lattice points.
+    Perlin,
+    /// gradients on a simplex grid, with fewer directional artifacts than perlin.
+    Simplex,
+}
+
+/// seeded, deterministic coherent noise, returning values roughly between -1.0 and 1.0.
+#[derive(Debug, Clone, Copy)]
+pub struct Noise {
+    seed: u64,
+    kind: NoiseKind,
+}
+
+impl Noise {
+    pub fn new(seed: u64, kind: NoiseKind) -> Self {
+        Self { seed, kind }
+    }
+
+    pub fn sample2(&self, x: f32, y: f32) -> f32 {
+        match self.kind {
+            NoiseKind::Value => self.value2(x, y),
+            NoiseKind::Perlin => self.perlin2(x, y),
+            NoiseKind::Simplex => self.simplex2(x, y),
+        }
+    }
+
+    pub fn sample3(&self, x: f32, y: f32, z: f32) -> f32 {
+        match self.kind {
+            NoiseKind::Value => self.value3(x, y, z),
+            NoiseKind::Perlin => self.perlin3(x, y, z),
+            NoiseKind::Simplex => self.simplex3(x, y, z),
+        }
+    }
+
+    /// sums `octaves` layers of noise, each at double the frequency and half the amplitude of the last.
+    ///
+    /// the result is normalised back to roughly -1.0 to 1.0.
+    pub fn fractal2(&self, x: f32, y: f32, octaves: u32) -> f32 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut max = 0.0;
+        for octave in 0..octaves.max(1) {
+            let layer = Noise::new(self.seed.wrapping_add(octave as u64), self.kind);
+            total += layer.sample2(x * frequency, y * frequency) * amplitude;
+            max += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        total / max
+    }
+
+    pub fn fractal3(&self, x: f32, y: f32, z: f32, octaves: u32) -> f32 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut max = 0.0;
+        for octave in 0..octaves.max(1) {
+            let layer = Noise::new(self.seed.wrapping_add(octave as u64), self.kind);
+            total += layer.sample3(x * frequency, y * frequency, z * frequency) * amplitude;
+            max += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        total / max
+    }
+
+    fn value2(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+        let (tx, ty) = (fade(x - x0 as f32), fade(y - y0 as f32));
+        let corner = |dx, dy| unit(hash(self.seed, x0 + dx, y0 + dy, 0)) * 2.0 - 1.0;
+
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), tx),
+            lerp(corner(0, 1), corner(1, 1), tx),
+            ty,
+        )
+    }
+
+    fn value3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x0, y0, z0) = (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+        let (tx, ty, tz) = (
+            fade(x - x0 as f32),
+            fade(y - y0 as f32),
+            fade(z - z0 as f32),
+        );
+        let corner = |dx, dy, dz| unit(hash(self.seed, x0 + dx, y0 + dy, z0 + dz)) * 2.0 - 1.0;
+
+        lerp(
+            lerp(
+                lerp(corner(0, 0, 0), corner(1, 0, 0), tx),
+                lerp(corner(0, 1, 0), corner(1, 1, 0), tx),
+                ty,
+            ),
+            lerp(
+                lerp(corner(0, 0, 1), corner(1, 0, 1), tx),
+                lerp(corner(0, 1, 1), corner(1, 1, 1), tx),
+                ty,
+            ),
+            tz,
+        )
+    }
+
+    fn perlin2(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let corner = |dx: i32, dy: i32| {
+            let (gx, gy) = gradient2(hash(self.seed, x0 + dx, y0 + dy, 0));
+            gx * (fx - dx as f32) + gy * (fy - dy as f32)
+        };
+
+        let (tx, ty) = (fade(fx), fade(fy));
+        // gradients are unit length, so the largest possible value is sqrt(0.5).
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), tx),
+            lerp(corner(0, 1), corner(1, 1), tx),
+            ty,
+        ) * std::f32::consts::SQRT_2
+    }
+
+    fn perlin3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x0, y0, z0) = (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+        let (fx, fy, fz) = (x - x0 as f32, y - y0 as f32, z - z0 as f32);
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            let (gx, gy, gz) = gradient3(hash(self.seed, x0 + dx, y0 + dy, z0 + dz));
+            gx * (fx - dx as f32) + gy * (fy - dy as f32) + gz * (fz - dz as f32)
+        };
+
+        let (tx, ty, tz) = (fade(fx), fade(fy), fade(fz));
+        lerp(
+            lerp(
+                lerp(corner(0, 0, 0), corner(1, 0, 0), tx),
+                lerp(corner(0, 1, 0), corner(1, 1, 0), tx),
+                ty,
+            ),
+            lerp(
+                lerp(corner(0, 0, 1), corner(1, 0, 1), tx),
+                lerp(corner(0, 1, 1), corner(1, 1, 1), tx),
+                ty,
+            ),
+            tz,
+        )
+    }
+
+    fn simplex2(&self, x: f32, y: f32) -> f32 {
+        const F2: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+        const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+
+        // skew into simplex space to find which cell the point is in.
+        let s = (x + y) * F2;
+        let (i, j) = ((x + s).floor() as i32, (y + s).floor() as i32);
+        let t = (i + j) as f32 * G2;
+        let (x0, y0) = (x - (i as f32 - t), y - (j as f32 - t));
+
+        // which of the cell's two triangles the point is in.
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+
+        let offsets = [
+            (0, 0, x0, y0),
+            (i1, j1, x0 - i1 as f32 + G2, y0 - j1 as f32 + G2),
+            (1, 1, x0 - 1.0 + 2.0 * G2, y0 - 1.0 + 2.0 * G2),
+        ];
+
+        let mut total = 0.0;
+        for (di, dj, cx, cy) in offsets {
+            let falloff = 0.5 - cx * cx - cy * cy;
+            if falloff > 0.0 {
+                let (gx, gy) = gradient2(hash(self.seed, i + di, j + dj, 0));
+                total += falloff.powi(4) * (gx * cx + gy * cy);
+            }
+        }
+
+        // scales the result to roughly -1.0 to 1.0.
+        total * 70.0
+    }
+
+    fn simplex3(&self, x: f32, y: f32, z: f32) -> f32 {
+        const F3: f32 = 1.0 / 3.0;
+        const G3: f32 = 1.0 / 6.0;
+
+        let s = (x + y + z) * F3;
+        let (i, j, k) = (
+            (x + s).floor() as i32,
+            (y + s).floor() as i32,
+            (z + s).floor() as i32,
+        );
+        let t = (i + j + k) as f32 * G3;
+        let (x0, y0, z0) = (x - (i as f32 - t), y - (j as f32 - t), z - (k as f32 - t));
+
+        // which of the cell's six tetrahedra the point is in.
+        let ((i1, j1, k1), (i2, j2, k2)) = if x0 >= y0 {
+            if y0 >= z0 {
+                ((1, 0, 0), (1, 1, 0))
+            } else if x0 >= z0 {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if y0 < z0 {
+            ((0, 0, 1), (0, 1, 1))
+        } else if x0 < z0 {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+
+        let corners = [
+            (0, 0, 0, 0.0),
+            (i1, j1, k1, G3),
+            (i2, j2, k2, 2.0 * G3),
+            (1, 1, 1, 3.0 * G3),
+        ];
+
+        let mut total = 0.0;
+        for (di, dj, dk, g) in corners {
+            let cx = x0 - di as f32 + g;
+            let cy = y0 - dj as f32 + g;
+            let cz = z0 - dk as f32 + g;
+            let falloff = 0.6 - cx * cx - cy * cy - cz * cz;
+            if falloff > 0.0 {
+                let (gx, gy, gz) = gradient3(hash(self.seed, i + di, j + dj, k + dk));
+                total += falloff.powi(4) * (gx * cx + gy * cy + gz * cz);
+            }
+        }
+
+        total * 32.0
+    }
+}
+
+/// hashes a seed and lattice coordinate into 64 well-mixed bits.
+pub fn hash(seed: u64, x: i32, y: i32, z: i32) -> u64 {
+    let mut h = seed
+        ^ (x as u32 as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ (y as u32 as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f)
+        ^ (z as u32 as u64).wrapping_mul(0x1656_67b1_9e37_79f9);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^ (h >> 33)
+}
+
+/// maps a hash to a value between 0.0 and 1.0.
+fn unit(hash: u64) -> f32 {
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn gradient2(hash: u64) -> (f32, f32) {
+    let angle = unit(hash) * std::f32::consts::TAU;
+    (angle.cos(), angle.sin())
+}
+
+fn gradient3(hash: u64) -> (f32, f32, f32) {
+    // the twelve edge midpoints of a cube, as used by improved perlin noise.
+    const GRADIENTS: [(f32, f32, f32); 12] = [
+        (1.0, 1.0, 0.0),
+        (-1.0, 1.0, 0.0),
+        (1.0, -1.0, 0.0),
+        (-1.0, -1.0, 0.0),
+        (1.0, 0.0, 1.0),
+        (-1.0, 0.0, 1.0),
+        (1.0, 0.0, -1.0),
+        (-1.0, 0.0, -1.0),
+        (0.0, 1.0, 1.0),
+        (0.0, -1.0, 1.0),
+        (0.0, 1.0, -1.0),
+        (0.0, -1.0, -1.0),
+    ];
+    GRADIENTS[(hash % 12) as usize]
+}
+
+/// perlin's quintic smoothstep.
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}